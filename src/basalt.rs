@@ -1,17 +1,244 @@
 use eframe::egui;
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
 
-use crate::features::note_graph::NoteGraphUi;
+#[cfg(unix)]
+use crate::features::command_socket::{run_socket_command, CommandSocket};
+use crate::features::configuration::Configuration;
+use crate::features::keymap::Keymap;
+use crate::features::note_graph::{mock_notes, NodeKind, NoteGraphAction, NoteGraphUi};
+use crate::features::notes::{
+  link_mention, rename_note, DiagnosticsPanel, LinksPanel, LinksPanelAction, NotesIndex,
+  RenameDialog, SearchPanel, TagsPanel,
+};
+use crate::features::palette::{Command, Palette, PaletteAction, PaletteMode};
+use crate::lib::graph::Graph;
+use crate::lib::import::read_graph;
+use crate::lib::owned_graph::NodeId;
 
 /// Global Basalt state
-#[derive(Default)]
 pub struct BasaltApp {
+  configuration: Configuration,
+  keymap: Keymap,
+  notes: NotesIndex,
   note_graph_ui: NoteGraphUi,
+  links_panel: LinksPanel,
+  search_panel: SearchPanel,
+  tags_panel: TagsPanel,
+  diagnostics_panel: DiagnosticsPanel,
+  palette: Palette,
+  rename_dialog: RenameDialog,
+  #[cfg(unix)]
+  command_socket: Option<CommandSocket>,
+  /// When the notes directory was last checked for changes
+  #[cfg(not(target_arch = "wasm32"))]
+  refreshed_at: Instant,
+}
+
+/// How often the notes directory is checked for changed files
+#[cfg(not(target_arch = "wasm32"))]
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+impl Default for BasaltApp {
+  fn default() -> Self {
+    Self::new(None)
+  }
+}
+
+impl BasaltApp {
+  /// Load notes from `notes_directory`, or from the one in the configuration.
+  /// A graph file is imported instead of notes. Without either, mock notes are shown.
+  pub fn new(notes_directory: Option<PathBuf>) -> Self {
+    #[cfg(not(target_arch = "wasm32"))]
+    let configuration = Configuration::load().unwrap_or_else(|error| {
+      log::error!("can't load configuration, using defaults: {error}");
+      Configuration::default()
+    });
+    #[cfg(target_arch = "wasm32")]
+    let configuration = Configuration::default();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let notes_directory = notes_directory.or_else(|| configuration.notes_directory.clone());
+    let mut notes = notes_directory
+      .and_then(|path| {
+        load_notes(&path)
+          .map_err(|error| log::error!("can't load notes from {}: {error}", path.display()))
+          .ok()
+      })
+      .unwrap_or_else(|| NotesIndex::from_notes(None, mock_notes()));
+    notes.set_case_sensitive(configuration.case_sensitive_links);
+
+    Self {
+      keymap: load_keymap(&configuration),
+      #[cfg(unix)]
+      command_socket: bind_command_socket(&configuration),
+      configuration,
+      note_graph_ui: NoteGraphUi::new(&notes.graph),
+      notes,
+      links_panel: LinksPanel::default(),
+      search_panel: SearchPanel::default(),
+      tags_panel: TagsPanel::default(),
+      diagnostics_panel: DiagnosticsPanel::default(),
+      palette: Palette::default(),
+      rename_dialog: RenameDialog::default(),
+      #[cfg(not(target_arch = "wasm32"))]
+      refreshed_at: Instant::now(),
+    }
+  }
+
+  fn run_command(&mut self, command: Command) {
+    match command {
+      Command::OpenQuickSwitcher => self.palette.toggle(PaletteMode::Notes),
+      Command::OpenPalette => self.palette.toggle(PaletteMode::Commands),
+      Command::ToggleLocalGraph => self.note_graph_ui.toggle_local_graph(),
+      Command::ReloadConfig => {
+        #[cfg(not(target_arch = "wasm32"))]
+        match Configuration::load() {
+          Ok(configuration) => {
+            self.keymap = load_keymap(&configuration);
+            #[cfg(unix)]
+            if configuration.command_socket != self.configuration.command_socket {
+              // The old socket is removed before a new one is bound, they may have the same path
+              self.command_socket = None;
+              self.command_socket = bind_command_socket(&configuration);
+            }
+            self
+              .notes
+              .set_case_sensitive(configuration.case_sensitive_links);
+            self.configuration = configuration;
+            self.note_graph_ui.configuration_changed();
+          }
+          Err(error) => log::error!("can't reload configuration: {error}"),
+        }
+      }
+      Command::FitGraph => self.note_graph_ui.fit_graph(&self.notes.graph),
+      Command::StepLayout => self.note_graph_ui.step_layout(&self.notes.graph),
+      Command::ResetLayout => self.note_graph_ui.reset_layout(&self.notes.graph),
+      Command::RenameNote => match self.note_graph_ui.open_note() {
+        Some(node_id) if self.notes.graph.get_node(node_id).kind == NodeKind::Note => {
+          self.rename_dialog.open(&self.notes, node_id);
+        }
+        _ => log::warn!("open a note to rename it"),
+      },
+      Command::FocusLeft => self.focus_towards(egui::Vec2::LEFT),
+      Command::FocusDown => self.focus_towards(egui::Vec2::DOWN),
+      Command::FocusUp => self.focus_towards(egui::Vec2::UP),
+      Command::FocusRight => self.focus_towards(egui::Vec2::RIGHT),
+    }
+  }
+
+  /// Open the note. Opening a ghost node creates the note, it's not opened if that fails.
+  fn open(&mut self, node_id: NodeId) {
+    if self.notes.graph.get_node(node_id).is_ghost() {
+      let template = &self.configuration.new_note_template;
+      if let Err(error) = self.notes.create_note(node_id, template) {
+        log::error!("can't create the note: {error}");
+        return;
+      }
+    }
+    self.note_graph_ui.open(node_id);
+  }
+
+  fn focus_towards(&mut self, direction: egui::Vec2) {
+    self
+      .note_graph_ui
+      .focus_towards(&self.notes.graph, direction);
+  }
+}
+
+/// Notes of the directory, or the graph of an imported file
+fn load_notes(path: &Path) -> io::Result<NotesIndex> {
+  if path.is_file() {
+    return Ok(NotesIndex::from_graph(read_graph(path)?));
+  }
+  NotesIndex::load(path)
+}
+
+/// Listen on the command socket of the configuration, if it has one
+#[cfg(unix)]
+fn bind_command_socket(configuration: &Configuration) -> Option<CommandSocket> {
+  let path = configuration.command_socket.as_ref()?;
+  CommandSocket::bind(path)
+    .map_err(|error| log::error!("can't listen on {}: {error}", path.display()))
+    .ok()
+}
+
+/// Keymap of the configuration, conflicting and invalid bindings are reported
+fn load_keymap(configuration: &Configuration) -> Keymap {
+  let (keymap, errors) = Keymap::new(&configuration.keymap);
+  for error in errors {
+    log::error!("{error}");
+  }
+  keymap
 }
 
 impl eframe::App for BasaltApp {
   fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    #[cfg(not(target_arch = "wasm32"))]
+    if self.refreshed_at.elapsed() >= REFRESH_INTERVAL {
+      if let Err(error) = self.notes.refresh() {
+        log::error!("can't refresh notes: {error}");
+      }
+      self.refreshed_at = Instant::now();
+    }
+    // The graph is not repainted continuously, wake up to look for changed notes
+    #[cfg(not(target_arch = "wasm32"))]
+    ctx.request_repaint_after(REFRESH_INTERVAL);
+    #[cfg(unix)]
+    if let Some(command_socket) = &self.command_socket {
+      command_socket.serve(|command| run_socket_command(&mut self.notes, command));
+    }
+    if let Some(command) = self.keymap.handle(ctx) {
+      self.run_command(command);
+    }
+    match self.palette.ui(ctx, &self.notes) {
+      Some(PaletteAction::Open(node_id)) => self.open(node_id),
+      Some(PaletteAction::Run(command)) => self.run_command(command),
+      None => (),
+    }
+    if let Some(plan) = self.rename_dialog.ui(ctx, &self.notes) {
+      if let Err(error) = rename_note(&mut self.notes, plan) {
+        log::error!("can't rename the note: {error}");
+      }
+    }
+    if let Some(open_note) = self.note_graph_ui.open_note() {
+      egui::SidePanel::right("links_panel").show(ctx, |ui| {
+        match self.links_panel.ui(ui, &self.notes, open_note) {
+          Some(LinksPanelAction::Open(node_id)) => self.open(node_id),
+          Some(LinksPanelAction::LinkMention(mention)) => {
+            if let Err(error) = link_mention(&mut self.notes, &mention) {
+              log::error!("can't link the mention: {error}");
+            }
+          }
+          None => (),
+        }
+      });
+    }
+    egui::SidePanel::left("search_panel").show(ctx, |ui| {
+      egui::CollapsingHeader::new("Tags").show(ui, |ui| {
+        if let Some(tag) = self.tags_panel.ui(ui, &self.notes) {
+          self.search_panel.set_query(format!("tag:{tag}"));
+        }
+      });
+      if let Some(node_id) = self.diagnostics_panel.ui(ui, &self.notes) {
+        self.open(node_id);
+      }
+      ui.separator();
+      if let Some(node_id) = self.search_panel.ui(ui, &self.notes) {
+        self.open(node_id);
+      }
+    });
     egui::CentralPanel::default().show(ctx, |ui| {
-      self.note_graph_ui.ui(ui);
+      let action = self
+        .note_graph_ui
+        .ui(ui, &mut self.configuration, &mut self.notes);
+      match action {
+        Some(NoteGraphAction::CreateNote(node_id)) => self.open(node_id),
+        Some(NoteGraphAction::Rename(node_id)) => self.rename_dialog.open(&self.notes, node_id),
+        None => (),
+      }
     });
   }
 }
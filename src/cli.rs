@@ -0,0 +1,222 @@
+use crate::features::configuration::Configuration;
+use crate::features::note_graph::{
+  apply_styles, metric_values, settled_layout, write_graph_image, ImageFormat,
+};
+use crate::features::notes::{check_notes, NotesIndex};
+use crate::lib::export::{export_graph, ExportFormat};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Most FDP steps taken to lay out an exported graph
+const MAX_LAYOUT_STEPS: usize = 1000;
+/// Width of exported images in pixels, if not given
+const DEFAULT_IMAGE_WIDTH: u32 = 1920;
+
+const USAGE: &str = "\
+usage:
+  basalt [<notes directory> | <graph file>]
+  basalt export [--format <format>] [--output <file>] [<notes directory>]
+  basalt image [--format <format>] [--output <file>] [--width <pixels>] [<notes directory>]
+  basalt check [--format text|json] [<notes directory>]
+
+graph files (.graphml, .gexf, .dot, .json) are imported and shown instead of notes
+export formats: graphml, gexf, dot, json
+image formats: svg, png, the width defaults to 1920 pixels
+check reports broken and ambiguous links, duplicate titles, malformed front matter and orphans,
+and fails if there are any but orphans
+the format defaults to the extension of the output file, the output to stdout";
+
+/// Run the command in the command line `args` (without the program name), without opening a window.
+/// Returns `None` if the arguments are no command, and the app should be opened instead.
+pub fn run_command(args: &[OsString]) -> Option<Result<(), String>> {
+  let (command, args) = args.split_first()?;
+  match command.to_str()? {
+    "export" => Some(export(args)),
+    "image" => Some(image(args)),
+    "check" => Some(check(args)),
+    "help" | "--help" | "-h" => {
+      println!("{USAGE}");
+      Some(Ok(()))
+    }
+    _ => None,
+  }
+}
+
+/// Arguments shared by the commands: options with their values, and the notes directory
+#[derive(Default)]
+struct Arguments {
+  options: Vec<(String, OsString)>,
+  notes_directory: Option<PathBuf>,
+}
+
+impl Arguments {
+  /// Parse `--name value` options, any other argument is the notes directory
+  fn parse(args: &[OsString], option_names: &[&str]) -> Result<Self, String> {
+    let mut arguments = Self::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+      match arg.to_str().filter(|arg| arg.starts_with("--")) {
+        Some(name) => {
+          let name = name.trim_start_matches("--");
+          if !option_names.contains(&name) {
+            return Err(format!("unknown option `--{name}`\n{USAGE}"));
+          }
+          let value = args
+            .next()
+            .ok_or_else(|| format!("missing value of `--{name}`"))?;
+          arguments.options.push((name.to_owned(), value.clone()));
+        }
+        None if arguments.notes_directory.is_none() => {
+          arguments.notes_directory = Some(PathBuf::from(arg));
+        }
+        None => {
+          return Err(format!(
+            "unexpected argument `{}`\n{USAGE}",
+            arg.to_string_lossy()
+          ))
+        }
+      }
+    }
+    Ok(arguments)
+  }
+
+  /// Last value of the option
+  fn option(&self, name: &str) -> Option<&OsString> {
+    self
+      .options
+      .iter()
+      .rev()
+      .find_map(|(option, value)| (option == name).then_some(value))
+  }
+
+  /// Notes of the notes directory, or of the one in the configuration, and the configuration
+  fn load_notes(&self) -> Result<(NotesIndex, Configuration), String> {
+    let configuration = Configuration::load().unwrap_or_else(|error| {
+      log::error!("can't load configuration, using defaults: {error}");
+      Configuration::default()
+    });
+    let notes_directory = self
+      .notes_directory
+      .clone()
+      .or_else(|| configuration.notes_directory.clone())
+      .ok_or("no notes directory given or configured")?;
+    let mut notes = NotesIndex::load(&notes_directory).map_err(|error| {
+      format!(
+        "can't load notes from {}: {error}",
+        notes_directory.display()
+      )
+    })?;
+    notes.set_case_sensitive(configuration.case_sensitive_links);
+    Ok((notes, configuration))
+  }
+}
+
+/// Lay out the note graph and write it to the output
+fn export(args: &[OsString]) -> Result<(), String> {
+  let arguments = Arguments::parse(args, &["format", "output"])?;
+  let output = arguments.option("output").map(PathBuf::from);
+  let format = match (arguments.option("format"), &output) {
+    (Some(format), _) => format.to_string_lossy().parse()?,
+    (None, Some(output)) => ExportFormat::from_path(output).ok_or_else(|| {
+      format!(
+        "can't tell the format of {} by it's extension, give it with `--format`",
+        output.display()
+      )
+    })?,
+    (None, None) => ExportFormat::Json,
+  };
+
+  let (notes, _) = arguments.load_notes()?;
+  let node_positions = settled_layout(&notes.graph, MAX_LAYOUT_STEPS);
+  write_output(output, |writer| {
+    export_graph(&notes.graph, &node_positions, format, writer)
+  })
+}
+
+/// Lay out the note graph, styled as in the app, and draw it into an image
+fn image(args: &[OsString]) -> Result<(), String> {
+  let arguments = Arguments::parse(args, &["format", "output", "width"])?;
+  let output = arguments.option("output").map(PathBuf::from);
+  let format = match (arguments.option("format"), &output) {
+    (Some(format), _) => format.to_string_lossy().parse()?,
+    (None, Some(output)) => ImageFormat::from_path(output).ok_or_else(|| {
+      format!(
+        "can't tell the format of {} by it's extension, give it with `--format`",
+        output.display()
+      )
+    })?,
+    (None, None) => ImageFormat::Svg,
+  };
+  let width = match arguments.option("width") {
+    Some(width) => width
+      .to_string_lossy()
+      .parse()
+      .ok()
+      .filter(|width| *width > 0)
+      .ok_or_else(|| format!("invalid width `{}`", width.to_string_lossy()))?,
+    None => DEFAULT_IMAGE_WIDTH,
+  };
+
+  let (mut notes, configuration) = arguments.load_notes()?;
+  let values = metric_values(&notes.graph, configuration.node_size.metric);
+  apply_styles(&mut notes.graph, &configuration, None, &values);
+  let node_positions = settled_layout(&notes.graph, MAX_LAYOUT_STEPS);
+  write_output(output, |writer| {
+    write_graph_image(
+      writer,
+      format,
+      &notes.graph,
+      &node_positions,
+      &configuration,
+      width,
+    )
+  })
+}
+
+/// Report problems of the notes on stdout, fail if there are errors
+fn check(args: &[OsString]) -> Result<(), String> {
+  let arguments = Arguments::parse(args, &["format"])?;
+  let json = match arguments
+    .option("format")
+    .map(|format| format.to_string_lossy())
+  {
+    None => false,
+    Some(format) if format == "text" => false,
+    Some(format) if format == "json" => true,
+    Some(format) => return Err(format!("unknown check format `{format}`")),
+  };
+  let (notes, _) = arguments.load_notes()?;
+  let report = check_notes(&notes);
+  if json {
+    let json = serde_json::to_string_pretty(&report).map_err(|error| error.to_string())?;
+    println!("{json}");
+  } else {
+    println!("{report}");
+  }
+  match report.error_count() {
+    0 => Ok(()),
+    errors => Err(format!("{errors} errors in the notes")),
+  }
+}
+
+/// Write to the output file, or to stdout
+fn write_output(
+  output: Option<PathBuf>,
+  write_to: impl Fn(&mut BufWriter<&mut dyn Write>) -> io::Result<()>,
+) -> Result<(), String> {
+  let write = |writer: &mut dyn Write| -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_to(&mut writer)?;
+    writer.flush()
+  };
+  output.map_or_else(
+    || write(&mut io::stdout().lock()).map_err(|error| error.to_string()),
+    |output| {
+      File::create(&output)
+        .and_then(|mut file| write(&mut file))
+        .map_err(|error| format!("can't write {}: {error}", output.display()))
+    },
+  )
+}
@@ -0,0 +1,222 @@
+use crate::features::notes::{plan_rename, rename_note, NotesIndex};
+use crate::lib::graph::Graph;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a connected client has to send it's command
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Command sent to the socket as a line of JSON, e.g.
+/// `{"command": "rename", "path": "Old.md", "new_path": "dir/New.md"}`
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum SocketCommand {
+  /// Rename the note at `path`, relative to the notes directory, rewriting links to it.
+  /// With `preview` the changed lines are returned without renaming anything.
+  Rename {
+    path: PathBuf,
+    new_path: String,
+    #[serde(default)]
+    preview: bool,
+  },
+}
+
+/// Reply to a command, written back as a line of JSON
+#[derive(Serialize, Debug, PartialEq, Eq, Default)]
+pub struct SocketReply {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+  /// Path the note was renamed to
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub new_path: Option<PathBuf>,
+  /// Changed lines of the notes
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub edits: Vec<EditReply>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct EditReply {
+  /// Path of the note before the rename
+  pub path: PathBuf,
+  /// Line, counted from 1, before and after the change
+  pub lines: Vec<(usize, String, String)>,
+}
+
+impl SocketReply {
+  fn error(message: String) -> Self {
+    Self {
+      error: Some(message),
+      ..Self::default()
+    }
+  }
+}
+
+/// Unix socket the app takes commands from while it's open, one per connection
+pub struct CommandSocket {
+  listener: UnixListener,
+  path: PathBuf,
+}
+
+impl CommandSocket {
+  /// Listen on the socket at `path`, replacing a socket left behind by an app that's gone
+  pub fn bind(path: &Path) -> io::Result<Self> {
+    let is_socket = path
+      .symlink_metadata()
+      .is_ok_and(|metadata| metadata.file_type().is_socket());
+    if is_socket && UnixStream::connect(path).is_err() {
+      std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    listener.set_nonblocking(true)?;
+    Ok(Self {
+      listener,
+      path: path.to_owned(),
+    })
+  }
+
+  /// Answer the clients that connected since the last call with what `run` returns for their command
+  pub fn serve(&self, mut run: impl FnMut(SocketCommand) -> SocketReply) {
+    loop {
+      let stream = match self.listener.accept() {
+        Ok((stream, _)) => stream,
+        Err(error) if error.kind() == io::ErrorKind::WouldBlock => return,
+        Err(error) => {
+          log::error!("can't accept a command socket client: {error}");
+          return;
+        }
+      };
+      if let Err(error) = serve_client(&stream, &mut run) {
+        log::error!("can't answer a command socket client: {error}");
+      }
+    }
+  }
+}
+
+impl Drop for CommandSocket {
+  fn drop(&mut self) {
+    // Nothing listens on it anymore
+    let _ = std::fs::remove_file(&self.path);
+  }
+}
+
+/// Read one command from the client and write the reply
+fn serve_client(
+  stream: &UnixStream,
+  run: &mut impl FnMut(SocketCommand) -> SocketReply,
+) -> io::Result<()> {
+  // Accepted streams may inherit the listener's non-blocking mode
+  stream.set_nonblocking(false)?;
+  stream.set_read_timeout(Some(READ_TIMEOUT))?;
+  let mut line = String::new();
+  BufReader::new(stream).read_line(&mut line)?;
+  let reply = match serde_json::from_str(&line) {
+    Ok(command) => run(command),
+    Err(error) => SocketReply::error(format!("invalid command: {error}")),
+  };
+  let mut reply = serde_json::to_string(&reply)?;
+  reply.push('\n');
+  let mut stream = stream;
+  stream.write_all(reply.as_bytes())
+}
+
+/// Run the command on the notes
+pub fn run_socket_command(notes: &mut NotesIndex, command: SocketCommand) -> SocketReply {
+  match command {
+    SocketCommand::Rename {
+      path,
+      new_path,
+      preview,
+    } => {
+      let Some(node_id) = notes.node_by_path(&path) else {
+        return SocketReply::error(format!("no note at {}", path.display()));
+      };
+      let plan = match plan_rename(notes, node_id, &new_path) {
+        Ok(plan) => plan,
+        Err(error) => return SocketReply::error(error.to_string()),
+      };
+      let reply = SocketReply {
+        error: None,
+        new_path: Some(plan.new_path.clone()),
+        edits: plan
+          .edits
+          .iter()
+          .map(|edit| EditReply {
+            path: notes.graph.get_node(edit.node_id).note.path.clone(),
+            lines: (edit.lines.iter())
+              .map(|(line, old, new)| (line + 1, old.clone(), new.clone()))
+              .collect(),
+          })
+          .collect(),
+      };
+      if preview {
+        return reply;
+      }
+      match rename_note(notes, plan) {
+        Ok(()) => reply,
+        Err(error) => SocketReply::error(format!("can't rename the note: {error}")),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn rename_from_the_socket() {
+    crate::lib::test::with_test_dir(|temp_dir| {
+      let notes_dir = temp_dir.join("notes");
+      std::fs::create_dir(&notes_dir)?;
+      std::fs::write(notes_dir.join("index.md"), "[[Old]]")?;
+      std::fs::write(notes_dir.join("Old.md"), "old")?;
+      let mut notes = NotesIndex::load(&notes_dir)?;
+      let socket = CommandSocket::bind(&temp_dir.join("basalt.sock"))?;
+      let send = |notes: &mut NotesIndex, request: &str| -> io::Result<serde_json::Value> {
+        let mut client = UnixStream::connect(temp_dir.join("basalt.sock"))?;
+        client.write_all(request.as_bytes())?;
+        socket.serve(|command| run_socket_command(notes, command));
+        let mut reply = String::new();
+        BufReader::new(client).read_line(&mut reply)?;
+        Ok(serde_json::from_str(&reply)?)
+      };
+
+      let reply = send(&mut notes, "rename Old.md\n")?;
+      assert!(reply["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid command"));
+
+      let request =
+        r#"{"command": "rename", "path": "Old.md", "new_path": "dir/New", "preview": true}"#;
+      let reply = send(&mut notes, &format!("{request}\n"))?;
+      assert_eq!(reply["new_path"], "dir/New.md");
+      assert_eq!(
+        reply["edits"][0],
+        serde_json::json!({"path": "index.md", "lines": [[1, "[[Old]]", "[[New]]"]]})
+      );
+      assert!(notes_dir.join("Old.md").exists());
+
+      let request = r#"{"command": "rename", "path": "Old.md", "new_path": "dir/New"}"#;
+      let reply = send(&mut notes, &format!("{request}\n"))?;
+      assert!(reply.get("error").is_none());
+      assert_eq!(
+        std::fs::read_to_string(notes_dir.join("dir/New.md"))?,
+        "old"
+      );
+      assert_eq!(
+        std::fs::read_to_string(notes_dir.join("index.md"))?,
+        "[[New]]"
+      );
+
+      drop(socket);
+      assert!(!temp_dir.join("basalt.sock").exists());
+      Ok(())
+    })
+    .unwrap();
+  }
+}
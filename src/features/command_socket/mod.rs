@@ -0,0 +1,3 @@
+mod command_socket;
+
+pub use command_socket::*;
@@ -1,16 +1,26 @@
+use crate::features::palette::Command;
 use egui::Color32;
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Map, Value};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Configuration {
   #[cfg(not(target_arch = "wasm32"))]
   #[serde(default)]
   pub include: Vec<PathBuf>,
+  /// Directory to load notes from, unless one is given on the command line
+  #[cfg(not(target_arch = "wasm32"))]
+  #[serde(default)]
+  pub notes_directory: Option<PathBuf>,
+  /// Unix socket the app takes commands from, e.g. to rename a note, none if not given
+  #[cfg(not(target_arch = "wasm32"))]
+  #[serde(default)]
+  pub command_socket: Option<PathBuf>,
   #[serde(default)]
   pub background_color: Color32,
   #[serde(default)]
@@ -19,16 +29,232 @@ pub struct Configuration {
   pub primary_color: Color32,
   #[serde(default)]
   pub secondary_color: Color32,
+  /// Ordered rules for coloring graph nodes, first matching rule wins
+  #[serde(default)]
+  pub color_groups: Vec<ColorGroup>,
+  #[serde(default)]
+  pub node_size: NodeSize,
+  /// Content of notes created from ghost nodes, `{{title}}` is replaced with the title
+  #[serde(default = "Configuration::default_new_note_template")]
+  pub new_note_template: String,
+  /// Key sequences bound to commands
+  #[serde(default = "Configuration::default_keymap")]
+  pub keymap: Vec<KeyBinding>,
+  /// Whether links have to match note names and paths case-sensitively
+  #[serde(default)]
+  pub case_sensitive_links: bool,
+  #[serde(default)]
+  pub edge_styles: EdgeStyles,
+  #[serde(default)]
+  pub minimap: Minimap,
 }
 
+/// Key sequence, e.g. `ctrl+p` or `g f`, that runs a command
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct KeyBinding {
+  pub keys: String,
+  pub command: Command,
+}
+
+/// Which notes a `ColorGroup` applies to
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorGroupRule {
+  /// Note path matches a glob, e.g. `journal/**`
+  Path(String),
+  /// Note has a tag, e.g. `#project`
+  Tag(String),
+  /// Note title or path contains a text (case-insensitive)
+  Query(String),
+}
+
+/// Color for the notes matching a rule
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ColorGroup {
+  pub rule: ColorGroupRule,
+  pub color: Color32,
+}
+
+/// How edges of one kind are drawn
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct EdgeStyle {
+  pub color: Color32,
+  pub width: f32,
+  pub dashed: bool,
+  /// Draw an arrowhead at the target
+  pub arrow: bool,
+}
+
+impl EdgeStyle {
+  const fn new(color: Color32, width: f32, dashed: bool, arrow: bool) -> Self {
+    Self {
+      color,
+      width,
+      dashed,
+      arrow,
+    }
+  }
+}
+
+impl Default for EdgeStyle {
+  fn default() -> Self {
+    Self::new(Color32::WHITE, 2.0, false, true)
+  }
+}
+
+/// Styles of the edge kinds
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct EdgeStyles {
+  pub link: EdgeStyle,
+  /// `![[embed]]`
+  pub embed: EdgeStyle,
+  /// Note to the tag node
+  pub tag: EdgeStyle,
+  /// Note to the folder note of it's folder
+  pub folder: EdgeStyle,
+  /// Note to it's heading node
+  pub heading: EdgeStyle,
+  /// Typed links, `parent:: [[Note]]`, without a style in `fields`
+  pub typed: EdgeStyle,
+  /// Styles of typed links by the field name
+  pub fields: BTreeMap<String, EdgeStyle>,
+  pub reciprocal: ReciprocalEdges,
+  /// Bend edges between communities of notes towards the community centers,
+  /// so that they run together
+  pub bundle: bool,
+}
+
+/// How links in both directions between two notes are drawn
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReciprocalEdges {
+  /// Two curves bent to opposite sides
+  #[default]
+  Curved,
+  /// One line with arrowheads at both ends
+  Merged,
+}
+
+impl Default for EdgeStyles {
+  fn default() -> Self {
+    Self {
+      link: EdgeStyle::default(),
+      embed: EdgeStyle::new(Color32::from_rgb(120, 180, 255), 2.0, false, true),
+      tag: EdgeStyle::new(Color32::from_rgb(255, 200, 80), 1.0, true, false),
+      folder: EdgeStyle::new(Color32::GRAY, 1.0, true, true),
+      heading: EdgeStyle::new(Color32::GRAY, 1.0, false, false),
+      typed: EdgeStyle::new(Color32::from_rgb(180, 130, 255), 2.0, false, true),
+      fields: BTreeMap::new(),
+      reciprocal: ReciprocalEdges::default(),
+      bundle: false,
+    }
+  }
+}
+
+/// Overview of the whole graph in a corner of the graph area
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(default)]
+pub struct Minimap {
+  pub show: bool,
+  pub corner: Corner,
+  /// Length of the longer side, in points
+  pub size: f32,
+  /// Distance from the edges of the graph area, in points
+  pub margin: f32,
+}
+
+impl Default for Minimap {
+  fn default() -> Self {
+    Self {
+      show: true,
+      corner: Corner::default(),
+      size: 200.0,
+      margin: 10.0,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  #[default]
+  BottomRight,
+}
+
+/// Metric that drives the radius of graph nodes
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeSizeMetric {
+  /// All nodes have `min_radius`
+  Fixed,
+  /// Number of backlinks
+  InDegree,
+  /// Number of outgoing links
+  OutDegree,
+  /// Number of links in both directions
+  #[default]
+  Degree,
+  /// Number of words in the note
+  WordCount,
+  PageRank,
+  /// Number of shortest paths going through the note
+  Betweenness,
+}
+
+/// How graph nodes are sized: metric values are scaled linearly
+/// between `min_radius` and `max_radius`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct NodeSize {
+  #[serde(default)]
+  pub metric: NodeSizeMetric,
+  #[serde(default = "NodeSize::default_min_radius")]
+  pub min_radius: f32,
+  #[serde(default = "NodeSize::default_max_radius")]
+  pub max_radius: f32,
+}
+
+impl NodeSize {
+  const fn default_min_radius() -> f32 {
+    15.0
+  }
+  const fn default_max_radius() -> f32 {
+    40.0
+  }
+}
+
+impl Default for NodeSize {
+  fn default() -> Self {
+    Self {
+      metric: NodeSizeMetric::default(),
+      min_radius: Self::default_min_radius(),
+      max_radius: Self::default_max_radius(),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 struct ConfigurationOptional {
   #[cfg(not(target_arch = "wasm32"))]
   pub include: Option<Vec<PathBuf>>,
+  #[cfg(not(target_arch = "wasm32"))]
+  pub notes_directory: Option<PathBuf>,
+  #[cfg(not(target_arch = "wasm32"))]
+  pub command_socket: Option<PathBuf>,
   pub background_color: Option<Color32>,
   pub foreground_color: Option<Color32>,
   pub primary_color: Option<Color32>,
   pub secondary_color: Option<Color32>,
+  pub color_groups: Option<Vec<ColorGroup>>,
+  pub node_size: Option<NodeSize>,
+  pub new_note_template: Option<String>,
+  pub keymap: Option<Vec<KeyBinding>>,
+  pub case_sensitive_links: Option<bool>,
+  pub edge_styles: Option<EdgeStyles>,
+  pub minimap: Option<Minimap>,
 }
 
 impl Default for Configuration {
@@ -36,10 +262,21 @@ impl Default for Configuration {
     Self {
       #[cfg(not(target_arch = "wasm32"))]
       include: vec![],
+      #[cfg(not(target_arch = "wasm32"))]
+      notes_directory: None,
+      #[cfg(not(target_arch = "wasm32"))]
+      command_socket: None,
       background_color: Color32::from_rgb(0, 0, 0),
       foreground_color: Color32::from_rgb(255, 255, 255),
       primary_color: Color32::from_rgb(255, 255, 255),
       secondary_color: Color32::from_rgb(255, 255, 255),
+      color_groups: vec![],
+      node_size: NodeSize::default(),
+      new_note_template: Self::default_new_note_template(),
+      keymap: Self::default_keymap(),
+      case_sensitive_links: false,
+      edge_styles: EdgeStyles::default(),
+      minimap: Minimap::default(),
     }
   }
 }
@@ -61,6 +298,64 @@ impl From<&Configuration> for Map<String, Value> {
 }
 
 impl Configuration {
+  fn default_new_note_template() -> String {
+    "# {{title}}\n\n".to_owned()
+  }
+
+  fn default_keymap() -> Vec<KeyBinding> {
+    [
+      ("ctrl+p", Command::OpenQuickSwitcher),
+      ("ctrl+shift+p", Command::OpenPalette),
+      ("g l", Command::ToggleLocalGraph),
+      ("g f", Command::FitGraph),
+      ("g s", Command::StepLayout),
+      ("g r", Command::ResetLayout),
+      ("f2", Command::RenameNote),
+      ("h", Command::FocusLeft),
+      ("j", Command::FocusDown),
+      ("k", Command::FocusUp),
+      ("l", Command::FocusRight),
+    ]
+    .into_iter()
+    .map(|(keys, command)| KeyBinding {
+      keys: keys.to_owned(),
+      command,
+    })
+    .collect()
+  }
+
+  /// Path of the user's configuration file:
+  /// `$XDG_CONFIG_HOME/basalt/config.json` or `~/.config/basalt/config.json`
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+      .map(PathBuf::from)
+      .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("basalt").join("config.json"))
+  }
+
+  /// Read the user's configuration file with it's includes.
+  /// Returns the default configuration if there is no such file.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn load() -> io::Result<Self> {
+    let Some(config_path) = Self::config_path().filter(|path| path.exists()) else {
+      return Ok(Self::default());
+    };
+    let config_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    Self::read_configuration(&mut File::open(&config_path)?)?.read_included(config_dir)
+  }
+
+  /// Write the configuration to the user's configuration file
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn save(&self) -> io::Result<()> {
+    let config_path = Self::config_path()
+      .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "can't find config directory"))?;
+    if let Some(config_dir) = config_path.parent() {
+      std::fs::create_dir_all(config_dir)?;
+    }
+    self.write_configuration(&mut File::create(config_path)?)
+  }
+
   //TODO: replace json to conf format with include ordering
   pub fn read_configuration(readable: &mut impl Read) -> io::Result<Self> {
     let mut content = String::new();
@@ -138,10 +433,19 @@ mod test {
   fn read_with_includes() {
     let expected_config = Configuration {
       include: vec![],
+      notes_directory: None,
+      command_socket: None,
       foreground_color: Color32::from_rgb(255, 255, 255),
       background_color: Color32::from_rgb(255, 255, 255),
       primary_color: Color32::from_rgb(255, 0, 255),
       secondary_color: Color32::from_rgb(0, 167, 0),
+      color_groups: vec![],
+      node_size: NodeSize::default(),
+      new_note_template: Configuration::default_new_note_template(),
+      keymap: Configuration::default_keymap(),
+      case_sensitive_links: false,
+      edge_styles: EdgeStyles::default(),
+      minimap: Minimap::default(),
     };
     let read_config = Configuration::read_configuration(
       &mut File::open("tests/configuration/first_config.json").expect("Could not open file"),
@@ -156,10 +460,19 @@ mod test {
   fn read_with_includes_with_partial_initial_config() {
     let expected_config = Configuration {
       include: vec![],
+      notes_directory: None,
+      command_socket: None,
       foreground_color: Color32::from_rgb(255, 255, 255),
       background_color: Color32::from_rgb(255, 255, 255),
       primary_color: Color32::from_rgb(255, 0, 255),
       secondary_color: Color32::from_rgb(0, 167, 0),
+      color_groups: vec![],
+      node_size: NodeSize::default(),
+      new_note_template: Configuration::default_new_note_template(),
+      keymap: Configuration::default_keymap(),
+      case_sensitive_links: false,
+      edge_styles: EdgeStyles::default(),
+      minimap: Minimap::default(),
     };
     let read_config = Configuration::read_configuration(
       &mut File::open("tests/configuration/first_config.json").expect("Could not open file"),
@@ -174,10 +487,42 @@ mod test {
   fn write_and_read() {
     let expected = Configuration {
       include: vec![],
+      notes_directory: Some(PathBuf::from("notes")),
+      command_socket: Some(PathBuf::from("/tmp/basalt.sock")),
       background_color: Color32::from_rgb(0, 0, 0),
       foreground_color: Color32::from_rgb(0, 0, 0),
       primary_color: Color32::from_rgb(0, 0, 0),
       secondary_color: Color32::from_rgb(0, 0, 0),
+      color_groups: vec![ColorGroup {
+        rule: ColorGroupRule::Path("journal/**".to_owned()),
+        color: Color32::from_rgb(0, 0, 255),
+      }],
+      node_size: NodeSize {
+        metric: NodeSizeMetric::PageRank,
+        min_radius: 10.0,
+        max_radius: 50.0,
+      },
+      new_note_template: "---\ntags: [todo]\n---\n# {{title}}\n".to_owned(),
+      keymap: vec![KeyBinding {
+        keys: "space f".to_owned(),
+        command: Command::FitGraph,
+      }],
+      case_sensitive_links: true,
+      edge_styles: EdgeStyles {
+        fields: BTreeMap::from([(
+          "parent".to_owned(),
+          EdgeStyle::new(Color32::RED, 3.0, true, false),
+        )]),
+        reciprocal: ReciprocalEdges::Merged,
+        bundle: true,
+        ..EdgeStyles::default()
+      },
+      minimap: Minimap {
+        show: false,
+        corner: Corner::TopLeft,
+        size: 150.0,
+        margin: 0.0,
+      },
     };
     crate::lib::test::with_test_dir(|temp_dir| {
       let first_config_file = temp_dir.join("first_config_file.json");
@@ -0,0 +1,273 @@
+use crate::features::configuration::KeyBinding;
+use crate::features::palette::Command;
+use egui::{Context, Event, Key, Modifiers};
+use std::fmt;
+
+/// Seconds to wait for the next key of a sequence
+const SEQUENCE_TIMEOUT: f64 = 1.0;
+
+/// Keys that can be bound, by their `Key::name`
+#[rustfmt::skip]
+const KEYS: &[Key] = &[
+  Key::ArrowDown, Key::ArrowLeft, Key::ArrowRight, Key::ArrowUp,
+  Key::Escape, Key::Tab, Key::Backspace, Key::Enter, Key::Space,
+  Key::Insert, Key::Delete, Key::Home, Key::End, Key::PageUp, Key::PageDown,
+  Key::Minus, Key::PlusEquals,
+  Key::Num0, Key::Num1, Key::Num2, Key::Num3, Key::Num4,
+  Key::Num5, Key::Num6, Key::Num7, Key::Num8, Key::Num9,
+  Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I,
+  Key::J, Key::K, Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R,
+  Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+  Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6,
+  Key::F7, Key::F8, Key::F9, Key::F10, Key::F11, Key::F12,
+];
+
+/// Key with modifiers, written as e.g. `ctrl+shift+p`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KeyChord {
+  pub modifiers: Modifiers,
+  pub key: Key,
+}
+
+impl KeyChord {
+  /// Parse a chord: modifiers (`ctrl`/`cmd`, `alt`, `shift`) and a key joined with `+`
+  pub fn parse(chord: &str) -> Result<Self, String> {
+    let mut parts = chord.split('+').map(str::trim).collect::<Vec<_>>();
+    let key_name = parts.pop().unwrap_or_default();
+    let mut modifiers = Modifiers::NONE;
+    for modifier in parts {
+      modifiers = modifiers
+        | match modifier.to_lowercase().as_str() {
+          // Ctrl on Linux and Windows, Cmd on Mac
+          "ctrl" | "cmd" => Modifiers::COMMAND,
+          "alt" => Modifiers::ALT,
+          "shift" => Modifiers::SHIFT,
+          _ => return Err(format!("unknown modifier `{modifier}`")),
+        };
+    }
+    let key = KEYS
+      .iter()
+      .find(|key| key.name().eq_ignore_ascii_case(key_name))
+      .ok_or_else(|| format!("unknown key `{key_name}`"))?;
+    Ok(Self {
+      modifiers,
+      key: *key,
+    })
+  }
+
+  /// Whether the pressed chord is this one
+  fn matches(self, pressed: Self) -> bool {
+    pressed.key == self.key && pressed.modifiers.matches(self.modifiers)
+  }
+}
+
+/// Problem with a binding found when loading the keymap
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum KeymapError {
+  Invalid {
+    keys: String,
+    reason: String,
+  },
+  /// Keys are the same as, or a prefix of, the keys of an earlier binding (or the other way around)
+  Conflict {
+    keys: String,
+    other: String,
+  },
+}
+
+impl fmt::Display for KeymapError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Invalid { keys, reason } => write!(f, "invalid key binding `{keys}`: {reason}"),
+      Self::Conflict { keys, other } => {
+        write!(f, "key binding `{keys}` conflicts with `{other}`")
+      }
+    }
+  }
+}
+
+/// Key sequences bound to commands, sequence keys are separated with spaces, e.g. `g f`
+#[derive(Default)]
+pub struct Keymap {
+  bindings: Vec<(Vec<KeyChord>, Command)>,
+  /// Keys of a sequence pressed so far
+  pending: Vec<KeyChord>,
+  /// Time the last key of `pending` was pressed at
+  pending_at: f64,
+}
+
+impl Keymap {
+  /// Parse the bindings. Invalid bindings and bindings conflicting with an earlier one
+  /// are left out and reported.
+  pub fn new(bindings: &[KeyBinding]) -> (Self, Vec<KeymapError>) {
+    let mut keymap = Self::default();
+    let mut errors = vec![];
+    let mut sources: Vec<&str> = vec![];
+    for binding in bindings {
+      let keys = match parse_keys(&binding.keys) {
+        Ok(keys) => keys,
+        Err(reason) => {
+          errors.push(KeymapError::Invalid {
+            keys: binding.keys.clone(),
+            reason,
+          });
+          continue;
+        }
+      };
+      let conflict = keymap.bindings.iter().position(|(other, _)| {
+        let shorter = keys.len().min(other.len());
+        keys[..shorter] == other[..shorter]
+      });
+      if let Some(index) = conflict {
+        errors.push(KeymapError::Conflict {
+          keys: binding.keys.clone(),
+          other: sources[index].to_owned(),
+        });
+        continue;
+      }
+      keymap.bindings.push((keys, binding.command));
+      sources.push(&binding.keys);
+    }
+    (keymap, errors)
+  }
+
+  /// Follow key presses, returns the command whose key sequence was completed.
+  /// While a text field has focus, only chords with Ctrl/Cmd or Alt are followed.
+  pub fn handle(&mut self, ctx: &Context) -> Option<Command> {
+    let typing = ctx.wants_keyboard_input();
+    ctx.input_mut(|input| {
+      if input.time - self.pending_at > SEQUENCE_TIMEOUT {
+        self.pending.clear();
+      }
+      let mut command = None;
+      input.events.retain(|event| {
+        let Event::Key {
+          key,
+          pressed: true,
+          repeat: false,
+          modifiers,
+        } = *event
+        else {
+          return true;
+        };
+        if command.is_some() || (typing && !modifiers.command && !modifiers.alt) {
+          return true;
+        }
+        command = self.press(KeyChord { modifiers, key });
+        self.pending_at = input.time;
+        // Keys that are part of a sequence are consumed
+        command.is_none() && self.pending.is_empty()
+      });
+      command
+    })
+  }
+
+  /// Add the chord to the pending sequence, returns the command if the sequence is complete
+  fn press(&mut self, chord: KeyChord) -> Option<Command> {
+    self.pending.push(chord);
+    if !self.is_pending_bound() {
+      // Not a continuation of the sequence, maybe the start of a new one
+      self.pending = vec![chord];
+      if !self.is_pending_bound() {
+        self.pending.clear();
+        return None;
+      }
+    }
+    let command = self
+      .bindings
+      .iter()
+      .find(|(keys, _)| keys.len() == self.pending.len() && starts_with(keys, &self.pending))
+      .map(|(_, command)| *command)?;
+    self.pending.clear();
+    Some(command)
+  }
+
+  /// Whether some binding starts with the pending keys
+  fn is_pending_bound(&self) -> bool {
+    self
+      .bindings
+      .iter()
+      .any(|(keys, _)| starts_with(keys, &self.pending))
+  }
+}
+
+/// Parse a key sequence: chords separated with spaces
+fn parse_keys(keys: &str) -> Result<Vec<KeyChord>, String> {
+  let keys = keys
+    .split_whitespace()
+    .map(KeyChord::parse)
+    .collect::<Result<Vec<_>, _>>()?;
+  if keys.is_empty() {
+    return Err("no keys".to_owned());
+  }
+  Ok(keys)
+}
+
+/// Whether the `pressed` chords are the start of the `bound` ones
+fn starts_with(bound: &[KeyChord], pressed: &[KeyChord]) -> bool {
+  bound.len() >= pressed.len()
+    && bound
+      .iter()
+      .zip(pressed)
+      .all(|(bound, pressed)| bound.matches(*pressed))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn binding(keys: &str, command: Command) -> KeyBinding {
+    KeyBinding {
+      keys: keys.to_owned(),
+      command,
+    }
+  }
+
+  #[test]
+  fn parse_and_conflicts() {
+    let (_, errors) = Keymap::new(&[
+      binding("ctrl+p", Command::OpenQuickSwitcher),
+      binding("g f", Command::FitGraph),
+      binding("Cmd+P", Command::OpenPalette),
+      binding("g", Command::StepLayout),
+      binding("hyper+x", Command::ResetLayout),
+    ]);
+    assert_eq!(
+      errors,
+      vec![
+        KeymapError::Conflict {
+          keys: "Cmd+P".to_owned(),
+          other: "ctrl+p".to_owned(),
+        },
+        KeymapError::Conflict {
+          keys: "g".to_owned(),
+          other: "g f".to_owned(),
+        },
+        KeymapError::Invalid {
+          keys: "hyper+x".to_owned(),
+          reason: "unknown modifier `hyper`".to_owned(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn sequences() {
+    let (mut keymap, errors) = Keymap::new(&[
+      binding("g f", Command::FitGraph),
+      binding("h", Command::FocusLeft),
+    ]);
+    assert!(errors.is_empty());
+    let chord = |key| KeyChord {
+      modifiers: Modifiers::NONE,
+      key,
+    };
+    assert_eq!(keymap.press(chord(Key::G)), None);
+    assert_eq!(keymap.press(chord(Key::F)), Some(Command::FitGraph));
+    // A broken sequence starts over
+    assert_eq!(keymap.press(chord(Key::G)), None);
+    assert_eq!(keymap.press(chord(Key::H)), Some(Command::FocusLeft));
+    assert_eq!(keymap.press(chord(Key::F)), None);
+    assert!(keymap.pending.is_empty());
+  }
+}
@@ -0,0 +1,3 @@
+mod keymap;
+
+pub use keymap::*;
@@ -1,2 +1,7 @@
+#[cfg(unix)]
+pub mod command_socket;
 pub mod configuration;
+pub mod keymap;
 pub mod note_graph;
+pub mod notes;
+pub mod palette;
@@ -0,0 +1,125 @@
+use crate::features::configuration::{ColorGroup, ColorGroupRule, Configuration};
+use crate::features::note_graph::graph_filter::{has_tag, matches_query};
+use crate::features::note_graph::NoteNodeData;
+use crate::lib::glob::glob_match;
+use crate::lib::graph::GraphMut;
+use egui::ecolor::Hsva;
+use egui::*;
+use std::collections::HashMap;
+
+const NODE_STROKE_WIDTH: f32 = 2.0;
+
+impl ColorGroupRule {
+  /// Whether the note falls into the group
+  pub fn matches(&self, node: &impl NoteNodeData) -> bool {
+    match self {
+      Self::Path(glob) => glob_match(glob, &node.path().to_string_lossy()),
+      Self::Tag(tag) => has_tag(node, tag),
+      Self::Query(query) => matches_query(node, query),
+    }
+  }
+
+  const fn kind(&self) -> &'static str {
+    match self {
+      Self::Path(_) => "Path",
+      Self::Tag(_) => "Tag",
+      Self::Query(_) => "Query",
+    }
+  }
+
+  fn value_mut(&mut self) -> &mut String {
+    match self {
+      Self::Path(value) | Self::Tag(value) | Self::Query(value) => value,
+    }
+  }
+}
+
+/// Set fill of every node to the color of the first matching group,
+/// or to `primary_color` if there is none. Stroke is `secondary_color`.
+pub fn apply_color_groups<G>(graph: &mut G, configuration: &Configuration)
+where
+  G: GraphMut,
+  G::NodeData: NoteNodeData,
+{
+  for (_, node) in graph.iter_nodes_mut() {
+    let fill = configuration
+      .color_groups
+      .iter()
+      .find(|group| group.rule.matches(node))
+      .map_or(configuration.primary_color, |group| group.color);
+    node.set_fill(fill);
+    node.set_stroke(Stroke::new(
+      NODE_STROKE_WIDTH,
+      configuration.secondary_color,
+    ));
+  }
+}
+
+/// Distinct color for the `index`-th community
+fn community_color(index: usize) -> Color32 {
+  // Golden ratio spreads consecutive hues far apart
+  const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+  let hue = (index as f32 * GOLDEN_RATIO_CONJUGATE).fract();
+  Hsva::new(hue, 0.6, 0.9, 1.0).into()
+}
+
+/// Set fill of every node to the color of it's community
+pub fn apply_community_colors<G>(graph: &mut G, communities: &HashMap<G::NodeId, usize>)
+where
+  G: GraphMut,
+  G::NodeData: NoteNodeData,
+{
+  for (node_id, node) in graph.iter_nodes_mut() {
+    node.set_fill(community_color(communities[&node_id]));
+  }
+}
+
+/// Show an editor for the ordered list of color groups, returns whether they changed
+pub fn color_groups_ui(ui: &mut Ui, color_groups: &mut Vec<ColorGroup>) -> bool {
+  let mut changed = false;
+  let mut swap = None;
+  let mut remove = None;
+  let len = color_groups.len();
+  for (index, group) in color_groups.iter_mut().enumerate() {
+    ui.horizontal(|ui| {
+      ComboBox::from_id_source(("color_group_rule", index))
+        .selected_text(group.rule.kind())
+        .show_ui(ui, |ui| {
+          let value = group.rule.value_mut().clone();
+          for rule in [
+            ColorGroupRule::Path(value.clone()),
+            ColorGroupRule::Tag(value.clone()),
+            ColorGroupRule::Query(value),
+          ] {
+            let kind = rule.kind();
+            changed |= ui.selectable_value(&mut group.rule, rule, kind).changed();
+          }
+        });
+      changed |= ui.text_edit_singleline(group.rule.value_mut()).changed();
+      changed |= ui.color_edit_button_srgba(&mut group.color).changed();
+      if ui.add_enabled(index > 0, Button::new("⏶")).clicked() {
+        swap = Some((index - 1, index));
+      }
+      if ui.add_enabled(index + 1 < len, Button::new("⏷")).clicked() {
+        swap = Some((index, index + 1));
+      }
+      if ui.button("🗑").clicked() {
+        remove = Some(index);
+      }
+    });
+  }
+  if let Some((a, b)) = swap {
+    color_groups.swap(a, b);
+  }
+  if let Some(index) = remove {
+    color_groups.remove(index);
+  }
+  if ui.button("Add group").clicked() {
+    color_groups.push(ColorGroup {
+      rule: ColorGroupRule::Path(String::new()),
+      color: Color32::WHITE,
+    });
+    changed = true;
+  }
+  changed || swap.is_some() || remove.is_some()
+}
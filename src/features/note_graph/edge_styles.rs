@@ -0,0 +1,79 @@
+use crate::features::configuration::{EdgeStyle, EdgeStyles, ReciprocalEdges};
+use crate::features::note_graph::{EdgeKind, NoteEdgeData};
+use crate::lib::graph::GraphMut;
+use egui::*;
+use std::collections::BTreeSet;
+
+/// Set the style of every edge to the style of it's kind
+pub fn apply_edge_styles<G>(graph: &mut G, styles: &EdgeStyles)
+where
+  G: GraphMut,
+  G::EdgeData: NoteEdgeData,
+{
+  for (_, edge) in graph.iter_edges_mut() {
+    let style = edge.kind().style(styles);
+    edge.set_style(style);
+  }
+}
+
+/// Style of the kind in the `styles`, typed links get their own entry
+fn style_mut<'a>(styles: &'a mut EdgeStyles, kind: &EdgeKind) -> &'a mut EdgeStyle {
+  match kind {
+    EdgeKind::Link => &mut styles.link,
+    EdgeKind::Embed => &mut styles.embed,
+    EdgeKind::Tag => &mut styles.tag,
+    EdgeKind::Folder => &mut styles.folder,
+    EdgeKind::Heading => &mut styles.heading,
+    EdgeKind::Typed(field) => styles.fields.entry(field.clone()).or_insert(styles.typed),
+  }
+}
+
+/// Edit the style of the edge `kinds` and whether they are shown, returns whether the styles changed
+pub fn edge_styles_ui(
+  ui: &mut Ui,
+  styles: &mut EdgeStyles,
+  kinds: &[EdgeKind],
+  hidden: &mut BTreeSet<EdgeKind>,
+) -> bool {
+  let mut changed = false;
+  Grid::new("edge_styles").num_columns(5).show(ui, |ui| {
+    for kind in kinds {
+      let mut shown = !hidden.contains(kind);
+      if ui.checkbox(&mut shown, kind.name()).changed() {
+        if shown {
+          hidden.remove(kind);
+        } else {
+          hidden.insert(kind.clone());
+        }
+      }
+      // Typed links are added to `fields` only once their style is edited
+      let mut style = kind.style(styles);
+      ui.color_edit_button_srgba(&mut style.color);
+      ui.add(
+        DragValue::new(&mut style.width)
+          .clamp_range(0.5..=10.0)
+          .speed(0.1),
+      );
+      ui.checkbox(&mut style.dashed, "Dashed");
+      ui.checkbox(&mut style.arrow, "Arrow");
+      ui.end_row();
+      if style != kind.style(styles) {
+        *style_mut(styles, kind) = style;
+        changed = true;
+      }
+    }
+  });
+  ui.horizontal(|ui| {
+    ui.label("Links both ways");
+    changed |= ui
+      .radio_value(&mut styles.reciprocal, ReciprocalEdges::Curved, "Curved")
+      .changed();
+    changed |= ui
+      .radio_value(&mut styles.reciprocal, ReciprocalEdges::Merged, "Merged")
+      .changed();
+  });
+  changed |= ui
+    .checkbox(&mut styles.bundle, "Bundle edges between communities")
+    .changed();
+  changed
+}
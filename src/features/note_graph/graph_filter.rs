@@ -0,0 +1,126 @@
+use crate::features::note_graph::{EdgeKind, NodeKind, NoteEdgeData, NoteNodeData};
+use crate::lib::glob::glob_match;
+use crate::lib::graph::Graph;
+use egui::*;
+use std::collections::{BTreeSet, HashSet};
+
+/// Which notes of the graph are shown.
+/// Empty strings mean "don't filter by this".
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct GraphFilter {
+  /// Show only notes with paths matching this glob
+  pub include: String,
+  /// Hide notes with paths matching this glob
+  pub exclude: String,
+  /// Show only notes with this tag
+  pub tag: String,
+  /// Show only notes matching this search query
+  pub query: String,
+  /// Hide notes without any links to or from them
+  pub hide_orphans: bool,
+  /// Hide non-Markdown files
+  pub hide_attachments: bool,
+  /// Show tags as nodes connected to the notes that have them
+  pub show_tags: bool,
+  /// Show headings as nodes around their notes
+  pub show_headings: bool,
+  /// Kinds of edges that are not shown
+  pub hidden_edges: BTreeSet<EdgeKind>,
+}
+
+impl GraphFilter {
+  /// Whether the note passes the filter, except for the search query
+  pub fn matches<G>(&self, graph: &G, node_id: G::NodeId) -> bool
+  where
+    G: Graph,
+    G::NodeData: NoteNodeData,
+  {
+    let node = graph.get_node(node_id);
+    if node.kind() == NodeKind::Tag {
+      return self.show_tags;
+    }
+    let path = node.path().to_string_lossy();
+
+    (self.include.is_empty() || glob_match(&self.include, &path))
+      && (self.exclude.is_empty() || !glob_match(&self.exclude, &path))
+      && (self.tag.trim().is_empty() || has_tag(node, &self.tag))
+      && (!self.hide_orphans || graph.iter_incidents(node_id).next().is_some())
+      && (!self.hide_attachments || is_markdown(&path))
+  }
+
+  /// Collect the nodes that pass the filter.
+  /// `query_matches` are the notes found by the search query, `None` if there is no query.
+  pub fn visible_nodes<G>(
+    &self,
+    graph: &G,
+    query_matches: Option<&HashSet<G::NodeId>>,
+  ) -> HashSet<G::NodeId>
+  where
+    G: Graph,
+    G::NodeData: NoteNodeData,
+  {
+    graph
+      .iter_nodes()
+      .map(|(node_id, _)| node_id)
+      .filter(|node_id| query_matches.map_or(true, |matches| matches.contains(node_id)))
+      .filter(|&node_id| self.matches(graph, node_id))
+      .collect()
+  }
+
+  /// Collect the edges whose kind is not hidden
+  pub fn visible_edges<G>(&self, graph: &G) -> HashSet<G::EdgeId>
+  where
+    G: Graph,
+    G::EdgeData: NoteEdgeData,
+  {
+    graph
+      .iter_edges()
+      .filter(|(_, edge)| !self.hidden_edges.contains(edge.kind()))
+      .map(|(edge_id, _)| edge_id)
+      .collect()
+  }
+
+  /// Show filter controls
+  pub fn ui(&mut self, ui: &mut Ui) {
+    Grid::new("graph_filter").num_columns(2).show(ui, |ui| {
+      ui.label("Search");
+      ui.add(TextEdit::singleline(&mut self.query).hint_text("rust tag:project"));
+      ui.end_row();
+      ui.label("Include paths");
+      ui.add(TextEdit::singleline(&mut self.include).hint_text("journal/**"));
+      ui.end_row();
+      ui.label("Exclude paths");
+      ui.add(TextEdit::singleline(&mut self.exclude).hint_text("**/*.png"));
+      ui.end_row();
+      ui.label("Tag");
+      ui.add(TextEdit::singleline(&mut self.tag).hint_text("#project"));
+      ui.end_row();
+    });
+    ui.checkbox(&mut self.hide_orphans, "Hide orphans");
+    ui.checkbox(&mut self.hide_attachments, "Hide attachments");
+    ui.checkbox(&mut self.show_tags, "Show tags");
+    ui.checkbox(&mut self.show_headings, "Show headings");
+    crate::ui::reset_button(ui, self);
+  }
+}
+
+/// Whether the note has the `tag`, with or without leading `#`
+pub(super) fn has_tag(node: &impl NoteNodeData, tag: &str) -> bool {
+  let tag = tag.trim().trim_start_matches('#');
+  node.tags().contains(&tag)
+}
+
+/// Whether the note's title or path contains the `query` (case-insensitive)
+pub(super) fn matches_query(node: &impl NoteNodeData, query: &str) -> bool {
+  let query = query.trim().to_lowercase();
+  node.title().to_lowercase().contains(&query)
+    || node
+      .path()
+      .to_string_lossy()
+      .to_lowercase()
+      .contains(&query)
+}
+
+fn is_markdown(path: &str) -> bool {
+  path.ends_with(".md")
+}
@@ -0,0 +1,548 @@
+use crate::features::configuration::Configuration;
+use crate::features::note_graph::note_graph_ui::{
+  graph_bounds, graph_shapes, tessellate, EdgeLines, EdgeRouting, GraphShapes, View,
+};
+use crate::features::note_graph::{NoteEdgeData, NoteGraph, NoteNodeData};
+use crate::lib::export::xml_escape;
+use crate::lib::fdp::eades_custom;
+use crate::lib::graph::Graph;
+use crate::lib::graph_algorithms;
+use crate::lib::owned_graph::{EdgeId, NodeId};
+use crate::lib::png::write_png;
+use egui::epaint::{ImageData, RectShape};
+use egui::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Size of the labels under the nodes at zoom 1, in points
+const LABEL_SIZE: f32 = 12.0;
+/// Space between a node and it's label, in points
+const LABEL_GAP: f32 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+  Svg,
+  Png,
+}
+
+impl ImageFormat {
+  pub const ALL: &'static [Self] = &[Self::Svg, Self::Png];
+
+  pub const fn extension(self) -> &'static str {
+    match self {
+      Self::Svg => "svg",
+      Self::Png => "png",
+    }
+  }
+
+  /// Format of the file by it's extension
+  pub fn from_path(path: &Path) -> Option<Self> {
+    path.extension()?.to_str()?.parse().ok()
+  }
+}
+
+impl fmt::Display for ImageFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.extension())
+  }
+}
+
+impl FromStr for ImageFormat {
+  type Err = String;
+
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    match name.to_lowercase().as_str() {
+      "svg" => Ok(Self::Svg),
+      "png" => Ok(Self::Png),
+      _ => Err(format!(
+        "unknown image format `{name}`, expected svg or png"
+      )),
+    }
+  }
+}
+
+/// Image export options of the graph view
+pub(super) struct ImageSettings {
+  pub(super) format: ImageFormat,
+  /// Width of PNG images in pixels
+  pub(super) width: u32,
+  /// Whether the whole graph is drawn, or the part of it that is shown
+  pub(super) whole_graph: bool,
+  /// File the image is written to, `graph.<extension>` if empty
+  pub(super) path: String,
+}
+
+impl Default for ImageSettings {
+  fn default() -> Self {
+    Self {
+      format: ImageFormat::Svg,
+      width: 1920,
+      whole_graph: false,
+      path: String::new(),
+    }
+  }
+}
+
+/// Part of the graph an image shows, and it's resolution
+#[derive(Clone, Copy)]
+pub(super) struct ImageFrame {
+  pub(super) view: View,
+  /// Size of the image, in points
+  pub(super) size: Vec2,
+  pub(super) pixels_per_point: f32,
+}
+
+impl ImageFrame {
+  /// Frame of the whole graph at zoom 1, `width` pixels wide.
+  /// Returns `None` for a graph without nodes.
+  pub(super) fn whole_graph<G>(
+    graph: &G,
+    node_positions: &eades_custom::NodePositions<NodeId>,
+    width: u32,
+  ) -> Option<Self>
+  where
+    G: Graph<NodeId = NodeId>,
+    G::NodeData: NoteNodeData,
+  {
+    // Room for the labels under the nodes at the bottom
+    const MARGIN: f32 = 40.0;
+    let bounds = graph_bounds(graph, node_positions)?.expand(MARGIN);
+    Some(Self {
+      view: View::new(bounds.size() / 2.0 - bounds.center().to_vec2()),
+      size: bounds.size(),
+      pixels_per_point: width as f32 / bounds.width(),
+    })
+  }
+
+  fn pixel_size(self) -> [u32; 2] {
+    let size = (self.size * self.pixels_per_point).round();
+    [size.x.max(1.0) as u32, size.y.max(1.0) as u32]
+  }
+}
+
+/// Title of a node, centered under it
+struct Label {
+  pos: Pos2,
+  text: String,
+  size: f32,
+}
+
+/// Draw the `graph` as it is drawn on the screen, into an image of the `frame`
+pub(super) fn write_image<G>(
+  writer: &mut impl Write,
+  format: ImageFormat,
+  graph: &G,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+  frame: ImageFrame,
+  routing: EdgeRouting,
+  visuals: &Visuals,
+) -> io::Result<()>
+where
+  G: Graph<NodeId = NodeId, EdgeId = EdgeId>,
+  G::NodeData: NoteNodeData,
+  G::EdgeData: NoteEdgeData,
+{
+  // A context of it's own, that paints at the resolution of the image
+  let ctx = Context::default();
+  ctx.set_visuals(visuals.clone());
+  let rect = Rect::from_min_size(Pos2::ZERO, frame.size);
+  ctx.begin_frame(RawInput {
+    screen_rect: Some(rect),
+    pixels_per_point: Some(frame.pixels_per_point),
+    ..Default::default()
+  });
+  let painter = Painter::new(ctx.clone(), LayerId::background(), rect);
+  let shapes = graph_shapes(
+    &painter,
+    graph,
+    node_positions,
+    frame.view,
+    None,
+    &HashSet::new(),
+    routing,
+  );
+  let labels = graph
+    .iter_nodes()
+    .filter_map(|(node_id, node)| {
+      let center = frame.view.to_screen(node_positions.get(&node_id)?.pos);
+      let pos = center + vec2(0.0, (node.radius() + LABEL_GAP) * frame.view.zoom());
+      rect
+        .expand(LABEL_SIZE * frame.view.zoom())
+        .contains(pos)
+        .then(|| Label {
+          pos,
+          text: node.title().to_owned(),
+          size: LABEL_SIZE * frame.view.zoom(),
+        })
+    })
+    .collect::<Vec<_>>();
+
+  let result = match format {
+    ImageFormat::Svg => write_svg(writer, frame, &shapes, &labels, visuals),
+    ImageFormat::Png => write_raster(writer, &ctx, frame, shapes, &labels, visuals),
+  };
+  // Nothing is shown, the output of the frame isn't needed
+  let _ = ctx.end_frame();
+  result
+}
+
+/// Draw the whole note graph into an image `width` pixels wide.
+/// Nodes and edges keep the styles they have, see [`apply_styles`](super::apply_styles),
+/// edges are routed as the `configuration` says.
+pub fn write_graph_image(
+  writer: &mut impl Write,
+  format: ImageFormat,
+  note_graph: &NoteGraph,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+  configuration: &Configuration,
+  width: u32,
+) -> io::Result<()> {
+  let frame = ImageFrame::whole_graph(note_graph, node_positions, width)
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the graph has no nodes"))?;
+  let communities = configuration
+    .edge_styles
+    .bundle
+    .then(|| graph_algorithms::louvain_communities(note_graph));
+  let routing = EdgeRouting {
+    reciprocal: configuration.edge_styles.reciprocal,
+    bundles: communities.as_ref(),
+  };
+  write_image(
+    writer,
+    format,
+    note_graph,
+    node_positions,
+    frame,
+    routing,
+    &Visuals::dark(),
+  )
+}
+
+// SVG
+
+/// ` fill="#rrggbb" fill-opacity="0.5"` for the color, `none` if transparent
+fn svg_paint(attribute: &str, color: Color32) -> String {
+  if color.a() == 0 {
+    return format!(r#" {attribute}="none""#);
+  }
+  let [r, g, b, a] = color.to_srgba_unmultiplied();
+  let mut paint = format!(r##" {attribute}="#{r:02x}{g:02x}{b:02x}""##);
+  if a < u8::MAX {
+    paint += &format!(r#" {attribute}-opacity="{:.3}""#, f32::from(a) / 255.0);
+  }
+  paint
+}
+
+fn svg_stroke(stroke: Stroke) -> String {
+  if stroke.is_empty() {
+    return svg_paint("stroke", Color32::TRANSPARENT);
+  }
+  svg_paint("stroke", stroke.color) + &format!(r#" stroke-width="{}""#, stroke.width)
+}
+
+fn svg_points(points: &[Pos2]) -> String {
+  points
+    .iter()
+    .map(|pos| format!("{},{}", pos.x, pos.y))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Write the node shapes, the ones the graph is drawn with, as SVG elements
+fn write_svg_shape(w: &mut impl Write, shape: &Shape) -> io::Result<()> {
+  match shape {
+    Shape::Vec(shapes) => {
+      for shape in shapes {
+        write_svg_shape(w, shape)?;
+      }
+      Ok(())
+    }
+    Shape::Circle(circle) => writeln!(
+      w,
+      r#"  <circle cx="{}" cy="{}" r="{}"{}{}/>"#,
+      circle.center.x,
+      circle.center.y,
+      circle.radius,
+      svg_paint("fill", circle.fill),
+      svg_stroke(circle.stroke)
+    ),
+    Shape::Rect(RectShape {
+      rect,
+      rounding,
+      fill,
+      stroke,
+      ..
+    }) => writeln!(
+      w,
+      r#"  <rect x="{}" y="{}" width="{}" height="{}" rx="{}"{}{}/>"#,
+      rect.min.x,
+      rect.min.y,
+      rect.width(),
+      rect.height(),
+      rounding.nw,
+      svg_paint("fill", *fill),
+      svg_stroke(*stroke)
+    ),
+    Shape::LineSegment { points, stroke } => writeln!(
+      w,
+      r#"  <line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+      points[0].x,
+      points[0].y,
+      points[1].x,
+      points[1].y,
+      svg_stroke(*stroke)
+    ),
+    Shape::Path(path) => {
+      let element = if path.closed { "polygon" } else { "polyline" };
+      writeln!(
+        w,
+        r#"  <{element} points="{}"{}{}/>"#,
+        svg_points(&path.points),
+        svg_paint("fill", path.fill),
+        svg_stroke(path.stroke)
+      )
+    }
+    _ => Ok(()),
+  }
+}
+
+fn write_svg(
+  w: &mut impl Write,
+  frame: ImageFrame,
+  shapes: &GraphShapes,
+  labels: &[Label],
+  visuals: &Visuals,
+) -> io::Result<()> {
+  let ImageFrame { size, .. } = frame;
+  writeln!(
+    w,
+    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+    size.x, size.y, size.x, size.y
+  )?;
+  writeln!(
+    w,
+    r#"  <rect width="100%" height="100%"{}/>"#,
+    svg_paint("fill", visuals.panel_fill)
+  )?;
+  for shape in &shapes.nodes {
+    write_svg_shape(w, shape)?;
+  }
+  for EdgeLines { stroke, lines } in &shapes.edges {
+    write!(w, r#"  <path d=""#)?;
+    for [from, to] in lines {
+      write!(w, "M{},{}L{},{}", from.x, from.y, to.x, to.y)?;
+    }
+    writeln!(w, r#"" fill="none"{}/>"#, svg_stroke(*stroke))?;
+  }
+  for label in labels {
+    writeln!(
+      w,
+      r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="hanging"{}>{}</text>"#,
+      label.pos.x,
+      label.pos.y,
+      label.size,
+      svg_paint("fill", visuals.text_color()),
+      xml_escape(&label.text)
+    )?;
+  }
+  writeln!(w, "</svg>")
+}
+
+// PNG
+
+/// Premultiplied color with channels from 0 to 1
+type Rgba = [f32; 4];
+
+fn rgba(color: Color32) -> Rgba {
+  color.to_array().map(|channel| f32::from(channel) / 255.0)
+}
+
+/// Z component of the cross product, twice the signed area of the triangle they span
+fn cross(a: Vec2, b: Vec2) -> f32 {
+  a.x.mul_add(b.y, -a.y * b.x)
+}
+
+/// Image the triangles of meshes are drawn into
+struct Raster {
+  width: usize,
+  height: usize,
+  pixels: Vec<Rgba>,
+  /// Coverage of the font texture, the only texture the graph is drawn with
+  font: (usize, usize, Vec<f32>),
+}
+
+impl Raster {
+  fn sample(&self, uv: Pos2) -> f32 {
+    let (width, height, coverage) = &self.font;
+    let x = ((uv.x * *width as f32) as usize).min(width - 1);
+    let y = ((uv.y * *height as f32) as usize).min(height - 1);
+    coverage[y * width + x]
+  }
+
+  /// Blend the triangle over the image, with it's vertex colors and uvs interpolated
+  fn triangle(&mut self, vertices: [&epaint::Vertex; 3], pixels_per_point: f32) {
+    let [a, b, c] = vertices.map(|vertex| vertex.pos.to_vec2() * pixels_per_point);
+    let area = cross(b - a, c - a);
+    if area.abs() < f32::EPSILON {
+      return;
+    }
+    let min = a.min(b).min(c).max(Vec2::ZERO);
+    let max = a.max(b).max(c);
+    let x_range = (min.x as usize)..(max.x.ceil() as usize).min(self.width);
+    let y_range = (min.y as usize)..(max.y.ceil() as usize).min(self.height);
+    let [color_a, color_b, color_c] = vertices.map(|vertex| rgba(vertex.color));
+    for y in y_range {
+      for x in x_range.clone() {
+        let p = vec2(x as f32 + 0.5, y as f32 + 0.5);
+        let weight = |from: Vec2, to: Vec2| cross(to - from, p - from) / area;
+        let [wa, wb, wc] = [weight(b, c), weight(c, a), weight(a, b)];
+        if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+          continue;
+        }
+        let uv = (vertices[0].uv.to_vec2() * wa
+          + vertices[1].uv.to_vec2() * wb
+          + vertices[2].uv.to_vec2() * wc)
+          .to_pos2();
+        let coverage = self.sample(uv);
+        let pixel = &mut self.pixels[y * self.width + x];
+        let source: Rgba = std::array::from_fn(|channel| {
+          color_a[channel].mul_add(wa, color_b[channel].mul_add(wb, color_c[channel] * wc))
+            * coverage
+        });
+        for channel in 0..4 {
+          pixel[channel] = pixel[channel].mul_add(1.0 - source[3], source[channel]);
+        }
+      }
+    }
+  }
+
+  /// Unmultiplied 8-bit RGBA pixels
+  fn rgba8(&self) -> Vec<u8> {
+    self
+      .pixels
+      .iter()
+      .flat_map(|&[r, g, b, a]| {
+        let unmultiply = |channel: f32| {
+          let channel = if a > 0.0 { channel / a } else { 0.0 };
+          (channel * 255.0).round().clamp(0.0, 255.0) as u8
+        };
+        [
+          unmultiply(r),
+          unmultiply(g),
+          unmultiply(b),
+          (a * 255.0).round() as u8,
+        ]
+      })
+      .collect()
+  }
+}
+
+/// Tessellate the shapes and labels as the screen renderer does, and draw them in software
+fn write_raster(
+  w: &mut impl Write,
+  ctx: &Context,
+  frame: ImageFrame,
+  shapes: GraphShapes,
+  labels: &[Label],
+  visuals: &Visuals,
+) -> io::Result<()> {
+  let mut shapes = shapes.into_shapes();
+  for label in labels {
+    let galley = ctx.fonts(|fonts| {
+      fonts.layout_no_wrap(
+        label.text.clone(),
+        FontId::proportional(label.size),
+        visuals.text_color(),
+      )
+    });
+    let pos = label.pos - vec2(galley.size().x / 2.0, 0.0);
+    shapes.push(Shape::galley(pos, galley));
+  }
+  let mesh = tessellate(ctx, shapes);
+
+  // Nothing has taken the font texture of the new context yet, so the whole of it is there
+  let font = match ctx.fonts(|fonts| fonts.font_image_delta()) {
+    Some(delta) => match delta.image {
+      ImageData::Font(image) if delta.pos.is_none() => (image.size[0], image.size[1], image.pixels),
+      _ => {
+        return Err(io::Error::new(
+          io::ErrorKind::Other,
+          "can't get the font texture",
+        ))
+      }
+    },
+    None => {
+      return Err(io::Error::new(
+        io::ErrorKind::Other,
+        "can't get the font texture",
+      ))
+    }
+  };
+  let [width, height] = frame.pixel_size();
+  let mut raster = Raster {
+    width: width as usize,
+    height: height as usize,
+    pixels: vec![rgba(visuals.panel_fill); width as usize * height as usize],
+    font,
+  };
+  for triangle in mesh.indices.chunks_exact(3) {
+    let vertices = [0, 1, 2].map(|corner| &mesh.vertices[triangle[corner] as usize]);
+    raster.triangle(vertices, frame.pixels_per_point);
+  }
+  write_png(w, width, height, &raster.rgba8())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::note_graph::{EdgeKind, NoteEdge, NoteNode};
+  use crate::features::notes::Note;
+  use crate::lib::fdp::eades_custom::{NodeFdpData, NodePositions};
+  use std::collections::HashMap;
+
+  #[test]
+  fn images_of_the_graph() {
+    let mut graph = NoteGraph::default();
+    let a = graph.insert_node(NoteNode::new(Note::new("a.md", "")));
+    let b = graph.insert_node(NoteNode::new(Note::new("Fish & chips.md", "")));
+    graph.insert_edge(a, b, NoteEdge::new(EdgeKind::Link, 0, None));
+    let node_positions = NodePositions(HashMap::from(
+      [(a, vec2(-100.0, 0.0)), (b, vec2(100.0, 0.0))].map(|(node_id, pos)| {
+        (
+          node_id,
+          NodeFdpData {
+            pos,
+            force: Vec2::ZERO,
+          },
+        )
+      }),
+    ));
+    let configuration = Configuration::default();
+    let image = |format| {
+      let mut output = vec![];
+      write_graph_image(
+        &mut output,
+        format,
+        &graph,
+        &node_positions,
+        &configuration,
+        400,
+      )
+      .unwrap();
+      output
+    };
+
+    let svg = String::from_utf8(image(ImageFormat::Svg)).unwrap();
+    assert_eq!(svg.matches("<circle").count(), 2);
+    assert!(svg.contains(">Fish &amp; chips</text>"));
+    assert!(svg.contains("<path d=\"M"));
+
+    let png = image(ImageFormat::Png);
+    assert!(png.starts_with(b"\x89PNG"));
+    // Width of the image in the header
+    assert_eq!(png[16..20], 400u32.to_be_bytes());
+  }
+}
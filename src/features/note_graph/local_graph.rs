@@ -0,0 +1,140 @@
+use crate::features::note_graph::note_graph_ui::{
+  graph_shapes, highlight, node_at, show_title_tooltip, EdgeRouting, View, SETTLED_MOVEMENT,
+};
+use crate::features::note_graph::NoteGraph;
+use crate::lib::fdp::eades_custom;
+use crate::lib::graph::{self, Direction};
+use crate::lib::owned_graph::NodeId;
+use crate::lib::subgraph::Subgraph;
+use egui::*;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
+
+const LOCAL_GRAPH_SIZE: f32 = 400.0;
+const MAX_DEPTH: usize = 5;
+
+/// Local graph state: the open note and its neighbors up to `depth` hops.
+/// Has it's own layout, independent of the global graph.
+pub struct LocalGraph {
+  depth: usize,
+  outgoing: bool,
+  backlinks: bool,
+  /// Note, settings and notes revision the current `nodes` were collected for
+  built_for: Option<(NodeId, usize, Option<Direction>, u64)>,
+  nodes: HashSet<NodeId>,
+  node_positions: eades_custom::NodePositions<NodeId>,
+}
+
+impl Default for LocalGraph {
+  fn default() -> Self {
+    Self {
+      depth: 1,
+      outgoing: true,
+      backlinks: true,
+      built_for: None,
+      nodes: HashSet::new(),
+      node_positions: eades_custom::NodePositions(HashMap::new()),
+    }
+  }
+}
+
+impl LocalGraph {
+  /// Direction to follow edges in, `None` if neither outgoing links nor backlinks are shown
+  const fn direction(&self) -> Option<Direction> {
+    match (self.outgoing, self.backlinks) {
+      (true, true) => Some(Direction::Both),
+      (true, false) => Some(Direction::Outgoing),
+      (false, true) => Some(Direction::Incoming),
+      (false, false) => None,
+    }
+  }
+
+  /// Recollect visible nodes around `center`.
+  /// Nodes that stay visible keep their positions, new ones are placed on a circle.
+  fn rebuild(&mut self, graph: &NoteGraph, center: NodeId) {
+    self.nodes = match self.direction() {
+      Some(direction) => graph::neighborhood(graph, center, self.depth, direction),
+      None => HashSet::from([center]),
+    };
+    self
+      .node_positions
+      .retain(|node_id, _| self.nodes.contains(node_id));
+
+    const RADIUS: f32 = 100.0;
+    let step = TAU / self.nodes.len() as f32;
+    for (index, &node_id) in self.nodes.iter().enumerate() {
+      let pos = if node_id == center {
+        Vec2::ZERO
+      } else {
+        RADIUS * vec2((index as f32 * step).cos(), (index as f32 * step).sin())
+      };
+      self
+        .node_positions
+        .entry(node_id)
+        .or_insert(eades_custom::NodeFdpData {
+          pos,
+          force: Vec2::default(),
+        });
+    }
+  }
+
+  /// Show local graph controls and the graph itself.
+  /// Returns the node that was clicked, if any.
+  pub(super) fn ui(
+    &mut self,
+    ui: &mut Ui,
+    graph: &NoteGraph,
+    revision: u64,
+    open_note: Option<NodeId>,
+    selected_nodes: &HashSet<NodeId>,
+    routing: EdgeRouting,
+  ) -> Option<NodeId> {
+    ui.horizontal(|ui| {
+      ui.add(Slider::new(&mut self.depth, 1..=MAX_DEPTH).text("Depth"));
+      ui.checkbox(&mut self.outgoing, "Outgoing links");
+      ui.checkbox(&mut self.backlinks, "Backlinks");
+    });
+
+    let Some(center) = open_note else {
+      ui.label("Click a note to see it's local graph");
+      return None;
+    };
+
+    let settings = Some((center, self.depth, self.direction(), revision));
+    if self.built_for != settings {
+      self.rebuild(graph, center);
+      self.built_for = settings;
+    }
+
+    let local_graph = Subgraph::new(graph, &self.nodes);
+    let movement = eades_custom::step(&local_graph, &mut self.node_positions);
+    if movement > SETTLED_MOVEMENT {
+      ui.ctx().request_repaint();
+    }
+
+    let (rect, response) = ui.allocate_exact_size(Vec2::splat(LOCAL_GRAPH_SIZE), Sense::click());
+    let view = View::new(rect.center().to_vec2());
+    let hovered_node = response
+      .hover_pos()
+      .and_then(|pos| node_at(&local_graph, &self.node_positions, view, pos));
+    if let Some(node_id) = hovered_node {
+      show_title_tooltip(ui, &local_graph, node_id);
+    }
+
+    let painter = ui.painter_at(rect);
+    painter.extend(
+      graph_shapes(
+        &painter,
+        &local_graph,
+        &self.node_positions,
+        view,
+        highlight(&local_graph, hovered_node.into_iter()).as_ref(),
+        selected_nodes,
+        routing,
+      )
+      .into_shapes(),
+    );
+
+    hovered_node.filter(|_| response.clicked())
+  }
+}
@@ -0,0 +1,86 @@
+use crate::features::configuration::{Corner, Minimap};
+use crate::features::note_graph::NoteNodeData;
+use crate::lib::fdp::eades_custom;
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use egui::*;
+
+/// Size of the node dots, in points
+const DOT_SIZE: f32 = 2.0;
+/// Space around the nodes, in graph coordinates
+const PADDING: f32 = 50.0;
+
+/// Show the whole layout of the `graph` in a corner of the graph `area`,
+/// with the part of the `viewport` (in graph coordinates) over the layout outlined.
+/// Returns the graph position to center the camera on, while the minimap is clicked or dragged.
+pub(super) fn minimap_ui<G>(
+  ctx: &Context,
+  area: Rect,
+  graph: &G,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+  viewport: Rect,
+  minimap: &Minimap,
+) -> Option<Vec2>
+where
+  G: Graph<NodeId = NodeId>,
+  G::NodeData: NoteNodeData,
+{
+  let bounds = graph
+    .iter_nodes()
+    .filter_map(|(node_id, _)| node_positions.get(&node_id))
+    .map(|node| node.pos.to_pos2())
+    .fold(Rect::NOTHING, |bounds, pos| {
+      bounds.union(Rect::from_min_max(pos, pos))
+    })
+    .expand(PADDING);
+  if !bounds.is_positive() {
+    return None;
+  }
+
+  // Keep the aspect ratio of the layout, the longer side is `size` long
+  let scale = minimap.size / bounds.width().max(bounds.height());
+  let size = bounds.size() * scale;
+  let area = area.shrink(minimap.margin);
+  let top_left = match minimap.corner {
+    Corner::TopLeft => area.left_top(),
+    Corner::TopRight => pos2(area.right() - size.x, area.top()),
+    Corner::BottomLeft => pos2(area.left(), area.bottom() - size.y),
+    Corner::BottomRight => area.right_bottom() - size,
+  };
+  let rect = Rect::from_min_size(top_left, size);
+  let to_minimap = |pos: Pos2| rect.min + (pos - bounds.min) * scale;
+
+  Area::new("minimap")
+    .fixed_pos(rect.min)
+    .show(ctx, |ui| {
+      let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
+      let visuals = ui.visuals();
+      painter.rect(
+        rect,
+        visuals.window_rounding,
+        visuals.extreme_bg_color.gamma_multiply(0.8),
+        visuals.window_stroke(),
+      );
+      let mut dots = Mesh::default();
+      for (node_id, node) in graph.iter_nodes() {
+        if let Some(node_fdp) = node_positions.get(&node_id) {
+          let center = to_minimap(node_fdp.pos.to_pos2());
+          dots.add_colored_rect(
+            Rect::from_center_size(center, Vec2::splat(DOT_SIZE)),
+            node.fill(),
+          );
+        }
+      }
+      painter.add(Shape::mesh(dots));
+      painter.rect_stroke(
+        Rect::from_min_max(to_minimap(viewport.min), to_minimap(viewport.max)),
+        0.0,
+        visuals.selection.stroke,
+      );
+
+      let pointer = response.interact_pointer_pos()?;
+      (response.is_pointer_button_down_on() || response.clicked())
+        .then(|| bounds.min.to_vec2() + (pointer - rect.min) / scale)
+    })
+    .inner
+}
@@ -1,6 +1,16 @@
+mod color_groups;
+mod edge_styles;
+pub mod graph_filter;
+mod image_export;
+mod local_graph;
+mod minimap;
+mod node_size;
 pub mod note_graph;
 mod note_graph_mock;
 pub mod note_graph_ui;
 
+pub use image_export::{write_graph_image, ImageFormat};
+pub use node_size::metric_values;
 pub use note_graph::*;
+pub use note_graph_mock::mock_notes;
 pub use note_graph_ui::*;
@@ -0,0 +1,101 @@
+use crate::features::configuration::{NodeSize, NodeSizeMetric};
+use crate::features::note_graph::NoteNodeData;
+use crate::lib::graph::{Direction, GraphMut};
+use crate::lib::graph_algorithms;
+use egui::*;
+use std::collections::HashMap;
+
+const METRICS: &[(NodeSizeMetric, &str)] = &[
+  (NodeSizeMetric::Fixed, "Fixed"),
+  (NodeSizeMetric::InDegree, "Backlinks"),
+  (NodeSizeMetric::OutDegree, "Outgoing links"),
+  (NodeSizeMetric::Degree, "All links"),
+  (NodeSizeMetric::WordCount, "Word count"),
+  (NodeSizeMetric::PageRank, "PageRank"),
+  (NodeSizeMetric::Betweenness, "Betweenness"),
+];
+
+/// Compute the metric for every node
+pub fn metric_values<G>(graph: &G, metric: NodeSizeMetric) -> HashMap<G::NodeId, f32>
+where
+  G: GraphMut,
+  G::NodeData: NoteNodeData,
+{
+  let as_f32 = |degrees: HashMap<G::NodeId, usize>| {
+    degrees
+      .into_iter()
+      .map(|(node_id, degree)| (node_id, degree as f32))
+      .collect()
+  };
+  match metric {
+    NodeSizeMetric::Fixed => graph.iter_nodes().map(|(id, _)| (id, 0.0)).collect(),
+    NodeSizeMetric::InDegree => as_f32(graph_algorithms::in_degrees(graph)),
+    NodeSizeMetric::OutDegree => as_f32(graph_algorithms::out_degrees(graph)),
+    NodeSizeMetric::Degree => as_f32(graph_algorithms::total_degrees(graph)),
+    NodeSizeMetric::WordCount => graph
+      .iter_nodes()
+      .map(|(id, node)| (id, node.word_count() as f32))
+      .collect(),
+    NodeSizeMetric::PageRank => graph_algorithms::page_rank(graph),
+    NodeSizeMetric::Betweenness => graph_algorithms::betweenness_centrality(graph, Direction::Both),
+  }
+}
+
+/// Set radius of every node by scaling the metric between `min_radius` and `max_radius`, the `values` of the metric
+pub fn apply_node_size<G>(graph: &mut G, node_size: &NodeSize, values: &HashMap<G::NodeId, f32>)
+where
+  G: GraphMut,
+  G::NodeData: NoteNodeData,
+{
+  let (min, max) = values
+    .values()
+    .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| {
+      (min.min(value), max.max(value))
+    });
+  let range = node_size.max_radius - node_size.min_radius;
+  for (node_id, node) in graph.iter_nodes_mut() {
+    let scale = if max > min {
+      (values[&node_id] - min) / (max - min)
+    } else {
+      0.0
+    };
+    let radius = range.mul_add(scale, node_size.min_radius);
+    node.set_radius(radius.clamp(node_size.min_radius, node_size.max_radius));
+  }
+}
+
+/// Show node size controls, returns whether the node size changed
+pub fn node_size_ui(ui: &mut Ui, node_size: &mut NodeSize) -> bool {
+  let mut changed = false;
+  let selected_text = METRICS
+    .iter()
+    .find(|(metric, _)| *metric == node_size.metric)
+    .map_or("", |(_, text)| text);
+  ComboBox::from_label("Size by")
+    .selected_text(selected_text)
+    .show_ui(ui, |ui| {
+      for &(metric, text) in METRICS {
+        changed |= ui
+          .selectable_value(&mut node_size.metric, metric, text)
+          .changed();
+      }
+    });
+  ui.horizontal(|ui| {
+    ui.label("Radius");
+    changed |= ui
+      .add(
+        DragValue::new(&mut node_size.min_radius)
+          .clamp_range(1.0..=node_size.max_radius)
+          .prefix("min: "),
+      )
+      .changed();
+    changed |= ui
+      .add(
+        DragValue::new(&mut node_size.max_radius)
+          .clamp_range(node_size.min_radius..=200.0)
+          .prefix("max: "),
+      )
+      .changed();
+  });
+  changed
+}
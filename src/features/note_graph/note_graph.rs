@@ -1 +1,317 @@
+use crate::features::configuration::{EdgeStyle, EdgeStyles};
+use crate::features::note_graph::{NoteEdgeData, NoteNodeData};
+use crate::features::notes::{Anchor, Heading, Link, Note};
+use crate::lib::export::ExportData;
+use crate::lib::import::ImportData;
+use crate::lib::owned_graph::OwnedGraph;
+use egui::{Color32, Stroke};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+/// Graph of the notes, edges are resolved links between them
+pub type NoteGraph = OwnedGraph<NoteNode, NoteEdge>;
+
+/// What a node of the graph stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+  Note,
+  /// Note that is linked to but doesn't exist yet
+  Ghost,
+  /// Tag, connected to the notes that have it
+  Tag,
+  /// Heading of a note, connected to the note
+  Heading,
+}
+
+/// What an edge of the graph stands for
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EdgeKind {
+  Link,
+  /// `![[embed]]`
+  Embed,
+  /// Note to the tag node
+  Tag,
+  /// Note to the folder note of it's folder
+  Folder,
+  /// Note to it's heading node
+  Heading,
+  /// Typed link with the field name, `parent:: [[Note]]`
+  Typed(String),
+}
+
+impl EdgeKind {
+  /// Kind of the edge for the link
+  pub fn of_link(link: &Link) -> Self {
+    match &link.field {
+      Some(field) => Self::Typed(field.clone()),
+      None if link.embed => Self::Embed,
+      None => Self::Link,
+    }
+  }
+
+  /// Whether the edge is written as a link in the source note
+  pub const fn is_link(&self) -> bool {
+    matches!(self, Self::Link | Self::Embed | Self::Typed(_))
+  }
+
+  pub fn name(&self) -> &str {
+    match self {
+      Self::Link => "Links",
+      Self::Embed => "Embeds",
+      Self::Tag => "Tags",
+      Self::Folder => "Folders",
+      Self::Heading => "Headings",
+      Self::Typed(field) => field,
+    }
+  }
+
+  /// Style of the kind in the `styles`
+  pub fn style(&self, styles: &EdgeStyles) -> EdgeStyle {
+    match self {
+      Self::Link => styles.link,
+      Self::Embed => styles.embed,
+      Self::Tag => styles.tag,
+      Self::Folder => styles.folder,
+      Self::Heading => styles.heading,
+      Self::Typed(field) => styles.fields.get(field).copied().unwrap_or(styles.typed),
+    }
+  }
+}
+
+/// Note with it's style in the graph
+#[derive(Clone, Debug)]
+pub struct NoteNode {
+  pub note: Note,
+  /// Links that don't point to any note (or point to ghost nodes)
+  pub unresolved_links: Vec<Link>,
+  pub kind: NodeKind,
+  fill: Color32,
+  stroke: Stroke,
+  radius: f32,
+}
+
+impl NoteNode {
+  pub fn new(note: Note) -> Self {
+    Self {
+      note,
+      unresolved_links: vec![],
+      kind: NodeKind::Note,
+      fill: Color32::WHITE,
+      stroke: Stroke::new(2.0, Color32::WHITE),
+      radius: 20.0,
+    }
+  }
+
+  /// Node for a note that doesn't exist, at `path`
+  pub fn ghost(path: PathBuf) -> Self {
+    Self {
+      kind: NodeKind::Ghost,
+      ..Self::new(Note::new(path, ""))
+    }
+  }
+
+  /// Node for the `tag` (without the leading `#`)
+  pub fn tag(tag: &str) -> Self {
+    let mut note = Note::new(format!("#{tag}"), "");
+    note.title = format!("#{tag}");
+    Self {
+      kind: NodeKind::Tag,
+      ..Self::new(note)
+    }
+  }
+
+  /// Node for a heading of the `note`
+  pub fn heading(note: &Note, heading: &Heading) -> Self {
+    let mut heading_note = Note::new(note.path.clone(), "");
+    heading_note.title = format!("{} › {}", note.title, heading.text);
+    Self {
+      kind: NodeKind::Heading,
+      radius: 10.0,
+      ..Self::new(heading_note)
+    }
+  }
+
+  /// Whether the note is linked to but doesn't exist yet
+  pub fn is_ghost(&self) -> bool {
+    self.kind == NodeKind::Ghost
+  }
+}
+
+impl NoteNodeData for NoteNode {
+  fn title(&self) -> &str {
+    &self.note.title
+  }
+  fn path(&self) -> &Path {
+    &self.note.path
+  }
+  fn tags(&self) -> Vec<&str> {
+    self.note.tags.iter().map(String::as_str).collect()
+  }
+  fn word_count(&self) -> usize {
+    self.note.word_count
+  }
+  fn kind(&self) -> NodeKind {
+    self.kind
+  }
+  fn fill(&self) -> Color32 {
+    self.fill
+  }
+  fn stroke(&self) -> Stroke {
+    self.stroke
+  }
+  fn radius(&self) -> f32 {
+    self.radius
+  }
+  fn set_fill(&mut self, fill: Color32) {
+    self.fill = fill;
+  }
+  fn set_stroke(&mut self, stroke: Stroke) {
+    self.stroke = stroke;
+  }
+  fn set_radius(&mut self, radius: f32) {
+    self.radius = radius;
+  }
+}
+
+impl ExportData for NoteNode {
+  fn attributes(&self) -> Vec<(&'static str, String)> {
+    let kind = match self.kind {
+      NodeKind::Note => "note",
+      NodeKind::Ghost => "ghost",
+      NodeKind::Tag => "tag",
+      NodeKind::Heading => "heading",
+    };
+    let mut attributes = vec![
+      ("label", self.note.title.clone()),
+      ("path", self.note.path.display().to_string()),
+      ("kind", kind.to_owned()),
+    ];
+    if !self.note.tags.is_empty() {
+      attributes.push(("tags", self.note.tags.join(" ")));
+    }
+    attributes
+  }
+}
+
+/// Attributes that are not kept as properties of imported nodes
+const NODE_ATTRIBUTES: &[&str] = &["label", "path", "kind", "tags"];
+
+impl ImportData for NoteNode {
+  /// Imported node: `label`, `path`, `kind` and `tags` (separated by spaces or commas)
+  /// are taken from it's attributes, other attributes become properties of the note
+  fn from_attributes(id: &str, attributes: &BTreeMap<String, String>) -> Self {
+    let mut note = Note::new(attributes.get("path").map_or(id, String::as_str), "");
+    note.title = attributes
+      .get("label")
+      .map_or(id, String::as_str)
+      .to_owned();
+    note.tags = attributes
+      .get("tags")
+      .into_iter()
+      .flat_map(|tags| tags.split(|char: char| char == ',' || char.is_whitespace()))
+      .map(|tag| tag.trim_start_matches('#'))
+      .filter(|tag| !tag.is_empty())
+      .map(str::to_owned)
+      .collect();
+    note.properties = attributes
+      .iter()
+      .filter(|(name, _)| !NODE_ATTRIBUTES.contains(&name.as_str()))
+      .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+      .collect();
+    let kind = match attributes.get("kind").map(String::as_str) {
+      Some("ghost") => NodeKind::Ghost,
+      Some("tag") => NodeKind::Tag,
+      Some("heading") => NodeKind::Heading,
+      _ => NodeKind::Note,
+    };
+    Self {
+      kind,
+      ..Self::new(note)
+    }
+  }
+}
+
+/// Resolved link between two notes
+#[derive(Clone, Debug)]
+pub struct NoteEdge {
+  pub kind: EdgeKind,
+  /// Index of the line in the source note the link is on
+  pub line: usize,
+  /// Heading or block of the target the link points to
+  pub anchor: Option<Anchor>,
+  style: EdgeStyle,
+}
+
+impl NoteEdge {
+  pub fn new(kind: EdgeKind, line: usize, anchor: Option<Anchor>) -> Self {
+    Self {
+      kind,
+      line,
+      anchor,
+      style: EdgeStyle::default(),
+    }
+  }
+}
+
+impl NoteEdgeData for NoteEdge {
+  fn kind(&self) -> &EdgeKind {
+    &self.kind
+  }
+  fn anchor(&self) -> Option<&Anchor> {
+    self.anchor.as_ref()
+  }
+  fn style(&self) -> EdgeStyle {
+    self.style
+  }
+  fn set_style(&mut self, style: EdgeStyle) {
+    self.style = style;
+  }
+}
+
+impl ExportData for NoteEdge {
+  fn attributes(&self) -> Vec<(&'static str, String)> {
+    let kind = match &self.kind {
+      EdgeKind::Link => "link",
+      EdgeKind::Embed => "embed",
+      EdgeKind::Tag => "tag",
+      EdgeKind::Folder => "folder",
+      EdgeKind::Heading => "heading",
+      EdgeKind::Typed(_) => "typed",
+    };
+    let mut attributes = vec![("kind", kind.to_owned()), ("line", self.line.to_string())];
+    if let EdgeKind::Typed(field) = &self.kind {
+      attributes.push(("field", field.clone()));
+    }
+    if let Some(anchor) = &self.anchor {
+      attributes.push(("anchor", anchor.to_string()));
+    }
+    attributes
+  }
+}
+
+impl ImportData for NoteEdge {
+  /// Imported edge: kinds other than the ones of exported edges are typed links of that name
+  fn from_attributes(_id: &str, attributes: &BTreeMap<String, String>) -> Self {
+    let kind = match (attributes.get("kind"), attributes.get("field")) {
+      (_, Some(field)) => EdgeKind::Typed(field.clone()),
+      (None, None) => EdgeKind::Link,
+      (Some(kind), None) => match kind.as_str() {
+        "link" => EdgeKind::Link,
+        "embed" => EdgeKind::Embed,
+        "tag" => EdgeKind::Tag,
+        "folder" => EdgeKind::Folder,
+        "heading" => EdgeKind::Heading,
+        kind => EdgeKind::Typed(kind.to_owned()),
+      },
+    };
+    let line = attributes
+      .get("line")
+      .and_then(|line| line.parse().ok())
+      .unwrap_or_default();
+    let anchor = attributes
+      .get("anchor")
+      .and_then(|anchor| Anchor::parse(anchor.strip_prefix('#')?));
+    Self::new(kind, line, anchor)
+  }
+}
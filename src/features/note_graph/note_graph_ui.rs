@@ -1,75 +1,299 @@
-use crate::features::note_graph::note_graph_mock::{MockGraph, NodeId, NODES};
+use crate::features::configuration::{Configuration, EdgeStyle, NodeSizeMetric, ReciprocalEdges};
+use crate::features::note_graph::color_groups::{
+  apply_color_groups, apply_community_colors, color_groups_ui,
+};
+use crate::features::note_graph::edge_styles::{apply_edge_styles, edge_styles_ui};
+use crate::features::note_graph::graph_filter::GraphFilter;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::features::note_graph::image_export::{
+  write_image, ImageFormat, ImageFrame, ImageSettings,
+};
+use crate::features::note_graph::local_graph::LocalGraph;
+use crate::features::note_graph::minimap::minimap_ui;
+use crate::features::note_graph::node_size::{apply_node_size, metric_values, node_size_ui};
+use crate::features::note_graph::{EdgeKind, NodeKind, NoteGraph};
+use crate::features::notes::{Anchor, NotesIndex};
+use crate::lib::export::{ExportFormat, ExportGraph};
 use crate::lib::fdp::eades_custom;
-use crate::lib::graph::{EdgeIncidents, Graph};
+use crate::lib::graph::{self, Direction, EdgeIncidents, Graph};
+use crate::lib::graph_algorithms::{self, GraphPath};
+use crate::lib::owned_graph::{EdgeId, NodeId};
+use crate::lib::subgraph::Subgraph;
 use egui::{containers::*, *};
-use epaint::CircleShape;
-use std::collections::HashMap;
+use epaint::{CircleShape, CubicBezierShape, Tessellator};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::f32::consts::TAU;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// How much to fade out nodes and edges that are not highlighted
+const DIM_FACTOR: f32 = 0.25;
+/// How much wider highlighted edges are drawn
+const HIGHLIGHT_WIDTH_FACTOR: f32 = 2.0;
+/// How far reciprocal edges bend to the side, relative to their length
+const RECIPROCAL_BEND: f32 = 0.15;
+/// How close to the community centers bundled edges are pulled
+const BUNDLE_STRENGTH: f32 = 0.7;
+/// Largest movement of a node, in points on the screen, at which the layout is settled
+pub(super) const SETTLED_MOVEMENT: f32 = 0.05;
+/// How much the zoom changes per scrolled point
+const ZOOM_SPEED: f32 = 0.002;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 4.0;
+
+/// What the user asked for in the graph
+pub enum NoteGraphAction {
+  /// Create the note of the ghost node and open it
+  CreateNote(NodeId),
+  /// Rename the note, rewriting links to it
+  Rename(NodeId),
+}
 
 /// NoteGraph ui state
 pub struct NoteGraphUi {
   node_positions: eades_custom::NodePositions<NodeId>,
-  note_graph: MockGraph,
   width: f32,
   height: f32,
+  zoom: f32,
+  /// Screen offset of the graph's origin from the center of the graph area
+  pan: Vec2,
   dragged_node: Option<NodeDrag>,
+  hovered_node: Option<NodeId>,
+  selected_nodes: HashSet<NodeId>,
+  /// Node the context menu was opened on, `None` for the background
+  context_node: Option<NodeId>,
+  /// Last note clicked without modifiers
+  open_note: Option<NodeId>,
+  show_local_graph: bool,
+  local_graph: LocalGraph,
+  filter: GraphFilter,
+  /// Filter and notes revision the current `visible_nodes` were collected with
+  filtered_with: Option<(GraphFilter, u64)>,
+  visible_nodes: HashSet<NodeId>,
+  visible_edges: HashSet<EdgeId>,
+  /// Kinds of the edges in the graph, the ones that can be styled and hidden
+  edge_kinds: Vec<EdgeKind>,
+  /// Color nodes by their community instead of color groups
+  color_by_community: bool,
+  /// Bumped when the configuration changes, so that it's not compared every frame
+  configuration_revision: u64,
+  /// Configuration revision, `color_by_community` and notes revision the nodes were last styled with
+  styled_with: Option<(u64, bool, u64)>,
+  /// Notes revision the `analytics` were computed for
+  analyzed_for: Option<u64>,
+  analytics: Analytics,
+  /// Metric the `metric_values` were computed for, `None` once the notes change
+  sized_by: Option<NodeSizeMetric>,
+  /// Value of the node size metric for each node
+  metric_values: HashMap<NodeId, f32>,
+  /// Whether the layout stopped moving, it's not progressed until something changes
+  layout_settled: bool,
+  /// Mesh of the graph as it was painted last, with what it was painted for
+  painted: Option<(PaintKey, Mesh)>,
+  #[cfg(not(target_arch = "wasm32"))]
+  export_format: ExportFormat,
+  /// File the graph is exported to, `graph.<extension>` if empty
+  #[cfg(not(target_arch = "wasm32"))]
+  export_path: String,
+  #[cfg(not(target_arch = "wasm32"))]
+  image: ImageSettings,
+}
+
+/// What the painted graph depends on, besides the layout, styles and filter
+#[derive(PartialEq)]
+struct PaintKey {
+  rect: Rect,
+  view: View,
+  hovered_node: Option<NodeId>,
+  selected_nodes: HashSet<NodeId>,
+}
+
+/// Summary of the graph structure, shown in the options
+#[derive(Default)]
+struct Analytics {
+  components: usize,
+  communities: usize,
+  /// Community of each node
+  node_communities: HashMap<NodeId, usize>,
+}
+
+impl Analytics {
+  fn new<G: Graph<NodeId = NodeId>>(graph: &G) -> Self {
+    let node_communities = graph_algorithms::louvain_communities(graph);
+    Self {
+      components: graph_algorithms::connected_components(graph).len(),
+      communities: node_communities.values().max().map_or(0, |max| max + 1),
+      node_communities,
+    }
+  }
 }
 
 /// Stuff that NodeData of the graph has to have
 pub trait NoteNodeData {
+  fn title(&self) -> &str;
+  /// Path of the note, relative to the notes directory
+  fn path(&self) -> &Path;
+  fn tags(&self) -> Vec<&str>;
+  fn word_count(&self) -> usize;
+  fn kind(&self) -> NodeKind;
   fn fill(&self) -> Color32;
   fn stroke(&self) -> Stroke;
   fn radius(&self) -> f32;
+  fn set_fill(&mut self, fill: Color32);
+  fn set_stroke(&mut self, stroke: Stroke);
+  fn set_radius(&mut self, radius: f32);
 }
 
 /// Stuff that EdgeData of the graph has to have
 pub trait NoteEdgeData {
-  fn stroke(&self) -> Stroke;
+  fn kind(&self) -> &EdgeKind;
+  /// Heading or block of the target the edge points to
+  fn anchor(&self) -> Option<&Anchor>;
+  fn style(&self) -> EdgeStyle;
+  fn set_style(&mut self, style: EdgeStyle);
+}
+
+/// Maps graph coordinates to the screen
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct View {
+  /// Screen position of the graph's origin
+  offset: Vec2,
+  zoom: f32,
+}
+
+impl View {
+  pub(super) const fn new(offset: Vec2) -> Self {
+    Self { offset, zoom: 1.0 }
+  }
+
+  /// View that fits the `bounds` (in graph coordinates) into the `rect` on the screen
+  pub(super) fn fitting(bounds: Rect, rect: Rect) -> Self {
+    const MARGIN: f32 = 0.9;
+    let zoom = (MARGIN * (rect.width() / bounds.width()).min(rect.height() / bounds.height()))
+      .clamp(MIN_ZOOM, MAX_ZOOM);
+    Self {
+      offset: rect.center().to_vec2() - bounds.center().to_vec2() * zoom,
+      zoom,
+    }
+  }
+
+  pub(super) fn to_screen(self, pos: Vec2) -> Pos2 {
+    (pos * self.zoom + self.offset).to_pos2()
+  }
+
+  pub(super) const fn zoom(self) -> f32 {
+    self.zoom
+  }
+
+  fn to_graph(self, pos: Pos2) -> Vec2 {
+    (pos.to_vec2() - self.offset) / self.zoom
+  }
 }
 
 /// Represents currently dragged node
 struct NodeDrag {
+  /// Offset from the pointer to the node, in graph coordinates
   offset: Vec2,
   node_id: NodeId,
 }
 
-impl Default for NoteGraphUi {
-  fn default() -> Self {
-    let note_graph = MockGraph;
-
-    // Initial node placement: a circle
-    const STEP: f32 = TAU / NODES.len() as f32;
-    const RADIUS: f32 = 300.0;
-    let node_positions = eades_custom::NodePositions(
-      note_graph
-        .iter_nodes()
-        .enumerate()
-        .map(|(index, (id, _))| {
-          let x = RADIUS * ((index as f32 * STEP).cos());
-          let y = RADIUS * ((index as f32 * STEP).sin());
-          (
-            id,
-            eades_custom::NodeFdpData {
-              pos: vec2(x, y),
-              force: Vec2::default(),
-            },
-          )
-        })
-        .collect::<HashMap<_, _>>(),
-    );
+/// How edges are laid out when painted
+#[derive(Clone, Copy)]
+pub(super) struct EdgeRouting<'a> {
+  pub(super) reciprocal: ReciprocalEdges,
+  /// Communities of the nodes, edges between communities are bundled if given
+  pub(super) bundles: Option<&'a HashMap<NodeId, usize>>,
+}
 
+/// Nodes and edges to emphasize: hovered and selected nodes,
+/// their neighbors and edges incident to them
+pub(super) struct Highlight {
+  nodes: HashSet<NodeId>,
+  edges: HashSet<EdgeId>,
+}
+
+impl NoteGraphUi {
+  pub fn new(note_graph: &NoteGraph) -> Self {
+    let node_positions = circle_layout(note_graph);
     Self {
       node_positions,
-      note_graph,
       width: Default::default(),
       height: Default::default(),
+      zoom: 1.0,
+      pan: Vec2::ZERO,
       dragged_node: None,
+      hovered_node: None,
+      selected_nodes: HashSet::new(),
+      context_node: None,
+      open_note: None,
+      show_local_graph: false,
+      local_graph: LocalGraph::default(),
+      filter: GraphFilter::default(),
+      filtered_with: None,
+      visible_nodes: HashSet::new(),
+      visible_edges: HashSet::new(),
+      edge_kinds: vec![],
+      color_by_community: false,
+      configuration_revision: 0,
+      styled_with: None,
+      analyzed_for: None,
+      analytics: Analytics::default(),
+      sized_by: None,
+      metric_values: HashMap::new(),
+      layout_settled: false,
+      painted: None,
+      #[cfg(not(target_arch = "wasm32"))]
+      export_format: ExportFormat::GraphMl,
+      #[cfg(not(target_arch = "wasm32"))]
+      export_path: String::new(),
+      #[cfg(not(target_arch = "wasm32"))]
+      image: ImageSettings::default(),
     }
   }
-}
 
-impl NoteGraphUi {
-  pub fn ui(&mut self, ui: &mut Ui) {
+  /// Show the graph and it's windows. Returns what was asked for in the context menu, if anything.
+  pub fn ui(
+    &mut self,
+    ui: &mut Ui,
+    configuration: &mut Configuration,
+    notes: &mut NotesIndex,
+  ) -> Option<NoteGraphAction> {
+    notes.set_tag_nodes(self.filter.show_tags);
+    notes.set_heading_nodes(self.filter.show_headings);
+    notes.set_folder_edges(!self.filter.hidden_edges.contains(&EdgeKind::Folder));
+    let revision = notes.revision();
+
+    // Analyze the graph only when the notes change
+    if self.analyzed_for != Some(revision) {
+      self.analytics = Analytics::new(&notes.graph);
+      self.sized_by = None;
+      self.analyzed_for = Some(revision);
+    }
+
+    // Restyle the nodes only when the configuration or the notes change
+    let style = (
+      self.configuration_revision,
+      self.color_by_community,
+      revision,
+    );
+    if self.styled_with != Some(style) {
+      let note_graph = &mut notes.graph;
+      self.place_new_nodes(note_graph);
+      let metric = configuration.node_size.metric;
+      if self.sized_by != Some(metric) {
+        self.metric_values = metric_values(note_graph, metric);
+        self.sized_by = Some(metric);
+      }
+      let communities = self
+        .color_by_community
+        .then_some(&self.analytics.node_communities);
+      apply_styles(note_graph, configuration, communities, &self.metric_values);
+      self.edge_kinds = edge_kinds(note_graph);
+      self.styled_with = Some(style);
+      self.layout_settled = false;
+      self.painted = None;
+    }
+
     let painter = Painter::new(
       ui.ctx().clone(),
       ui.layer_id(),
@@ -77,25 +301,159 @@ impl NoteGraphUi {
     );
     self.width = painter.clip_rect().width();
     self.height = painter.clip_rect().height();
-    self.paint(&painter);
 
-    // Required, or else will redraw only on mouse movement/other interactions
-    ui.ctx().request_repaint();
+    // Sense clicks on the whole graph area, so that windows on top of it
+    // don't leak hovers and clicks into the graph
+    let response = ui.interact(
+      painter.clip_rect(),
+      ui.id().with("note_graph"),
+      Sense::click(),
+    );
+
+    // Scroll - zoom around the pointer
+    let scroll = ui.input(|input| input.scroll_delta.y);
+    if let (Some(pointer), true) = (response.hover_pos(), scroll != 0.0) {
+      let view = self.view(painter.clip_rect());
+      let under_pointer = view.to_graph(pointer);
+      self.zoom = (self.zoom * (scroll * ZOOM_SPEED).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+      // Keep the point under the pointer in place
+      self.pan += pointer - self.view(painter.clip_rect()).to_screen(under_pointer);
+    }
+    let view = self.view(painter.clip_rect());
+
+    // Apply the filter only when it or the notes change
+    let filtered_with = (self.filter.clone(), revision);
+    if self.filtered_with.as_ref() != Some(&filtered_with) {
+      let query_matches = (!self.filter.query.trim().is_empty()).then(|| {
+        notes
+          .search(&self.filter.query)
+          .into_iter()
+          .map(|result| result.node_id)
+          .collect::<HashSet<_>>()
+      });
+      self.visible_nodes = self
+        .filter
+        .visible_nodes(&notes.graph, query_matches.as_ref());
+      self.visible_edges = self.filter.visible_edges(&notes.graph);
+      self.filtered_with = Some(filtered_with);
+      self.layout_settled = false;
+      self.painted = None;
+    }
+    let note_graph = &notes.graph;
+    let routing = EdgeRouting {
+      reciprocal: configuration.edge_styles.reciprocal,
+      bundles: (configuration.edge_styles.bundle).then_some(&self.analytics.node_communities),
+    };
+    let graph = Subgraph::new(note_graph, &self.visible_nodes).with_edges(&self.visible_edges);
+
+    // Hover - find the node under the pointer and show it's title
+    self.hovered_node = response
+      .hover_pos()
+      .and_then(|pos| node_at(&graph, &self.node_positions, view, pos));
+    if let Some(node_id) = self.hovered_node {
+      show_title_tooltip(ui, &graph, node_id);
+    }
+
+    // Click - select the node, shift-click - add it to (or remove from) selection
+    let mut opened_node = None;
+    if response.clicked() {
+      let shift = ui.input(|input| input.modifiers.shift);
+      let clicked_node = response
+        .interact_pointer_pos()
+        .and_then(|pos| node_at(&graph, &self.node_positions, view, pos));
+      match (clicked_node, shift) {
+        (Some(node_id), true) => {
+          if !self.selected_nodes.remove(&node_id) {
+            self.selected_nodes.insert(node_id);
+          }
+        }
+        (Some(node_id), false) => opened_node = Some(node_id),
+        (None, true) => (),
+        (None, false) => self.selected_nodes.clear(),
+      }
+    }
+
+    // Progress the FDP until it settles, only visible nodes take part in it
+    if !self.layout_settled {
+      let movement = eades_custom::step(&graph, &mut self.node_positions);
+      self.layout_settled = movement * self.zoom < SETTLED_MOVEMENT;
+      self.painted = None;
+    }
+
+    // Paint the graph again only if something has changed since the last time
+    let paint_key = PaintKey {
+      rect: painter.clip_rect(),
+      view,
+      hovered_node: self.hovered_node,
+      selected_nodes: self.selected_nodes.clone(),
+    };
+    let mesh = match &self.painted {
+      Some((key, mesh)) if *key == paint_key => mesh.clone(),
+      _ => {
+        // Two selected notes - highlight the path between them,
+        // otherwise - neighborhoods of hovered and selected notes
+        let focused = self.hovered_node.iter().chain(self.selected_nodes.iter());
+        let highlight = self
+          .selected_path(&graph)
+          .map(|path| Highlight {
+            nodes: path.nodes.into_iter().collect(),
+            edges: path.edges.into_iter().collect(),
+          })
+          .or_else(|| highlight(&graph, focused.copied()));
+        let shapes = graph_shapes(
+          &painter,
+          &graph,
+          &self.node_positions,
+          view,
+          highlight.as_ref(),
+          &self.selected_nodes,
+          routing,
+        );
+        let mesh = tessellate(ui.ctx(), shapes.into_shapes());
+        self.painted = Some((paint_key, mesh.clone()));
+        mesh
+      }
+    };
+    painter.add(Shape::mesh(mesh));
+
+    // Minimap - clicking or dragging it moves the camera there
+    if configuration.minimap.show {
+      let rect = painter.clip_rect();
+      let viewport = Rect::from_min_max(
+        view.to_graph(rect.min).to_pos2(),
+        view.to_graph(rect.max).to_pos2(),
+      );
+      let target = minimap_ui(
+        ui.ctx(),
+        rect,
+        &graph,
+        &self.node_positions,
+        viewport,
+        &configuration.minimap,
+      );
+      if let Some(target) = target {
+        self.pan = -target * self.zoom;
+        ui.ctx().request_repaint();
+      }
+    }
+
+    // Input makes egui redraw, the layout moving on it's own has to ask for it
+    if !self.layout_settled {
+      ui.ctx().request_repaint();
+    }
 
     // Make sure we allocate what we used (everything)
     // TODO: figure out why exactly this is needed (or not)
     ui.expand_to_include_rect(painter.clip_rect());
 
-    // On click - find the node and put it as dragged one
-    if ui.input(|input| input.pointer.primary_pressed()) {
+    // On press - find the node and put it as dragged one
+    if response.hovered() && ui.input(|input| input.pointer.primary_pressed()) {
       let pointer_pos = ui.input(|input| input.pointer.interact_pos());
-      self.dragged_node = self.node_positions.iter().find_map(|(&node_id, node_fdp)| {
-        let pointer_pos = pointer_pos.expect("pointer_pos is checked to not be `None` already");
-        let pointer_to_node = node_fdp.pos - pointer_pos.to_vec2();
-        let center = vec2(self.width / 2.0, self.height / 2.0);
-        let node_radius = self.note_graph.get_node(node_id).radius();
-        ((pointer_to_node + center).length() <= node_radius).then_some(NodeDrag {
-          offset: pointer_to_node,
+      self.dragged_node = pointer_pos.and_then(|pointer_pos| {
+        let node_id = node_at(&graph, &self.node_positions, view, pointer_pos)?;
+        let node_fdp = self.node_positions.get(&node_id)?;
+        Some(NodeDrag {
+          offset: node_fdp.pos - view.to_graph(pointer_pos),
           node_id,
         })
       });
@@ -108,93 +466,938 @@ impl NoteGraphUi {
         .node_positions
         .get_mut(&self.dragged_node.as_ref().unwrap().node_id)
         .unwrap()
-        .pos = interact_pos.to_vec2() + self.dragged_node.as_ref().unwrap().offset;
+        .pos = view.to_graph(interact_pos) + self.dragged_node.as_ref().unwrap().offset;
+      self.layout_settled = false;
     } else {
       // No longer dragged - relaase
       self.dragged_node = None;
     }
 
+    // Secondary click - context menu of the node under the pointer, or of the graph
+    if response.secondary_clicked() {
+      self.context_node = response
+        .interact_pointer_pos()
+        .and_then(|pos| node_at(&graph, &self.node_positions, view, pos));
+    }
+    let context_node = self
+      .context_node
+      .filter(|&node_id| note_graph.contains_node(node_id));
+    let mut action = None;
+    let mut fit_graph = false;
+    response.context_menu(|ui| match context_node {
+      Some(node_id) => {
+        if ui.button("Open").clicked() {
+          opened_node = Some(node_id);
+          ui.close_menu();
+        }
+        let is_note = note_graph.get_node(node_id).kind == NodeKind::Note;
+        if ui.add_enabled(is_note, Button::new("Rename…")).clicked() {
+          action = Some(NoteGraphAction::Rename(node_id));
+          ui.close_menu();
+        }
+      }
+      None => {
+        if ui.button("Fit graph").clicked() {
+          fit_graph = true;
+          ui.close_menu();
+        }
+      }
+    });
+
+    if let Some(node_id) = opened_node {
+      action = action.or_else(|| self.open_or_create(note_graph, node_id));
+    }
+
     // Popup window
     Window::new("Options")
       .frame(Frame::popup(ui.style()))
       .show(&ui.ctx().clone(), |ui| {
         ui.set_max_width(512.0);
-        self.options_ui(ui);
+        self.options_ui(ui, configuration, &notes.graph);
+      });
+
+    let mut clicked_in_local_graph = None;
+    Window::new("Local graph")
+      .open(&mut self.show_local_graph)
+      .show(&ui.ctx().clone(), |ui| {
+        clicked_in_local_graph = self.local_graph.ui(
+          ui,
+          note_graph,
+          revision,
+          self.open_note,
+          &self.selected_nodes,
+          EdgeRouting {
+            reciprocal: configuration.edge_styles.reciprocal,
+            bundles: None,
+          },
+        );
       });
+    if let Some(node_id) = clicked_in_local_graph {
+      action = action.or_else(|| self.open_or_create(note_graph, node_id));
+    }
+    if fit_graph {
+      self.fit_graph(note_graph);
+    }
+    action
+  }
+
+  /// Give positions to nodes added since the layout was made, next to their neighbors,
+  /// and forget nodes that were removed
+  fn place_new_nodes(&mut self, note_graph: &NoteGraph) {
+    const NEW_NODE_DISTANCE: f32 = 40.0;
+    // Spread nodes added next to the same neighbor
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+    let new_nodes = note_graph
+      .iter_nodes()
+      .map(|(node_id, _)| node_id)
+      .filter(|node_id| !self.node_positions.contains_key(node_id))
+      .collect::<Vec<_>>();
+    for (index, node_id) in new_nodes.into_iter().enumerate() {
+      let angle = index as f32 * GOLDEN_ANGLE;
+      let offset = NEW_NODE_DISTANCE * vec2(angle.cos(), angle.sin());
+      let pos = graph::neighbors(note_graph, node_id, Direction::Both)
+        .find_map(|neighbor| self.node_positions.get(&neighbor))
+        .map_or(offset, |neighbor| neighbor.pos + offset);
+      self.node_positions.insert(
+        node_id,
+        eades_custom::NodeFdpData {
+          pos,
+          force: Vec2::default(),
+        },
+      );
+    }
+    self
+      .node_positions
+      .retain(|node_id, _| note_graph.contains_node(*node_id));
+    self
+      .selected_nodes
+      .retain(|node_id| note_graph.contains_node(*node_id));
+    self.open_note = self
+      .open_note
+      .filter(|node_id| note_graph.contains_node(*node_id));
+  }
+
+  /// Shortest path between the two selected notes, following links if possible
+  fn selected_path<G>(&self, graph: &G) -> Option<GraphPath<NodeId, EdgeId>>
+  where
+    G: Graph<NodeId = NodeId, EdgeId = EdgeId>,
+  {
+    let [a, b] = *self.selected_nodes.iter().collect::<Vec<_>>() else {
+      return None;
+    };
+    graph_algorithms::shortest_path(graph, *a, *b, Direction::Outgoing)
+      .or_else(|| graph_algorithms::shortest_path(graph, *b, *a, Direction::Outgoing))
+      .or_else(|| graph_algorithms::shortest_path(graph, *a, *b, Direction::Both))
+  }
+
+  /// View of the graph drawn in the `rect`
+  fn view(&self, rect: Rect) -> View {
+    View {
+      offset: rect.center().to_vec2() + self.pan,
+      zoom: self.zoom,
+    }
+  }
+
+  /// Zoom and pan so that all visible notes fit the graph area
+  pub fn fit_graph(&mut self, note_graph: &NoteGraph) {
+    let graph = Subgraph::new(note_graph, &self.visible_nodes).with_edges(&self.visible_edges);
+    let Some(bounds) = graph_bounds(&graph, &self.node_positions) else {
+      return;
+    };
+    let rect = Rect::from_center_size(Pos2::ZERO, vec2(self.width, self.height));
+    let view = View::fitting(bounds, rect);
+    self.zoom = view.zoom;
+    self.pan = view.offset;
+  }
+
+  /// Progress the layout of the visible notes by one step
+  pub fn step_layout(&mut self, note_graph: &NoteGraph) {
+    let graph = Subgraph::new(note_graph, &self.visible_nodes).with_edges(&self.visible_edges);
+    eades_custom::step(&graph, &mut self.node_positions);
+    self.layout_settled = false;
+  }
+
+  /// Place all notes on a circle again
+  pub fn reset_layout(&mut self, note_graph: &NoteGraph) {
+    self.node_positions = circle_layout(note_graph);
+    self.layout_settled = false;
+    self.zoom = 1.0;
+    self.pan = Vec2::ZERO;
+  }
+
+  /// Open the visible neighbor of the open note that is nearest in the `direction`
+  /// (in screen coordinates). Without an open note, open the one nearest to the center.
+  pub fn focus_towards(&mut self, note_graph: &NoteGraph, direction: Vec2) {
+    // Neighbors further than this angle from the direction are not considered
+    const MAX_ANGLE_COS: f32 = 0.5;
+    let graph = Subgraph::new(note_graph, &self.visible_nodes).with_edges(&self.visible_edges);
+    let position = |node_id| self.node_positions.get(&node_id).map(|node| node.pos);
+    let nearest = match self
+      .open_note
+      .filter(|node_id| self.visible_nodes.contains(node_id))
+    {
+      Some(open_note) => {
+        let Some(from) = position(open_note) else {
+          return;
+        };
+        graph::neighbors(&graph, open_note, Direction::Both)
+          .filter_map(|node_id| Some((node_id, position(node_id)? - from)))
+          .filter(|(_, offset)| offset.normalized().dot(direction) >= MAX_ANGLE_COS)
+          .min_by(|(_, a), (_, b)| a.length().total_cmp(&b.length()))
+      }
+      None => {
+        let center = -self.pan / self.zoom;
+        graph
+          .iter_nodes()
+          .filter_map(|(node_id, _)| Some((node_id, position(node_id)? - center)))
+          .min_by(|(_, a), (_, b)| a.length().total_cmp(&b.length()))
+      }
+    };
+    if let Some((node_id, _)) = nearest {
+      self.open(node_id);
+    }
+  }
+
+  /// Restyle the nodes after the configuration was replaced
+  pub fn configuration_changed(&mut self) {
+    self.configuration_revision += 1;
+  }
+
+  pub fn toggle_local_graph(&mut self) {
+    self.show_local_graph = !self.show_local_graph;
+  }
+
+  /// Note that was opened last
+  pub const fn open_note(&self) -> Option<NodeId> {
+    self.open_note
+  }
+
+  /// Make the note current: select it and center the local graph on it
+  pub fn open(&mut self, node_id: NodeId) {
+    self.selected_nodes = HashSet::from([node_id]);
+    self.open_note = Some(node_id);
   }
 
-  fn options_ui(&mut self, ui: &mut Ui) {
-    if ui.button("Step").clicked() {
-      eades_custom::apply_forces(&self.note_graph, &mut self.node_positions);
-      for (id, _node) in self.note_graph.iter_nodes() {
-        let eades_custom::NodeFdpData { pos, force } = self.node_positions.get_mut(&id).unwrap();
-        *pos += *force;
+  /// Open the note, ghosts are left for the caller to create
+  fn open_or_create(&mut self, note_graph: &NoteGraph, node_id: NodeId) -> Option<NoteGraphAction> {
+    if note_graph.get_node(node_id).is_ghost() {
+      return Some(NoteGraphAction::CreateNote(node_id));
+    }
+    self.open(node_id);
+    None
+  }
+
+  /// Write the visible notes with their current positions in the `format`
+  pub fn export(
+    &self,
+    note_graph: &NoteGraph,
+    format: ExportFormat,
+    writer: &mut impl Write,
+  ) -> io::Result<()> {
+    let graph = Subgraph::new(note_graph, &self.visible_nodes).with_edges(&self.visible_edges);
+    ExportGraph::new(&graph, &self.node_positions).write(format, writer)
+  }
+
+  /// Draw the visible notes into an image, `width` pixels wide.
+  /// The image shows what the graph area shows, or the whole graph.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn export_image(
+    &self,
+    note_graph: &NoteGraph,
+    configuration: &Configuration,
+    visuals: &Visuals,
+    format: ImageFormat,
+    (width, whole_graph): (u32, bool),
+    writer: &mut impl Write,
+  ) -> io::Result<()> {
+    let graph = Subgraph::new(note_graph, &self.visible_nodes).with_edges(&self.visible_edges);
+    let frame = if whole_graph {
+      ImageFrame::whole_graph(&graph, &self.node_positions, width)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no notes are shown"))?
+    } else {
+      let size = vec2(self.width, self.height);
+      ImageFrame {
+        view: self.view(Rect::from_min_size(Pos2::ZERO, size)),
+        size,
+        pixels_per_point: width as f32 / self.width,
+      }
+    };
+    let routing = EdgeRouting {
+      reciprocal: configuration.edge_styles.reciprocal,
+      bundles: (configuration.edge_styles.bundle).then_some(&self.analytics.node_communities),
+    };
+    write_image(
+      writer,
+      format,
+      &graph,
+      &self.node_positions,
+      frame,
+      routing,
+      visuals,
+    )
+  }
+
+  #[cfg(not(target_arch = "wasm32"))]
+  fn export_ui(&mut self, ui: &mut Ui, note_graph: &NoteGraph, configuration: &Configuration) {
+    ui.horizontal(|ui| {
+      ComboBox::from_id_source("export_format")
+        .selected_text(self.export_format.to_string())
+        .show_ui(ui, |ui| {
+          for &format in ExportFormat::ALL {
+            ui.selectable_value(&mut self.export_format, format, format.to_string());
+          }
+        });
+      let default_path = format!("graph.{}", self.export_format.extension());
+      ui.add(TextEdit::singleline(&mut self.export_path).hint_text(&default_path));
+      if ui.button("Export").clicked() {
+        let path = if self.export_path.trim().is_empty() {
+          default_path
+        } else {
+          self.export_path.trim().to_owned()
+        };
+        let exported = std::fs::File::create(&path).and_then(|file| {
+          let mut writer = io::BufWriter::new(file);
+          self.export(note_graph, self.export_format, &mut writer)?;
+          writer.flush()
+        });
+        match exported {
+          Ok(()) => log::info!("exported the graph to {path}"),
+          Err(error) => log::error!("can't export the graph to {path}: {error}"),
+        }
       }
+    });
+
+    ui.horizontal(|ui| {
+      let image = &mut self.image;
+      ComboBox::from_id_source("image_format")
+        .selected_text(image.format.to_string())
+        .show_ui(ui, |ui| {
+          for &format in ImageFormat::ALL {
+            ui.selectable_value(&mut image.format, format, format.to_string());
+          }
+        });
+      ui.add_enabled(
+        image.format == ImageFormat::Png,
+        DragValue::new(&mut image.width)
+          .clamp_range(16..=16384)
+          .suffix(" px"),
+      );
+      ui.checkbox(&mut image.whole_graph, "Whole graph");
+    });
+    ui.horizontal(|ui| {
+      let default_path = format!("graph.{}", self.image.format.extension());
+      ui.add(TextEdit::singleline(&mut self.image.path).hint_text(&default_path));
+      if ui.button("Export image").clicked() {
+        let path = if self.image.path.trim().is_empty() {
+          default_path
+        } else {
+          self.image.path.trim().to_owned()
+        };
+        let ImageSettings {
+          format,
+          width,
+          whole_graph,
+          ..
+        } = self.image;
+        let exported = std::fs::File::create(&path).and_then(|file| {
+          let mut writer = io::BufWriter::new(file);
+          self.export_image(
+            note_graph,
+            configuration,
+            ui.visuals(),
+            format,
+            (width, whole_graph),
+            &mut writer,
+          )?;
+          writer.flush()
+        });
+        match exported {
+          Ok(()) => log::info!("exported the image to {path}"),
+          Err(error) => log::error!("can't export the image to {path}: {error}"),
+        }
+      }
+    });
+  }
+
+  fn options_ui(&mut self, ui: &mut Ui, configuration: &mut Configuration, note_graph: &NoteGraph) {
+    // Whether the styles changed, the minimap and filters don't need a restyle
+    let mut changed = false;
+    ui.horizontal(|ui| {
+      ui.checkbox(&mut self.show_local_graph, "Local graph");
+      ui.checkbox(&mut configuration.minimap.show, "Minimap");
+    });
+    CollapsingHeader::new("Filters").show(ui, |ui| self.filter.ui(ui));
+    CollapsingHeader::new("Colors").show(ui, |ui| {
+      ui.horizontal(|ui| {
+        ui.label("Fill");
+        changed |= ui
+          .color_edit_button_srgba(&mut configuration.primary_color)
+          .changed();
+        ui.label("Stroke");
+        changed |= ui
+          .color_edit_button_srgba(&mut configuration.secondary_color)
+          .changed();
+      });
+      ui.checkbox(&mut self.color_by_community, "Color by community");
+      ui.add_enabled_ui(!self.color_by_community, |ui| {
+        changed |= color_groups_ui(ui, &mut configuration.color_groups);
+      });
+    });
+    CollapsingHeader::new("Node size").show(ui, |ui| {
+      changed |= node_size_ui(ui, &mut configuration.node_size);
+    });
+    CollapsingHeader::new("Edges").show(ui, |ui| {
+      changed |= edge_styles_ui(
+        ui,
+        &mut configuration.edge_styles,
+        &self.edge_kinds,
+        &mut self.filter.hidden_edges,
+      );
+    });
+    if changed {
+      self.configuration_changed();
+    }
+    CollapsingHeader::new("Analytics").show(ui, |ui| {
+      ui.label(format!(
+        "Connected components: {}",
+        self.analytics.components
+      ));
+      ui.label(format!("Communities: {}", self.analytics.communities));
+      ui.label("Select two notes with shift-click to see the shortest path between them");
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    CollapsingHeader::new("Export").show(ui, |ui| {
+      self.export_ui(ui, note_graph, configuration);
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    if ui.button("Save configuration").clicked() {
+      if let Err(error) = configuration.save() {
+        log::error!("can't save configuration: {error}");
+      }
+    }
+  }
+}
+
+/// Style the nodes and edges as configured, with the nodes colored by the `communities` if given
+/// and sized by the `metric_values` of the configured metric
+pub fn apply_styles(
+  note_graph: &mut NoteGraph,
+  configuration: &Configuration,
+  communities: Option<&HashMap<NodeId, usize>>,
+  metric_values: &HashMap<NodeId, f32>,
+) {
+  apply_color_groups(note_graph, configuration);
+  if let Some(communities) = communities {
+    apply_community_colors(note_graph, communities);
+  }
+  apply_node_size(note_graph, &configuration.node_size, metric_values);
+  apply_edge_styles(note_graph, &configuration.edge_styles);
+}
+
+/// Show a tooltip with the title of the node at the pointer
+pub(super) fn show_title_tooltip<G>(ui: &Ui, graph: &G, node_id: NodeId)
+where
+  G: Graph<NodeId = NodeId>,
+  G::NodeData: NoteNodeData,
+{
+  let title = graph.get_node(node_id).title();
+  show_tooltip_at_pointer(ui.ctx(), ui.id().with("note_graph_tooltip"), |ui| {
+    ui.label(title);
+  });
+}
+
+/// Kinds of the edges in the graph, and folder edges that are only there while shown
+fn edge_kinds(note_graph: &NoteGraph) -> Vec<EdgeKind> {
+  note_graph
+    .iter_edges()
+    .map(|(_, edge)| edge.kind.clone())
+    .chain([EdgeKind::Folder])
+    .collect::<BTreeSet<_>>()
+    .into_iter()
+    .collect()
+}
+
+/// Lay out the whole graph as the graph view does, without showing it:
+/// notes start on a circle and move until the layout settles or `max_steps` are taken
+pub fn settled_layout(
+  note_graph: &NoteGraph,
+  max_steps: usize,
+) -> eades_custom::NodePositions<NodeId> {
+  let mut node_positions = circle_layout(note_graph);
+  for _ in 0..max_steps {
+    if eades_custom::step(note_graph, &mut node_positions) < SETTLED_MOVEMENT {
+      break;
     }
-    crate::ui::reset_button(ui, self);
   }
+  node_positions
+}
 
-  fn paint(&mut self, painter: &Painter) {
-    let mut shapes: Vec<Shape> = Vec::new();
+/// Initial node placement: a circle
+fn circle_layout(note_graph: &NoteGraph) -> eades_custom::NodePositions<NodeId> {
+  let step = TAU / note_graph.iter_nodes().count() as f32;
+  const RADIUS: f32 = 300.0;
+  eades_custom::NodePositions(
+    note_graph
+      .iter_nodes()
+      .enumerate()
+      .map(|(index, (id, _))| {
+        let x = RADIUS * ((index as f32 * step).cos());
+        let y = RADIUS * ((index as f32 * step).sin());
+        (
+          id,
+          eades_custom::NodeFdpData {
+            pos: vec2(x, y),
+            force: Vec2::default(),
+          },
+        )
+      })
+      .collect::<HashMap<_, _>>(),
+  )
+}
 
-    // Progress the FDP
-    // TODO: maybe decouple FDP force application from rendering/painting
-    eades_custom::apply_forces(&self.note_graph, &mut self.node_positions);
+/// Bounds of the nodes with their radii, in graph coordinates.
+/// Returns `None` for a graph without nodes.
+pub(super) fn graph_bounds<G>(
+  graph: &G,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+) -> Option<Rect>
+where
+  G: Graph<NodeId = NodeId>,
+  G::NodeData: NoteNodeData,
+{
+  graph
+    .iter_nodes()
+    .filter_map(|(node_id, node)| {
+      let pos = node_positions.get(&node_id)?.pos.to_pos2();
+      Some(Rect::from_center_size(
+        pos,
+        Vec2::splat(node.radius() * 2.0),
+      ))
+    })
+    .reduce(Rect::union)
+}
 
-    // Render nodes & apply the FDP forces to positions
-    for (id, node) in self.note_graph.iter_nodes() {
-      let eades_custom::NodeFdpData { pos, force } = self.node_positions.get_mut(&id).unwrap();
+/// Find the node under the `pos` (in screen coordinates)
+pub(super) fn node_at<G>(
+  graph: &G,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+  view: View,
+  pos: Pos2,
+) -> Option<NodeId>
+where
+  G: Graph<NodeId = NodeId>,
+  G::NodeData: NoteNodeData,
+{
+  graph.iter_nodes().find_map(|(node_id, node)| {
+    let node_fdp = node_positions.get(&node_id)?;
+    ((view.to_screen(node_fdp.pos) - pos).length() <= node.radius() * view.zoom).then_some(node_id)
+  })
+}
 
-      // Apply forces
-      // TODO: maybe decouple FDP force application from rendering/painting
-      *pos += *force;
+/// Collect `focused` nodes with their neighborhoods.
+/// Returns `None` if there is nothing to highlight.
+pub(super) fn highlight<G>(graph: &G, focused: impl Iterator<Item = NodeId>) -> Option<Highlight>
+where
+  G: Graph<NodeId = NodeId, EdgeId = EdgeId>,
+{
+  let mut highlight = Highlight {
+    nodes: HashSet::new(),
+    edges: HashSet::new(),
+  };
+  for node_id in focused {
+    highlight.nodes.insert(node_id);
+    for (edge_id, ..) in graph.iter_incidents(node_id) {
+      let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+      highlight.nodes.extend([node_from, node_to]);
+      highlight.edges.insert(edge_id);
+    }
+  }
+  (!highlight.nodes.is_empty()).then_some(highlight)
+}
 
-      // Render
-      let pos = pos.to_pos2() + vec2(self.width / 2.0, self.height / 2.0);
-      shapes.push(Shape::Circle(CircleShape {
+/// Shapes of the nodes, and the lines the edges are drawn with
+pub(super) struct GraphShapes {
+  pub(super) nodes: Vec<Shape>,
+  pub(super) edges: Vec<EdgeLines>,
+}
+
+/// Straight lines an edge is drawn with: it's segments, dashes and arrowheads
+pub(super) struct EdgeLines {
+  pub(super) stroke: Stroke,
+  pub(super) lines: Vec<[Pos2; 2]>,
+}
+
+impl GraphShapes {
+  /// Shapes of the nodes, followed by a single mesh of all edges
+  pub(super) fn into_shapes(self) -> Vec<Shape> {
+    let mut edges = Mesh::default();
+    for EdgeLines { stroke, lines } in self.edges {
+      for line in lines {
+        add_line(&mut edges, line, stroke);
+      }
+    }
+    let mut shapes = self.nodes;
+    shapes.push(Shape::mesh(edges));
+    shapes
+  }
+}
+
+/// Shapes of the nodes and edges of the `graph` as seen through the `view`.
+/// Whatever is outside of the painter's clip rect is left out.
+pub(super) fn graph_shapes<G>(
+  painter: &Painter,
+  graph: &G,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+  view: View,
+  highlight: Option<&Highlight>,
+  selected_nodes: &HashSet<NodeId>,
+  routing: EdgeRouting,
+) -> GraphShapes
+where
+  G: Graph<NodeId = NodeId, EdgeId = EdgeId>,
+  G::NodeData: NoteNodeData,
+  G::EdgeData: NoteEdgeData,
+{
+  let mut shapes: Vec<Shape> = Vec::new();
+  let selection_stroke = painter.ctx().style().visuals.selection.stroke;
+  let clip_rect = painter.clip_rect();
+
+  // Render nodes
+  for (id, node) in graph.iter_nodes() {
+    let eades_custom::NodeFdpData { pos, .. } = node_positions.get(&id).unwrap();
+    let pos = view.to_screen(*pos);
+    let radius = node.radius() * view.zoom;
+    if !clip_rect.expand(radius).contains(pos) {
+      continue;
+    }
+    let (mut fill, mut stroke) = (node.fill(), node.stroke());
+    if selected_nodes.contains(&id) {
+      stroke.color = selection_stroke.color;
+    }
+    if highlight.is_some_and(|h| !h.nodes.contains(&id)) {
+      fill = fill.gamma_multiply(DIM_FACTOR);
+      stroke.color = stroke.color.gamma_multiply(DIM_FACTOR);
+    }
+    match node.kind() {
+      NodeKind::Note => shapes.push(Shape::Circle(CircleShape {
         center: pos,
-        radius: node.radius(),
-        fill: node.fill(),
-        stroke: node.stroke(),
-      }));
-    }
-
-    // Render edges
-    for (edge_id, edge) in self.note_graph.iter_edges() {
-      let EdgeIncidents { node_from, node_to } = self.note_graph.get_edge_incidents(edge_id);
-      let eades_custom::NodeFdpData { pos: start, .. } =
-        self.node_positions.get(&node_from).unwrap();
-      let eades_custom::NodeFdpData { pos: end, .. } = self.node_positions.get(&node_to).unwrap();
-      let start_node = self.note_graph.get_node(node_from);
-      let end_node = self.note_graph.get_node(node_to);
-      let start = start.to_pos2() + vec2(self.width / 2.0, self.height / 2.0);
-      let end = end.to_pos2() + vec2(self.width / 2.0, self.height / 2.0);
-      let start_offset = (end - start).normalized() * start_node.radius();
-      let end_offset = (start - end).normalized() * end_node.radius();
-
-      let start = start + start_offset;
-      let end = end + end_offset;
-
-      // Draw a line from node to node
-      shapes.push(Shape::line_segment([start, end], edge.stroke()));
-
-      // Draw arrow head
-      const ARROW_HEAD_ANGLE_DEGREES: f32 = 20.0;
-      const ARROW_HEAD_LENGTH: f32 = 13.0;
-      let theta = TAU / 360.0 * ARROW_HEAD_ANGLE_DEGREES;
-      let norm_towards_start = (start - end).normalized() * ARROW_HEAD_LENGTH;
-      shapes.extend([theta, -theta].into_iter().map(|theta| {
-        let arrow_part = pos2(
-          norm_towards_start
-            .x
-            .mul_add(theta.cos(), -norm_towards_start.y * theta.sin()),
-          norm_towards_start
-            .x
-            .mul_add(theta.sin(), norm_towards_start.y * theta.cos()),
-        ) + end.to_vec2();
-        Shape::line_segment([end, arrow_part], edge.stroke())
-      }))
-    }
-    painter.extend(shapes);
+        radius,
+        fill,
+        stroke,
+      })),
+      NodeKind::Ghost => {
+        // Hollow, with a dashed outline
+        const GHOST_SEGMENTS: usize = 32;
+        let outline = (0..=GHOST_SEGMENTS)
+          .map(|index| {
+            let angle = TAU * index as f32 / GHOST_SEGMENTS as f32;
+            pos + radius * vec2(angle.cos(), angle.sin())
+          })
+          .collect::<Vec<_>>();
+        shapes.extend(Shape::dashed_line(&outline, stroke, 6.0, 4.0));
+      }
+      NodeKind::Tag => {
+        // Diamond
+        let points = [
+          vec2(0.0, -1.0),
+          vec2(1.0, 0.0),
+          vec2(0.0, 1.0),
+          vec2(-1.0, 0.0),
+        ]
+        .map(|corner| pos + radius * corner)
+        .to_vec();
+        shapes.push(Shape::convex_polygon(points, fill, stroke));
+      }
+      NodeKind::Heading => {
+        // Rounded square
+        let rect = Rect::from_center_size(pos, Vec2::splat(2.0 * radius));
+        let rounding = radius / 3.0;
+        shapes.push(Shape::rect_filled(rect, rounding, fill));
+        shapes.push(Shape::rect_stroke(rect, rounding, stroke));
+      }
+    }
+  }
+
+  // Render edges
+  let pairs = graph
+    .iter_edges()
+    .map(|(edge_id, _)| graph.get_edge_incidents(edge_id))
+    .map(|EdgeIncidents { node_from, node_to }| (node_from, node_to))
+    .collect::<HashSet<_>>();
+  let centers = routing
+    .bundles
+    .map(|communities| community_centers(graph, node_positions, communities));
+  let mut edges = vec![];
+  for (edge_id, edge) in graph.iter_edges() {
+    let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+    let reciprocal = node_from != node_to && pairs.contains(&(node_to, node_from));
+    let merged = reciprocal && routing.reciprocal == ReciprocalEdges::Merged;
+    // Merged pairs are drawn once
+    if merged && node_from > node_to {
+      continue;
+    }
+    let eades_custom::NodeFdpData { pos: start, .. } = node_positions.get(&node_from).unwrap();
+    let eades_custom::NodeFdpData { pos: end, .. } = node_positions.get(&node_to).unwrap();
+    let start_node = graph.get_node(node_from);
+    let end_node = graph.get_node(node_to);
+    let start = view.to_screen(*start);
+    let end = view.to_screen(*end);
+
+    // Control points of a cubic curve from center to center, the same as the ends for a line
+    let (control_from, control_to) = if reciprocal && !merged {
+      // Bend to the right of the direction, so that the reverse edge bends the other way
+      let bend = (end - start).rot90() * RECIPROCAL_BEND;
+      (
+        start.lerp(end, 1.0 / 3.0) + bend,
+        start.lerp(end, 2.0 / 3.0) + bend,
+      )
+    } else {
+      let community_center = |node_id| {
+        let communities = routing.bundles?;
+        let center = centers.as_ref()?.get(communities.get(&node_id)?)?;
+        Some(view.to_screen(*center))
+      };
+      match (community_center(node_from), community_center(node_to)) {
+        (Some(center_from), Some(center_to)) if center_from != center_to => (
+          start.lerp(center_from, BUNDLE_STRENGTH),
+          end.lerp(center_to, BUNDLE_STRENGTH),
+        ),
+        _ => (start, end),
+      }
+    };
+    // The curve stays within the bounds of it's control points
+    if !Rect::from_points(&[start, control_from, control_to, end]).intersects(clip_rect) {
+      continue;
+    }
+
+    let straight = (control_from, control_to) == (start, end);
+
+    // Cut the ends at the node outlines
+    let start = start + towards(start, control_from, end) * start_node.radius() * view.zoom;
+    let end = end + towards(end, control_to, start) * end_node.radius() * view.zoom;
+
+    let style = edge.style();
+    let mut stroke = Stroke::new(style.width, style.color);
+    match highlight {
+      Some(highlight) if highlight.edges.contains(&edge_id) => {
+        stroke.width *= HIGHLIGHT_WIDTH_FACTOR;
+      }
+      Some(_) => stroke.color = stroke.color.gamma_multiply(DIM_FACTOR),
+      None => (),
+    }
+
+    // Draw a line or a curve from node to node
+    let points = if straight {
+      vec![start, end]
+    } else {
+      CubicBezierShape::from_points_stroke(
+        [start, control_from, control_to, end],
+        false,
+        Color32::TRANSPARENT,
+        stroke,
+      )
+      .flatten(None)
+    };
+    let mut lines = if style.dashed {
+      dashed_lines(&points)
+    } else {
+      points
+        .windows(2)
+        .map(|segment| [segment[0], segment[1]])
+        .collect()
+    };
+
+    if style.arrow {
+      lines.extend(arrow_head_lines(end, towards(end, control_to, start)));
+      if merged {
+        lines.extend(arrow_head_lines(start, towards(start, control_from, end)));
+      }
+    }
+    edges.push(EdgeLines { stroke, lines });
+  }
+  GraphShapes {
+    nodes: shapes,
+    edges,
+  }
+}
+
+/// Tessellate the shapes into one mesh, that can be painted again while nothing changes
+pub(super) fn tessellate(ctx: &Context, shapes: Vec<Shape>) -> Mesh {
+  let mut tessellator = Tessellator::new(
+    ctx.pixels_per_point(),
+    ctx.tessellation_options(|options| *options),
+    ctx.fonts(|fonts| fonts.font_image_size()),
+    vec![],
+  );
+  let mut mesh = Mesh::default();
+  for shape in shapes {
+    tessellator.tessellate_shape(shape, &mut mesh);
+  }
+  mesh
+}
+
+/// Unit vector from `from` towards `to`, or towards the `fallback` if they are the same point
+fn towards(from: Pos2, to: Pos2, fallback: Pos2) -> Vec2 {
+  let direction = if to == from {
+    fallback - from
+  } else {
+    to - from
+  };
+  direction.normalized()
+}
+
+/// Add a straight line to the mesh, as a quad
+fn add_line(mesh: &mut Mesh, [from, to]: [Pos2; 2], stroke: Stroke) {
+  let side = (to - from).normalized().rot90() * (stroke.width / 2.0);
+  let index = mesh.vertices.len() as u32;
+  for pos in [from + side, from - side, to + side, to - side] {
+    mesh.colored_vertex(pos, stroke.color);
+  }
+  mesh.add_triangle(index, index + 1, index + 2);
+  mesh.add_triangle(index + 1, index + 2, index + 3);
+}
+
+/// Dashes of a dashed line through the `points`
+fn dashed_lines(points: &[Pos2]) -> Vec<[Pos2; 2]> {
+  const DASH_LENGTH: f32 = 8.0;
+  const GAP_LENGTH: f32 = 5.0;
+  let mut dashes = vec![];
+  // Distance from the start of the current dash
+  let mut phase = 0.0;
+  for segment in points.windows(2) {
+    let [from, to] = [segment[0], segment[1]];
+    let length = (to - from).length();
+    let mut distance = 0.0;
+    while distance < length {
+      let in_dash = phase < DASH_LENGTH;
+      let left = if in_dash {
+        DASH_LENGTH
+      } else {
+        DASH_LENGTH + GAP_LENGTH
+      } - phase;
+      let step = left.min(length - distance);
+      if in_dash {
+        dashes.push([distance / length, (distance + step) / length].map(|t| from.lerp(to, t)));
+      }
+      distance += step;
+      phase = (phase + step) % (DASH_LENGTH + GAP_LENGTH);
+    }
+  }
+  dashes
+}
+
+/// The two strokes of an arrowhead at the `tip`, opening in the `back` direction
+fn arrow_head_lines(tip: Pos2, back: Vec2) -> [[Pos2; 2]; 2] {
+  const ARROW_HEAD_ANGLE_DEGREES: f32 = 20.0;
+  const ARROW_HEAD_LENGTH: f32 = 13.0;
+  let theta = TAU / 360.0 * ARROW_HEAD_ANGLE_DEGREES;
+  let back = back * ARROW_HEAD_LENGTH;
+  [theta, -theta].map(|theta| {
+    let arrow_part = pos2(
+      back.x.mul_add(theta.cos(), -back.y * theta.sin()),
+      back.x.mul_add(theta.sin(), back.y * theta.cos()),
+    ) + tip.to_vec2();
+    [tip, arrow_part]
+  })
+}
+
+/// Mean position of the nodes of each community, in graph coordinates
+fn community_centers<G>(
+  graph: &G,
+  node_positions: &eades_custom::NodePositions<NodeId>,
+  communities: &HashMap<NodeId, usize>,
+) -> HashMap<usize, Vec2>
+where
+  G: Graph<NodeId = NodeId>,
+{
+  let mut sums = HashMap::<usize, (Vec2, f32)>::new();
+  for (node_id, _) in graph.iter_nodes() {
+    let (Some(community), Some(node)) = (communities.get(&node_id), node_positions.get(&node_id))
+    else {
+      continue;
+    };
+    let (sum, count) = sums.entry(*community).or_default();
+    *sum += node.pos;
+    *count += 1.0;
+  }
+  sums
+    .into_iter()
+    .map(|(community, (sum, count))| (community, sum / count))
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::note_graph::{NoteEdge, NoteNode};
+  use crate::features::notes::Note;
+  use crate::lib::fdp::eades_custom::{NodeFdpData, NodePositions};
+
+  #[test]
+  fn reciprocal_edges_are_curved_or_merged() {
+    let mut graph = NoteGraph::default();
+    let a = graph.insert_node(NoteNode::new(Note::new("a.md", "")));
+    let b = graph.insert_node(NoteNode::new(Note::new("b.md", "")));
+    graph.insert_edge(a, b, NoteEdge::new(EdgeKind::Link, 0, None));
+    graph.insert_edge(b, a, NoteEdge::new(EdgeKind::Link, 0, None));
+    let node_positions = NodePositions(HashMap::from(
+      [(a, vec2(-100.0, 0.0)), (b, vec2(100.0, 0.0))].map(|(node_id, pos)| {
+        (
+          node_id,
+          NodeFdpData {
+            pos,
+            force: Vec2::ZERO,
+          },
+        )
+      }),
+    ));
+    let ctx = Context::default();
+    let rect = Rect::from_min_size(Pos2::ZERO, vec2(400.0, 200.0));
+    ctx.begin_frame(RawInput {
+      screen_rect: Some(rect),
+      ..Default::default()
+    });
+    let painter = Painter::new(ctx.clone(), LayerId::background(), rect);
+    let edges = |reciprocal| {
+      let routing = EdgeRouting {
+        reciprocal,
+        bundles: None,
+      };
+      let view = View::new(rect.center().to_vec2());
+      graph_shapes(
+        &painter,
+        &graph,
+        &node_positions,
+        view,
+        None,
+        &HashSet::new(),
+        routing,
+      )
+      .edges
+    };
+
+    let curved = edges(ReciprocalEdges::Curved);
+    assert_eq!(curved.len(), 2);
+    // Bent to opposite sides of the line between the nodes
+    let bend = |edge: &EdgeLines| {
+      edge
+        .lines
+        .iter()
+        .map(|[from, _]| from.y - rect.center().y)
+        .sum::<f32>()
+    };
+    assert!(bend(&curved[0]) * bend(&curved[1]) < 0.0);
+
+    let merged = edges(ReciprocalEdges::Merged);
+    assert_eq!(merged.len(), 1);
+    // A straight line with an arrowhead at both ends
+    assert_eq!(merged[0].lines.len(), 1 + 2 * 2);
+    let tips = merged[0].lines[1..]
+      .iter()
+      .map(|[tip, _]| tip.x < rect.center().x)
+      .collect::<Vec<_>>();
+    assert_eq!(tips, vec![false, false, true, true]);
+    // Nothing is shown, the output of the frame isn't needed
+    let _ = ctx.end_frame();
   }
 }
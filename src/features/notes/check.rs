@@ -0,0 +1,265 @@
+use crate::features::note_graph::NodeKind;
+use crate::features::notes::{link_diagnostics, NotesIndex};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Problems of the notes found by `basalt check`, and some statistics.
+/// Lines are counted from 1.
+#[derive(Serialize, Debug, PartialEq, Eq, Default)]
+pub struct CheckReport {
+  pub statistics: Statistics,
+  /// Links to notes that don't exist
+  pub broken_links: Vec<LinkProblem>,
+  /// Wikilinks matching several notes
+  pub ambiguous_links: Vec<LinkProblem>,
+  /// Notes and attachments without links to or from other notes
+  pub orphans: Vec<PathBuf>,
+  /// Notes sharing a title, ignoring case
+  pub duplicate_titles: Vec<DuplicateTitle>,
+  pub front_matter_errors: Vec<FrontMatterProblem>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Default)]
+pub struct Statistics {
+  /// Markdown notes
+  pub notes: usize,
+  /// Files other than Markdown notes
+  pub attachments: usize,
+  /// Links, embeds and typed links, broken ones included
+  pub links: usize,
+  pub tags: usize,
+  pub words: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct LinkProblem {
+  /// Note the link is in
+  pub path: PathBuf,
+  pub line: usize,
+  pub target: String,
+  /// Notes the link could point to, the first one is used
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub candidates: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct DuplicateTitle {
+  pub title: String,
+  pub paths: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct FrontMatterProblem {
+  pub path: PathBuf,
+  pub line: usize,
+  pub message: String,
+}
+
+impl CheckReport {
+  /// Number of problems that fail the check, orphans are only reported
+  pub fn error_count(&self) -> usize {
+    self.broken_links.len()
+      + self.ambiguous_links.len()
+      + self.duplicate_titles.len()
+      + self.front_matter_errors.len()
+  }
+}
+
+/// Check the links, titles and front matter of the notes
+pub fn check_notes(notes: &NotesIndex) -> CheckReport {
+  let mut report = CheckReport::default();
+  let path = |node_id: NodeId| notes.graph.get_node(node_id).note.path.clone();
+
+  let mut note_ids = notes
+    .graph
+    .iter_nodes()
+    .filter(|(_, node)| node.kind == NodeKind::Note)
+    .map(|(node_id, _)| node_id)
+    .collect::<Vec<_>>();
+  note_ids.sort_by_key(|&node_id| path(node_id));
+
+  let mut tags = HashSet::new();
+  let mut titles = BTreeMap::<String, Vec<NodeId>>::new();
+  for &node_id in &note_ids {
+    let note = &notes.graph.get_node(node_id).note;
+    if !note.is_markdown() {
+      report.statistics.attachments += 1;
+      continue;
+    }
+    report.statistics.notes += 1;
+    report.statistics.words += note.word_count;
+    tags.extend(note.tags.iter().map(|tag| tag.to_lowercase()));
+    titles
+      .entry(note.title.to_lowercase())
+      .or_default()
+      .push(node_id);
+    if let Some(error) = &note.front_matter_error {
+      report.front_matter_errors.push(FrontMatterProblem {
+        path: note.path.clone(),
+        line: error.line + 1,
+        message: error.message.clone(),
+      });
+    }
+  }
+  report.statistics.tags = tags.len();
+  report.statistics.links = notes
+    .graph
+    .iter_edges()
+    .filter(|(_, edge)| edge.kind.is_link())
+    .count();
+
+  for diagnostic in link_diagnostics(notes) {
+    let problem = LinkProblem {
+      path: path(diagnostic.source),
+      line: diagnostic.link.line + 1,
+      target: diagnostic.link.target.clone(),
+      candidates: diagnostic.candidates.iter().map(|&id| path(id)).collect(),
+    };
+    if diagnostic.is_ambiguous() {
+      report.ambiguous_links.push(problem);
+    } else {
+      report.broken_links.push(problem);
+    }
+  }
+
+  report.orphans = note_ids
+    .iter()
+    .filter(|&&node_id| {
+      !notes.graph.iter_incidents(node_id).any(|(edge_id, edge)| {
+        let incidents = notes.graph.get_edge_incidents(edge_id);
+        edge.kind.is_link() && incidents.node_from != incidents.node_to
+      })
+    })
+    .map(|&node_id| path(node_id))
+    .collect();
+
+  report.duplicate_titles = titles
+    .into_values()
+    .filter(|node_ids| node_ids.len() > 1)
+    .map(|node_ids| DuplicateTitle {
+      title: notes.graph.get_node(node_ids[0]).note.title.clone(),
+      paths: node_ids.into_iter().map(path).collect(),
+    })
+    .collect();
+  report
+}
+
+/// Problems one per line as `path:line: problem`, then the statistics
+impl fmt::Display for CheckReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let paths = |paths: &[PathBuf]| {
+      paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+    };
+    for problem in &self.front_matter_errors {
+      let (path, line) = (problem.path.display(), problem.line);
+      writeln!(
+        f,
+        "{path}:{line}: malformed front matter: {}",
+        problem.message
+      )?;
+    }
+    for problem in &self.broken_links {
+      let (path, line) = (problem.path.display(), problem.line);
+      writeln!(f, "{path}:{line}: broken link `{}`", problem.target)?;
+    }
+    for problem in &self.ambiguous_links {
+      let (path, line) = (problem.path.display(), problem.line);
+      writeln!(
+        f,
+        "{path}:{line}: ambiguous link `{}`: {}",
+        problem.target,
+        paths(&problem.candidates)
+      )?;
+    }
+    for duplicate in &self.duplicate_titles {
+      writeln!(
+        f,
+        "duplicate title `{}`: {}",
+        duplicate.title,
+        paths(&duplicate.paths)
+      )?;
+    }
+    for orphan in &self.orphans {
+      writeln!(f, "{}: orphan", orphan.display())?;
+    }
+    let Statistics {
+      notes,
+      attachments,
+      links,
+      tags,
+      words,
+    } = self.statistics;
+    write!(
+      f,
+      "{notes} notes, {attachments} attachments, {links} links, {tags} tags, {words} words, \
+       {} errors, {} orphans",
+      self.error_count(),
+      self.orphans.len()
+    )
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::notes::Note;
+
+  #[test]
+  fn problems_of_the_notes() {
+    let notes = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "[[b]] [[missing]] [[c]] #tag"),
+        Note::new("b.md", "---\ntitle: [oops\n---\n[[a]]"),
+        Note::new("c.md", "Alone"),
+        Note::new("dir/c.md", "Also alone"),
+        Note::new("image.png", ""),
+      ],
+    );
+    let report = check_notes(&notes);
+    assert_eq!(
+      report.statistics,
+      Statistics {
+        notes: 4,
+        attachments: 1,
+        links: 4,
+        tags: 1,
+        words: 8,
+      }
+    );
+    assert_eq!(
+      report.broken_links,
+      vec![LinkProblem {
+        path: "a.md".into(),
+        line: 1,
+        target: "missing".to_owned(),
+        candidates: vec![],
+      }]
+    );
+    assert_eq!(
+      report.ambiguous_links[0].candidates,
+      vec![PathBuf::from("c.md"), PathBuf::from("dir/c.md")]
+    );
+    assert_eq!(
+      report.orphans,
+      vec![PathBuf::from("dir/c.md"), PathBuf::from("image.png")]
+    );
+    assert_eq!(report.duplicate_titles[0].title, "c");
+    assert_eq!(report.front_matter_errors[0].path, PathBuf::from("b.md"));
+    assert_eq!(report.error_count(), 4);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["broken_links"][0]["target"], "missing");
+    assert!(report
+      .to_string()
+      .starts_with("b.md:2: malformed front matter"));
+  }
+}
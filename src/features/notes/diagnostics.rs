@@ -0,0 +1,45 @@
+use crate::features::note_graph::NodeKind;
+use crate::features::notes::{parse_links, Link, NotesIndex};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+
+/// Link that doesn't point to exactly one note
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkDiagnostic {
+  /// Note the link is in
+  pub source: NodeId,
+  pub link: Link,
+  /// Notes the link could point to, best first (the first one is used).
+  /// Empty if the link is unresolved.
+  pub candidates: Vec<NodeId>,
+}
+
+impl LinkDiagnostic {
+  pub fn is_ambiguous(&self) -> bool {
+    self.candidates.len() > 1
+  }
+}
+
+/// Unresolved and ambiguous links of all notes, by source path and line
+pub fn link_diagnostics(notes: &NotesIndex) -> Vec<LinkDiagnostic> {
+  let mut sources = notes
+    .graph
+    .iter_nodes()
+    .filter(|(_, node)| node.kind == NodeKind::Note && node.note.is_markdown())
+    .collect::<Vec<_>>();
+  sources.sort_by(|(_, a), (_, b)| a.note.path.cmp(&b.note.path));
+  let mut diagnostics = vec![];
+  for (source, node) in sources {
+    for link in parse_links(&node.note.content) {
+      let candidates = notes.resolve_candidates(source, &link);
+      if candidates.len() != 1 {
+        diagnostics.push(LinkDiagnostic {
+          source,
+          link,
+          candidates,
+        });
+      }
+    }
+  }
+  diagnostics
+}
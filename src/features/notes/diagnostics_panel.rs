@@ -0,0 +1,73 @@
+use crate::features::notes::{link_diagnostics, LinkDiagnostic, NotesIndex};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use egui::*;
+
+const DIAGNOSTICS_PANEL_HEIGHT: f32 = 240.0;
+
+/// Diagnostics panel state
+#[derive(Default)]
+pub struct DiagnosticsPanel {
+  /// Notes revision the `diagnostics` were collected for
+  collected_for: Option<u64>,
+  diagnostics: Vec<LinkDiagnostic>,
+}
+
+impl DiagnosticsPanel {
+  /// Show unresolved and ambiguous links. Returns the note that was clicked, if any.
+  pub fn ui(&mut self, ui: &mut Ui, notes: &NotesIndex) -> Option<NodeId> {
+    if self.collected_for != Some(notes.revision()) {
+      self.diagnostics = link_diagnostics(notes);
+      self.collected_for = Some(notes.revision());
+    }
+    let mut clicked = None;
+    CollapsingHeader::new(format!("Diagnostics ({})", self.diagnostics.len()))
+      .id_source("diagnostics_panel")
+      .show(ui, |ui| {
+        ScrollArea::vertical()
+          .id_source("diagnostics_panel")
+          .max_height(DIAGNOSTICS_PANEL_HEIGHT)
+          .show(ui, |ui| {
+            for diagnostic in &self.diagnostics {
+              let source = &notes.graph.get_node(diagnostic.source).note;
+              if ui
+                .link(format!(
+                  "{}:{}",
+                  source.path.display(),
+                  diagnostic.link.line + 1
+                ))
+                .clicked()
+              {
+                clicked = Some(diagnostic.source);
+              }
+              ui.weak(describe(notes, diagnostic));
+            }
+          });
+      });
+    clicked
+  }
+}
+
+fn describe(notes: &NotesIndex, diagnostic: &LinkDiagnostic) -> String {
+  let target = &diagnostic.link.target;
+  if !diagnostic.is_ambiguous() {
+    return format!("`{target}` is unresolved");
+  }
+  let paths = diagnostic
+    .candidates
+    .iter()
+    .map(|&node_id| {
+      notes
+        .graph
+        .get_node(node_id)
+        .note
+        .path
+        .display()
+        .to_string()
+    })
+    .collect::<Vec<_>>();
+  format!(
+    "`{target}` is ambiguous: {}, using the first",
+    paths.join(", ")
+  )
+}
@@ -0,0 +1,382 @@
+use serde_json::{Map, Number, Value};
+use std::fmt;
+
+/// Metadata from the YAML front matter of a note
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct FrontMatter {
+  pub title: Option<String>,
+  pub aliases: Vec<String>,
+  /// Tags without the leading `#`
+  pub tags: Vec<String>,
+  /// All properties, including the ones above
+  pub properties: Map<String, Value>,
+}
+
+/// Front matter that can't be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrontMatterError {
+  /// Index of the line in the note
+  pub line: usize,
+  pub message: String,
+}
+
+impl fmt::Display for FrontMatterError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "front matter, line {}: {}", self.line + 1, self.message)
+  }
+}
+
+fn error(line: usize, message: impl Into<String>) -> FrontMatterError {
+  FrontMatterError {
+    line,
+    message: message.into(),
+  }
+}
+
+/// Parse the front matter between `---` lines at the very start of the content.
+/// Returns `None` if there is no front matter, otherwise the parsed front matter
+/// and the index of the first line after it.
+pub fn parse_front_matter(content: &str) -> Option<(Result<FrontMatter, FrontMatterError>, usize)> {
+  let mut lines = content.lines();
+  if lines.next()?.trim_end() != "---" {
+    return None;
+  }
+  let Some(end) = lines.position(|line| matches!(line.trim_end(), "---" | "...")) else {
+    return Some((Err(error(0, "front matter is not closed with `---`")), 0));
+  };
+  let yaml = content.lines().skip(1).take(end).collect::<Vec<_>>();
+  let front_matter = parse_yaml(&yaml).map(|properties| FrontMatter {
+    title: properties
+      .get("title")
+      .and_then(Value::as_str)
+      .map(str::trim)
+      .filter(|title| !title.is_empty())
+      .map(str::to_owned),
+    aliases: strings(
+      properties
+        .get("aliases")
+        .or_else(|| properties.get("alias")),
+      false,
+    ),
+    tags: strings(
+      properties.get("tags").or_else(|| properties.get("tag")),
+      true,
+    )
+    .into_iter()
+    .map(|tag| tag.trim_start_matches('#').to_owned())
+    .filter(|tag| !tag.is_empty())
+    .collect(),
+    properties,
+  });
+  Some((front_matter, end + 2))
+}
+
+/// Strings of a list property. A single string is split on commas, and on whitespace if `words`.
+fn strings(value: Option<&Value>, words: bool) -> Vec<String> {
+  let split = |text: &str| {
+    text
+      .split(|c: char| c == ',' || (words && c.is_whitespace()))
+      .map(str::trim)
+      .filter(|part| !part.is_empty())
+      .map(str::to_owned)
+      .collect::<Vec<_>>()
+  };
+  match value {
+    Some(Value::String(text)) if words => split(text),
+    Some(Value::String(text)) => vec![text.trim().to_owned()],
+    Some(Value::Array(items)) => items
+      .iter()
+      .filter_map(|item| match item {
+        Value::String(text) => Some(text.trim().to_owned()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
+/// Non-empty line of YAML without comments
+struct Line<'a> {
+  /// Index of the line in the note
+  number: usize,
+  indent: usize,
+  text: &'a str,
+}
+
+/// Parser for the YAML subset used in front matter: nested block mappings and sequences,
+/// flow sequences, quoted and plain scalars, and `|`/`>` block scalars
+struct Parser<'a> {
+  lines: Vec<Line<'a>>,
+  next: usize,
+}
+
+fn parse_yaml(yaml: &[&str]) -> Result<Map<String, Value>, FrontMatterError> {
+  let lines = yaml
+    .iter()
+    .enumerate()
+    .filter_map(|(index, line)| {
+      let line = strip_comment(line).trim_end();
+      let text = line.trim_start();
+      (!text.is_empty()).then_some(Line {
+        // Line 0 is the opening `---`
+        number: index + 1,
+        indent: line.len() - text.len(),
+        text,
+      })
+    })
+    .collect();
+  let mut parser = Parser { lines, next: 0 };
+  let Some(first) = parser.peek() else {
+    return Ok(Map::new());
+  };
+  let (number, indent) = (first.number, first.indent);
+  if is_sequence_item(first.text) {
+    return Err(error(number, "expected properties, found a list"));
+  }
+  let properties = parser.mapping(indent)?;
+  parser.peek().map_or(Ok(properties), |line| {
+    Err(error(line.number, "unexpected indentation"))
+  })
+}
+
+impl<'a> Parser<'a> {
+  fn peek(&self) -> Option<&Line<'a>> {
+    self.lines.get(self.next)
+  }
+
+  /// Mapping or sequence that starts at the next line, which has the `indent`
+  fn block(&mut self, indent: usize) -> Result<Value, FrontMatterError> {
+    match self.peek() {
+      Some(line) if is_sequence_item(line.text) => self.sequence(indent),
+      Some(_) => self.mapping(indent).map(Value::Object),
+      None => Ok(Value::Null),
+    }
+  }
+
+  fn mapping(&mut self, indent: usize) -> Result<Map<String, Value>, FrontMatterError> {
+    let mut map = Map::new();
+    while let Some(line) = self.peek().filter(|line| line.indent == indent) {
+      let number = line.number;
+      if is_sequence_item(line.text) {
+        return Err(error(number, "unexpected list item"));
+      }
+      let Some((key, rest)) = split_key(line.text) else {
+        return Err(error(number, "expected `key: value`"));
+      };
+      self.next += 1;
+      let value = match rest {
+        "" => match self.peek() {
+          // Nested block, sequences may be at the same indentation as the key
+          Some(next)
+            if next.indent > indent || (next.indent == indent && is_sequence_item(next.text)) =>
+          {
+            let next_indent = next.indent;
+            self.block(next_indent)?
+          }
+          _ => Value::Null,
+        },
+        "|" | "|-" | "|+" => Value::String(self.block_scalar(indent, "\n")),
+        ">" | ">-" | ">+" => Value::String(self.block_scalar(indent, " ")),
+        rest => scalar(rest, number)?,
+      };
+      map.insert(key, value);
+    }
+    if let Some(line) = self.peek().filter(|line| line.indent > indent) {
+      return Err(error(line.number, "unexpected indentation"));
+    }
+    Ok(map)
+  }
+
+  fn sequence(&mut self, indent: usize) -> Result<Value, FrontMatterError> {
+    let mut items = vec![];
+    while let Some(line) = self
+      .peek()
+      .filter(|line| line.indent == indent && is_sequence_item(line.text))
+    {
+      let (number, rest) = (line.number, line.text[1..].trim_start());
+      self.next += 1;
+      let item = match self.peek() {
+        Some(next) if rest.is_empty() && next.indent > indent => {
+          let next_indent = next.indent;
+          self.block(next_indent)?
+        }
+        _ if rest.is_empty() => Value::Null,
+        _ => scalar(rest, number)?,
+      };
+      items.push(item);
+    }
+    Ok(Value::Array(items))
+  }
+
+  /// Lines indented deeper than the key, joined with the `separator`
+  fn block_scalar(&mut self, indent: usize, separator: &str) -> String {
+    let mut text = vec![];
+    while let Some(line) = self.peek().filter(|line| line.indent > indent) {
+      text.push(line.text);
+      self.next += 1;
+    }
+    text.join(separator)
+  }
+}
+
+fn is_sequence_item(text: &str) -> bool {
+  text == "-" || text.starts_with("- ")
+}
+
+/// Split `key: value` into the (unquoted) key and the rest
+fn split_key(text: &str) -> Option<(String, &str)> {
+  if let Some(quote @ ('"' | '\'')) = text.chars().next() {
+    let end = text[1..].find(quote)? + 1;
+    let rest = text[end + 1..].strip_prefix(':')?;
+    return Some((text[1..end].to_owned(), rest.trim()));
+  }
+  let colon = text
+    .match_indices(':')
+    .map(|(index, _)| index)
+    .find(|&index| text[index + 1..].is_empty() || text[index + 1..].starts_with(' '))?;
+  Some((text[..colon].trim().to_owned(), text[colon + 1..].trim()))
+}
+
+/// Line without a ` #` comment outside of quotes
+fn strip_comment(line: &str) -> &str {
+  let mut quote = None;
+  let mut previous = ' ';
+  for (index, c) in line.char_indices() {
+    match (quote, c) {
+      (None, '"' | '\'') => quote = Some(c),
+      (Some(open), c) if c == open => quote = None,
+      (None, '#') if previous.is_whitespace() => return &line[..index],
+      _ => (),
+    }
+    previous = c;
+  }
+  line
+}
+
+fn scalar(text: &str, line: usize) -> Result<Value, FrontMatterError> {
+  let text = text.trim();
+  if let Some(inner) = text.strip_prefix('[') {
+    let inner = inner
+      .strip_suffix(']')
+      .ok_or_else(|| error(line, "list is not closed with `]`"))?;
+    return split_flow(inner)
+      .into_iter()
+      .map(|item| scalar(item, line))
+      .collect::<Result<_, _>>()
+      .map(Value::Array);
+  }
+  if text.starts_with('{') {
+    return match text {
+      "{}" => Ok(Value::Object(Map::new())),
+      _ => Err(error(line, "inline mappings are not supported")),
+    };
+  }
+  if let Some(quote @ ('"' | '\'')) = text.chars().next() {
+    let inner = text[1..]
+      .strip_suffix(quote)
+      .ok_or_else(|| error(line, "string is not closed"))?;
+    return Ok(Value::String(if quote == '"' {
+      inner
+        .replace("\\\"", "\"")
+        .replace("\\n", "\n")
+        .replace("\\\\", "\\")
+    } else {
+      inner.replace("''", "'")
+    }));
+  }
+  Ok(match text {
+    "true" | "True" | "TRUE" => Value::Bool(true),
+    "false" | "False" | "FALSE" => Value::Bool(false),
+    "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+    _ => number(text).map_or_else(|| Value::String(text.to_owned()), Value::Number),
+  })
+}
+
+fn number(text: &str) -> Option<Number> {
+  if !text
+    .chars()
+    .all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+  {
+    return None;
+  }
+  text
+    .parse::<i64>()
+    .ok()
+    .map(Number::from)
+    .or_else(|| Number::from_f64(text.parse().ok()?))
+}
+
+/// Items of a flow sequence, commas in quotes don't separate items
+fn split_flow(inner: &str) -> Vec<&str> {
+  let mut items = vec![];
+  let mut quote = None;
+  let mut start = 0;
+  for (index, c) in inner.char_indices() {
+    match (quote, c) {
+      (None, '"' | '\'') => quote = Some(c),
+      (Some(open), c) if c == open => quote = None,
+      (None, ',') => {
+        items.push(&inner[start..index]);
+        start = index + 1;
+      }
+      _ => (),
+    }
+  }
+  items.push(&inner[start..]);
+  items
+    .into_iter()
+    .map(str::trim)
+    .filter(|item| !item.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn front_matter() {
+    let content = "---
+title: Graph theory # comment
+aliases:
+  - Graphs
+  - \"Networks, too\"
+tags: [math, '#cs']
+rating: 4.5
+draft: false
+source:
+  author: Euler
+  year: 1736
+description: >
+  Study of
+  graphs
+---
+# Graph theory";
+    let (front_matter, body_line) = parse_front_matter(content).unwrap();
+    let front_matter = front_matter.unwrap();
+    assert_eq!(body_line, 15);
+    assert_eq!(front_matter.title.as_deref(), Some("Graph theory"));
+    assert_eq!(front_matter.aliases, vec!["Graphs", "Networks, too"]);
+    assert_eq!(front_matter.tags, vec!["math", "cs"]);
+    assert_eq!(
+      Value::Object(front_matter.properties),
+      json!({
+        "title": "Graph theory",
+        "aliases": ["Graphs", "Networks, too"],
+        "tags": ["math", "#cs"],
+        "rating": 4.5,
+        "draft": false,
+        "source": { "author": "Euler", "year": 1736 },
+        "description": "Study of graphs",
+      })
+    );
+
+    assert_eq!(parse_front_matter("# No front matter"), None);
+    let (malformed, _) = parse_front_matter("---\ntags: [a, b\n---\n").unwrap();
+    assert_eq!(malformed.unwrap_err().line, 1);
+    let (unclosed, _) = parse_front_matter("---\ntitle: a\n").unwrap();
+    assert!(unclosed.is_err());
+  }
+}
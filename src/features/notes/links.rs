@@ -0,0 +1,243 @@
+use std::fmt;
+use std::ops::Range;
+
+/// Syntax the link was written with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkKind {
+  /// `[[Note]]`
+  Wiki,
+  /// `[text](note.md)`
+  Markdown,
+}
+
+/// Part of the linked note a link points to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+  /// `[[Note#Heading]]`
+  Heading(String),
+  /// `[[Note#^block-id]]`
+  Block(String),
+}
+
+impl Anchor {
+  /// Anchor written after the `#` of a link
+  pub fn parse(anchor: &str) -> Option<Self> {
+    let anchor = anchor.trim();
+    match anchor.strip_prefix('^') {
+      Some(block) if !block.is_empty() => Some(Self::Block(block.to_owned())),
+      Some(_) => None,
+      None if anchor.is_empty() => None,
+      None => Some(Self::Heading(anchor.to_owned())),
+    }
+  }
+}
+
+impl fmt::Display for Anchor {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Heading(heading) => write!(f, "#{heading}"),
+      Self::Block(block) => write!(f, "#^{block}"),
+    }
+  }
+}
+
+/// A link from one note to another, as written in the source note
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Link {
+  pub kind: LinkKind,
+  /// Linked note without the `#heading` or `|alias` parts
+  pub target: String,
+  pub anchor: Option<Anchor>,
+  /// Text shown instead of the target, `[[Note|text]]` or `[text](note.md)`
+  pub text: Option<String>,
+  /// `![[embed]]` or `![image](image.png)`
+  pub embed: bool,
+  /// Field of a typed link: `parent:: [[Note]]`, or a front matter property `parent: "[[Note]]"`
+  pub field: Option<String>,
+  /// Index of the line the link is on
+  pub line: usize,
+  /// Byte range of the target in the line, as written: `Note%20one.md` of `[a](Note%20one.md#top)`
+  pub target_range: Range<usize>,
+}
+
+/// Collect wikilinks and links to local files from Markdown text.
+/// Links inside fenced code blocks are ignored.
+pub fn parse_links(content: &str) -> Vec<Link> {
+  let mut links = vec![];
+  let mut in_code_block = false;
+  let mut in_front_matter = false;
+  // Front matter property the current line belongs to
+  let mut property = None;
+  for (line_index, line) in content.lines().enumerate() {
+    if line.trim_end() == "---" && (line_index == 0 || in_front_matter) {
+      in_front_matter = line_index == 0;
+      continue;
+    }
+    if line.trim_start().starts_with("```") && !in_front_matter {
+      in_code_block = !in_code_block;
+      continue;
+    }
+    if in_code_block {
+      continue;
+    }
+    let field = if in_front_matter {
+      if !line.starts_with([' ', '-']) {
+        property = line.split_once(':').map(|(key, _)| key.trim().to_owned());
+      }
+      property.clone()
+    } else {
+      inline_field(line)
+    };
+    let first_link = links.len();
+    parse_wikilinks(line, line_index, &mut links);
+    parse_markdown_links(line, line_index, &mut links);
+    for link in &mut links[first_link..] {
+      link.field = field.clone();
+    }
+  }
+  links
+}
+
+/// Name of the Dataview-style inline field the line starts with: `parent:: [[Note]]`
+fn inline_field(line: &str) -> Option<String> {
+  let line = line.trim_start();
+  let line = line.strip_prefix("- ").unwrap_or(line);
+  let (field, _) = line.split_once("::")?;
+  let field = field.trim();
+  let is_name = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | ' ');
+  (!field.is_empty() && field.chars().all(is_name)).then(|| field.to_owned())
+}
+
+fn parse_wikilinks(line: &str, line_index: usize, links: &mut Vec<Link>) {
+  let mut rest = line;
+  while let Some(start) = rest.find("[[") {
+    let after_start = &rest[start + 2..];
+    let Some(end) = after_start.find("]]") else {
+      break;
+    };
+    let inner = &after_start[..end];
+    let (target, text) = inner.split_once('|').unwrap_or((inner, ""));
+    let (target, anchor) = target.split_once('#').unwrap_or((target, ""));
+    let target = target.trim();
+    if !target.is_empty() {
+      let target_start = offset_in(line, target);
+      links.push(Link {
+        kind: LinkKind::Wiki,
+        target: target.to_owned(),
+        anchor: Anchor::parse(anchor),
+        text: Some(text.trim())
+          .filter(|text| !text.is_empty())
+          .map(str::to_owned),
+        embed: rest[..start].ends_with('!'),
+        field: None,
+        line: line_index,
+        target_range: target_start..target_start + target.len(),
+      });
+    }
+    rest = &after_start[end + 2..];
+  }
+}
+
+fn parse_markdown_links(line: &str, line_index: usize, links: &mut Vec<Link>) {
+  let mut rest = line;
+  while let Some(start) = rest.find("](") {
+    let after_start = &rest[start + 2..];
+    let Some(end) = after_start.find(')') else {
+      break;
+    };
+    let target = after_start[..end].trim();
+    let target = target
+      .strip_prefix('<')
+      .and_then(|target| target.strip_suffix('>'))
+      .unwrap_or(target);
+    let (target, anchor) = target.split_once('#').unwrap_or((target, ""));
+    let target_range = offset_in(line, target)..offset_in(line, target) + target.len();
+    let target = target.replace("%20", " ");
+    let is_external = target.contains("://") || target.starts_with("mailto:");
+    if !target.is_empty() && !is_external {
+      let text_start = rest[..start].rfind('[');
+      let text = text_start.map(|text_start| &rest[text_start + 1..start]);
+      links.push(Link {
+        kind: LinkKind::Markdown,
+        target,
+        anchor: Anchor::parse(&anchor.replace("%20", " ")),
+        text: text.filter(|text| !text.is_empty()).map(str::to_owned),
+        embed: text_start.is_some_and(|text_start| rest[..text_start].ends_with('!')),
+        field: None,
+        line: line_index,
+        target_range,
+      });
+    }
+    rest = &after_start[end + 1..];
+  }
+}
+
+/// Byte offset of the `part` slice in the `line` it's taken from
+fn offset_in(line: &str, part: &str) -> usize {
+  part.as_ptr() as usize - line.as_ptr() as usize
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn wikilinks() {
+    let links = parse_links(
+      "See [[Note|the note]] and [[folder/Other#Heading]].\n![[image.png]] [[Note#^block-1]]",
+    );
+    let targets = links
+      .iter()
+      .map(|link| link.target.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(targets, vec!["Note", "folder/Other", "image.png", "Note"]);
+    assert_eq!(links[0].text.as_deref(), Some("the note"));
+    assert_eq!(links[1].anchor, Some(Anchor::Heading("Heading".to_owned())));
+    assert!(links[2].embed && !links[3].embed);
+    assert_eq!(links[2].line, 1);
+    assert_eq!(links[3].anchor, Some(Anchor::Block("block-1".to_owned())));
+    assert_eq!(links[1].target_range, 28..40);
+  }
+
+  #[test]
+  fn markdown_links() {
+    let links = parse_links("[a](Note%20one.md) [b](https://example.com) ![c](<../x.png#top>)");
+    let targets = links
+      .iter()
+      .map(|link| link.target.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(targets, vec!["Note one.md", "../x.png"]);
+    assert_eq!(links[0].text.as_deref(), Some("a"));
+    assert!(links[1].embed);
+    assert_eq!(links[1].anchor, Some(Anchor::Heading("top".to_owned())));
+    assert_eq!(links[0].target_range, 4..17);
+  }
+
+  #[test]
+  fn typed_links() {
+    let links = parse_links(
+      "---\nup: \"[[Index]]\"\nrelated:\n  - \"[[A]]\"\n---\n- parent:: [[Home]]\nSee [[B]] :: [[C]]",
+    );
+    let fields = links
+      .iter()
+      .map(|link| (link.target.as_str(), link.field.as_deref()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      fields,
+      vec![
+        ("Index", Some("up")),
+        ("A", Some("related")),
+        ("Home", Some("parent")),
+        ("B", None),
+        ("C", None),
+      ]
+    );
+  }
+
+  #[test]
+  fn code_blocks_are_skipped() {
+    let links = parse_links("```\n[[Not a link]]\n```\n[[Link]]");
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].target, "Link");
+  }
+}
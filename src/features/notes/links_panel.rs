@@ -0,0 +1,175 @@
+use crate::features::note_graph::{EdgeKind, NodeKind};
+use crate::features::notes::{is_markdown, unlinked_mentions, Mention, NotesIndex};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use egui::*;
+use serde_json::Value;
+use std::path::Path;
+
+/// Maximum number of characters of the linking line shown in a snippet
+const SNIPPET_LENGTH: usize = 80;
+
+/// Linking line without surrounding whitespace, shortened to `SNIPPET_LENGTH`
+fn snippet(line: &str) -> String {
+  let line = line.trim();
+  if line.chars().count() <= SNIPPET_LENGTH {
+    return line.to_owned();
+  }
+  line.chars().take(SNIPPET_LENGTH).collect::<String>() + "…"
+}
+
+/// What the user asked for in the links panel
+pub enum LinksPanelAction {
+  /// Navigate to the note
+  Open(NodeId),
+  /// Turn the unlinked mention into a wikilink
+  LinkMention(Mention),
+}
+
+/// Links panel state
+#[derive(Default)]
+pub struct LinksPanel {
+  /// Note and notes revision the current `mentions` were collected for
+  mentions_for: Option<(NodeId, u64)>,
+  mentions: Vec<Mention>,
+}
+
+impl LinksPanel {
+  /// Show backlinks, outgoing links and unlinked mentions of the note.
+  /// Returns what was clicked, if anything.
+  pub fn ui(
+    &mut self,
+    ui: &mut Ui,
+    notes: &NotesIndex,
+    node_id: NodeId,
+  ) -> Option<LinksPanelAction> {
+    let key = Some((node_id, notes.revision()));
+    if self.mentions_for != key {
+      // Tags and headings are not mentioned by name
+      self.mentions = match notes.graph.get_node(node_id).kind {
+        NodeKind::Tag | NodeKind::Heading => vec![],
+        _ => unlinked_mentions(notes, node_id),
+      };
+      self.mentions_for = key;
+    }
+    links_panel_ui(ui, notes, node_id, &self.mentions)
+  }
+}
+
+fn links_panel_ui(
+  ui: &mut Ui,
+  notes: &NotesIndex,
+  node_id: NodeId,
+  mentions: &[Mention],
+) -> Option<LinksPanelAction> {
+  let graph = &notes.graph;
+  let note = &graph.get_node(node_id).note;
+  let mut clicked = None;
+
+  ui.heading(&note.title);
+  if !note.aliases.is_empty() {
+    ui.weak(note.aliases.join(", "));
+  }
+  if let Some(error) = &note.front_matter_error {
+    ui.colored_label(ui.visuals().warn_fg_color, error.to_string());
+  }
+  ScrollArea::vertical().show(ui, |ui| {
+    if !note.properties.is_empty() {
+      CollapsingHeader::new("Properties").show(ui, |ui| {
+        Grid::new("properties").striped(true).show(ui, |ui| {
+          for (key, value) in &note.properties {
+            ui.label(key);
+            match value {
+              Value::String(text) => ui.label(text),
+              value => ui.label(value.to_string()),
+            };
+            ui.end_row();
+          }
+        });
+      });
+    }
+
+    // Tag nodes list the notes that have the tag
+    let backlinks = graph
+      .iter_in_edges(node_id)
+      .filter(|(_, edge)| edge.kind.is_link() || edge.kind == EdgeKind::Tag)
+      .collect::<Vec<_>>();
+    CollapsingHeader::new(format!("Backlinks ({})", backlinks.len()))
+      .default_open(true)
+      .show(ui, |ui| {
+        for (edge_id, edge) in backlinks {
+          let source_id = graph.get_edge_incidents(edge_id).node_from;
+          let source = &graph.get_node(source_id).note;
+          let title = edge.anchor.as_ref().map_or_else(
+            || source.title.clone(),
+            |anchor| format!("{} → {anchor}", source.title),
+          );
+          if ui.link(title).clicked() {
+            clicked = Some(LinksPanelAction::Open(source_id));
+          }
+          let line = source.line(edge.line).unwrap_or_default();
+          ui.weak(snippet(line));
+        }
+      });
+
+    let outgoing = graph
+      .iter_out_edges(node_id)
+      .filter(|(_, edge)| edge.kind.is_link())
+      .collect::<Vec<_>>();
+    // Unresolved links to notes are ghost nodes, only missing attachments are left
+    let missing_attachments = graph
+      .get_node(node_id)
+      .unresolved_links
+      .iter()
+      .filter(|link| !is_markdown(Path::new(&link.target)))
+      .filter(|link| Path::new(&link.target).extension().is_some())
+      .collect::<Vec<_>>();
+    CollapsingHeader::new(format!(
+      "Outgoing links ({})",
+      outgoing.len() + missing_attachments.len()
+    ))
+    .default_open(true)
+    .show(ui, |ui| {
+      for (edge_id, edge) in outgoing {
+        let target_id = graph.get_edge_incidents(edge_id).node_to;
+        let target = graph.get_node(target_id);
+        let response = if target.is_ghost() {
+          ui.link(RichText::new(&target.note.title).italics())
+            .on_hover_text("Not created yet, click to create")
+        } else {
+          ui.link(&target.note.title)
+        };
+        if response.clicked() {
+          clicked = Some(LinksPanelAction::Open(target_id));
+        }
+        ui.weak(snippet(note.line(edge.line).unwrap_or_default()));
+      }
+      for link in missing_attachments {
+        ui.label(RichText::new(&link.target).italics())
+          .on_hover_text("Missing attachment");
+        ui.weak(snippet(note.line(link.line).unwrap_or_default()));
+      }
+    });
+
+    CollapsingHeader::new(format!("Unlinked mentions ({})", mentions.len())).show(ui, |ui| {
+      for mention in mentions {
+        let source = &graph.get_node(mention.source).note;
+        ui.horizontal(|ui| {
+          if ui.link(&source.title).clicked() {
+            clicked = Some(LinksPanelAction::Open(mention.source));
+          }
+          if ui
+            .small_button("Link")
+            .on_hover_text("Turn the mention into a link")
+            .clicked()
+          {
+            clicked = Some(LinksPanelAction::LinkMention(mention.clone()));
+          }
+        });
+        ui.weak(snippet(source.line(mention.line).unwrap_or_default()));
+      }
+    });
+  });
+
+  clicked
+}
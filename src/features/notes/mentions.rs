@@ -0,0 +1,171 @@
+use crate::features::notes::NotesIndex;
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use std::io;
+use std::ops::Range;
+
+/// Title of a note written as plain text in another note
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mention {
+  /// Note the mention is in
+  pub source: NodeId,
+  /// Note that is mentioned
+  pub target: NodeId,
+  /// Index of the line the mention is on
+  pub line: usize,
+  /// Byte range of the mention in the line
+  pub range: Range<usize>,
+}
+
+/// Find mentions of the note's names (title, file name and aliases) in other Markdown notes
+/// that are not links. Matching is ASCII case-insensitive and only whole words are matched.
+pub fn unlinked_mentions(notes: &NotesIndex, target: NodeId) -> Vec<Mention> {
+  let mut names = notes.graph.get_node(target).note.names();
+  names.retain(|name| !name.is_empty());
+  // Longer names win when names overlap, e.g. an alias that contains the title
+  names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+  if names.is_empty() {
+    return vec![];
+  }
+  let mut mentions = vec![];
+  for (source, node) in notes.graph.iter_nodes() {
+    if source == target || !node.note.is_markdown() {
+      continue;
+    }
+    let mut in_code_block = false;
+    let lines = node.note.content.lines().enumerate();
+    for (line_index, line) in lines.skip(node.note.body_line) {
+      if line.trim_start().starts_with("```") {
+        in_code_block = !in_code_block;
+        continue;
+      }
+      if in_code_block {
+        continue;
+      }
+      // Links and mentions found so far
+      let mut taken = link_ranges(line);
+      for name in &names {
+        for range in find_words(line, name) {
+          if taken
+            .iter()
+            .any(|taken| taken.start < range.end && range.start < taken.end)
+          {
+            continue;
+          }
+          taken.push(range.clone());
+          mentions.push(Mention {
+            source,
+            target,
+            line: line_index,
+            range,
+          });
+        }
+      }
+    }
+  }
+  mentions
+}
+
+/// Replace the mention with a `[[wikilink]]` to the mentioned note, keeping the text as written
+pub fn link_mention(notes: &mut NotesIndex, mention: &Mention) -> io::Result<()> {
+  let target = &notes.graph.get_node(mention.target).note;
+  let (title, names) = (target.title.clone(), target.names());
+  let content = &notes.graph.get_node(mention.source).note.content;
+  let mut lines = content.split('\n').collect::<Vec<_>>();
+  let line = lines
+    .get(mention.line)
+    .and_then(|line| Some((line, line.get(mention.range.clone())?)))
+    .filter(|(_, text)| names.iter().any(|name| text.eq_ignore_ascii_case(name)));
+  let Some((line, text)) = line else {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      "mention is no longer in the note",
+    ));
+  };
+  let link = if text == title {
+    format!("[[{title}]]")
+  } else {
+    format!("[[{title}|{text}]]")
+  };
+  let new_line = format!(
+    "{}{link}{}",
+    &line[..mention.range.start],
+    &line[mention.range.end..]
+  );
+  lines[mention.line] = &new_line;
+  let new_content = lines.join("\n");
+  notes.update_note(mention.source, new_content)
+}
+
+/// Byte ranges of wikilinks and Markdown links in the line
+fn link_ranges(line: &str) -> Vec<Range<usize>> {
+  let mut ranges = vec![];
+  let mut offset = 0;
+  while let Some(start) = line[offset..].find("[[") {
+    let start = offset + start;
+    let Some(end) = line[start..].find("]]") else {
+      break;
+    };
+    offset = start + end + 2;
+    ranges.push(start..offset);
+  }
+  let mut offset = 0;
+  while let Some(middle) = line[offset..].find("](") {
+    let middle = offset + middle;
+    let Some(end) = line[middle..].find(')') else {
+      break;
+    };
+    let start = line[..middle].rfind('[').unwrap_or(middle);
+    offset = middle + end + 1;
+    ranges.push(start..offset);
+  }
+  ranges
+}
+
+/// Byte ranges of whole-word occurrences of `word` in the `line`
+fn find_words<'a>(line: &'a str, word: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+  let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+  line.char_indices().filter_map(move |(start, _)| {
+    let end = start + word.len();
+    let candidate = line.get(start..end)?;
+    let starts_word = !line[..start].chars().next_back().is_some_and(is_word_char);
+    let ends_word = !line[end..].chars().next().is_some_and(is_word_char);
+    (candidate.eq_ignore_ascii_case(word) && starts_word && ends_word).then_some(start..end)
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::notes::Note;
+  use std::path::Path;
+
+  #[test]
+  fn mentions_are_found_and_linked() {
+    let mut notes = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("Rust.md", "# Rust"),
+        Note::new(
+          "a.md",
+          "I like rust and [[Rust]].\nRusty is not a mention, [Rust](Rust.md) neither.",
+        ),
+      ],
+    );
+    let rust = notes.node_by_path(Path::new("Rust.md")).unwrap();
+    let a = notes.node_by_path(Path::new("a.md")).unwrap();
+    let mentions = unlinked_mentions(&notes, rust);
+    assert_eq!(mentions.len(), 1);
+    assert_eq!(mentions[0].range, 7..11);
+
+    link_mention(&mut notes, &mentions[0]).unwrap();
+    assert!(notes
+      .graph
+      .get_node(a)
+      .note
+      .content
+      .starts_with("I like [[Rust|rust]] and [[Rust]]."));
+    assert_eq!(notes.graph.iter_in_edges(rust).count(), 3);
+    assert!(unlinked_mentions(&notes, rust).is_empty());
+  }
+}
@@ -0,0 +1,29 @@
+mod check;
+mod diagnostics;
+mod diagnostics_panel;
+mod front_matter;
+mod links;
+mod links_panel;
+mod mentions;
+mod note;
+mod notes_index;
+mod rename;
+mod rename_dialog;
+mod search;
+mod search_panel;
+mod tags_panel;
+
+pub use check::*;
+pub use diagnostics::*;
+pub use diagnostics_panel::*;
+pub use front_matter::*;
+pub use links::*;
+pub use links_panel::*;
+pub use mentions::*;
+pub use note::*;
+pub use notes_index::*;
+pub use rename::*;
+pub use rename_dialog::*;
+pub use search::*;
+pub use search_panel::*;
+pub use tags_panel::*;
@@ -0,0 +1,166 @@
+use crate::features::notes::{parse_front_matter, FrontMatterError};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// A file in the notes directory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+  /// Path relative to the notes directory
+  pub path: PathBuf,
+  /// Title from the front matter, or file name without the `.md` extension for Markdown notes,
+  /// file name otherwise
+  pub title: String,
+  /// Other names of the note from the front matter, links can use them
+  pub aliases: Vec<String>,
+  /// Contents of a Markdown note, empty for attachments
+  pub content: String,
+  /// Tags from the front matter and the text, without the leading `#`
+  pub tags: Vec<String>,
+  /// Front matter properties
+  pub properties: Map<String, Value>,
+  /// Why the front matter couldn't be parsed
+  pub front_matter_error: Option<FrontMatterError>,
+  /// Index of the first line after the front matter
+  pub body_line: usize,
+  pub word_count: usize,
+}
+
+impl Note {
+  pub fn new(path: impl Into<PathBuf>, content: impl Into<String>) -> Self {
+    let path = path.into();
+    let content = content.into();
+    let (front_matter, body_line) =
+      parse_front_matter(&content).unwrap_or((Ok(Default::default()), 0));
+    let (front_matter, front_matter_error) = match front_matter {
+      Ok(front_matter) => (front_matter, None),
+      Err(error) => (Default::default(), Some(error)),
+    };
+    let body = content
+      .lines()
+      .skip(body_line)
+      .collect::<Vec<_>>()
+      .join("\n");
+    let mut tags = front_matter.tags;
+    for tag in inline_tags(&body) {
+      if !tags.contains(&tag) {
+        tags.push(tag);
+      }
+    }
+    Self {
+      title: front_matter.title.unwrap_or_else(|| title_from_path(&path)),
+      aliases: front_matter.aliases,
+      properties: front_matter.properties,
+      front_matter_error,
+      body_line,
+      word_count: body.split_whitespace().count(),
+      path,
+      content,
+      tags,
+    }
+  }
+
+  /// Names links can use for the note: file name, title and aliases
+  pub fn names(&self) -> Vec<String> {
+    let file_title = title_from_path(&self.path);
+    let mut names = vec![file_title];
+    for name in std::iter::once(&self.title).chain(&self.aliases) {
+      if !names.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+        names.push(name.clone());
+      }
+    }
+    names
+  }
+
+  /// Whether the note is a Markdown file (as opposed to an attachment)
+  pub fn is_markdown(&self) -> bool {
+    is_markdown(&self.path)
+  }
+
+  /// Markdown headings after the front matter, outside of code blocks
+  pub fn headings(&self) -> Vec<Heading> {
+    let mut headings = vec![];
+    let mut in_code_block = false;
+    for (line, text) in self.content.lines().enumerate().skip(self.body_line) {
+      if text.trim_start().starts_with("```") {
+        in_code_block = !in_code_block;
+        continue;
+      }
+      let level = text.chars().take_while(|&c| c == '#').count();
+      let Some(heading) = text[level..].strip_prefix(' ') else {
+        continue;
+      };
+      let heading = heading.trim().trim_end_matches('#').trim_end();
+      if !in_code_block && (1..=6).contains(&level) && !heading.is_empty() {
+        headings.push(Heading {
+          level,
+          text: heading.to_owned(),
+          line,
+        });
+      }
+    }
+    headings
+  }
+
+  /// Line of the content by it's index
+  pub fn line(&self, index: usize) -> Option<&str> {
+    self.content.lines().nth(index)
+  }
+}
+
+/// `## Heading` of a note
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heading {
+  /// Number of `#`s
+  pub level: usize,
+  pub text: String,
+  /// Index of the line of the heading
+  pub line: usize,
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+  path.extension().is_some_and(|extension| extension == "md")
+}
+
+/// Name of the note from it's file name, the one links use without a title or alias
+pub fn title_from_path(path: &Path) -> String {
+  let name = if is_markdown(path) {
+    path.file_stem()
+  } else {
+    path.file_name()
+  };
+  name.unwrap_or_default().to_string_lossy().into_owned()
+}
+
+/// Collect `#tags` from the text: `#` at the start of a word, followed by
+/// letters, digits, `_`, `-` or `/`, and not consisting of digits only. Code blocks are skipped.
+fn inline_tags(content: &str) -> Vec<String> {
+  let mut tags = vec![];
+  let mut in_code_block = false;
+  for line in content.lines() {
+    if line.trim_start().starts_with("```") {
+      in_code_block = !in_code_block;
+      continue;
+    }
+    // Headings and code are not tags
+    if in_code_block || line.trim_start().starts_with("# ") {
+      continue;
+    }
+    for word in line.split_whitespace() {
+      let Some(tag) = word.strip_prefix('#') else {
+        continue;
+      };
+      let tag = tag
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '/'))
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('/');
+      if !tag.is_empty()
+        && !tag.chars().all(|c| c.is_ascii_digit())
+        && !tags.iter().any(|known| known == tag)
+      {
+        tags.push(tag.to_owned());
+      }
+    }
+  }
+  tags
+}
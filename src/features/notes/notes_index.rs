@@ -0,0 +1,1466 @@
+use crate::features::note_graph::{EdgeKind, NodeKind, NoteEdge, NoteGraph, NoteNode};
+use crate::features::notes::{
+  is_markdown, parse_links, Anchor, Heading, Link, LinkKind, Note, SearchIndex, SearchResult,
+};
+use crate::lib::graph::{Graph, GraphMut};
+use crate::lib::owned_graph::NodeId;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Notes of a directory, loaded as a graph
+#[derive(Default)]
+pub struct NotesIndex {
+  /// Directory the notes were loaded from, `None` for in-memory notes
+  pub root: Option<PathBuf>,
+  pub graph: NoteGraph,
+  by_path: HashMap<PathBuf, NodeId>,
+  /// Lowercased names of the notes: file names, titles and aliases
+  by_name: HashMap<String, Vec<NodeId>>,
+  /// Ghost nodes of notes that are linked to but don't exist, by lowercased path
+  ghosts: HashMap<String, NodeId>,
+  /// Tag nodes by lowercased tag, `None` if tags are not shown in the graph
+  tag_nodes: Option<HashMap<String, NodeId>>,
+  /// Whether notes are connected to the folder notes of their folders
+  folder_edges: bool,
+  /// Heading nodes of the notes, `None` if headings are not shown in the graph
+  heading_nodes: Option<HashMap<NodeId, Vec<(Heading, NodeId)>>>,
+  /// Whether links have to match names and paths case-sensitively
+  case_sensitive: bool,
+  search: SearchIndex,
+  /// Modification times of the files the notes were read from
+  modified: HashMap<PathBuf, SystemTime>,
+  /// Incremented every time the notes change
+  revision: u64,
+  /// Whether the graph was imported, it's edges are kept as they are
+  imported: bool,
+}
+
+impl NotesIndex {
+  /// Build the graph from notes, resolving links between them
+  pub fn from_notes(root: Option<PathBuf>, notes: Vec<Note>) -> Self {
+    let mut index = Self {
+      root,
+      ..Self::default()
+    };
+    let node_ids = notes
+      .into_iter()
+      .map(|note| index.insert_note(note))
+      .collect::<Vec<_>>();
+    for node_id in node_ids {
+      index.link_note(node_id);
+    }
+    index
+  }
+
+  /// Index of a graph imported from another format.
+  /// It's edges are not resolved from links, tag, heading and folder nodes are not added to it,
+  /// and the notes can't be edited.
+  pub fn from_graph(graph: NoteGraph) -> Self {
+    let mut index = Self {
+      graph,
+      imported: true,
+      ..Self::default()
+    };
+    for (node_id, node) in index.graph.iter_nodes() {
+      index.by_path.insert(node.note.path.clone(), node_id);
+      index.search.index_note(node_id, &node.note);
+    }
+    for node_id in index.note_ids() {
+      index.index_names(node_id);
+    }
+    index
+  }
+
+  /// Error for edits of an imported graph
+  fn check_editable(&self) -> io::Result<()> {
+    if self.imported {
+      return Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "notes of an imported graph can't be edited",
+      ));
+    }
+    Ok(())
+  }
+
+  fn insert_note(&mut self, note: Note) -> NodeId {
+    let path = note.path.clone();
+    let node_id = self.graph.insert_node(NoteNode::new(note));
+    self.by_path.insert(path, node_id);
+    self.index_names(node_id);
+    self
+      .search
+      .index_note(node_id, &self.graph.get_node(node_id).note);
+    self.insert_headings(node_id);
+    node_id
+  }
+
+  /// Add a note that appeared after the notes were loaded.
+  /// If there is a ghost node for it, the ghost becomes the note.
+  fn add_note(&mut self, note: Note) -> NodeId {
+    let ghost_key = self.ghost_key(&note.path);
+    if let Some(&ghost_id) = self.ghosts.get(&ghost_key) {
+      self.materialize_ghost(ghost_id, note);
+      return ghost_id;
+    }
+    let node_id = self.insert_note(note);
+    self.link_note(node_id);
+    self.relink_resolving_to(node_id);
+    self.relink_folders();
+    node_id
+  }
+
+  /// Resolve links again that point to the note now but are not connected to it,
+  /// e.g. links to a new alias, or links to a name the note is now the closest match for
+  fn relink_resolving_to(&mut self, node_id: NodeId) {
+    let linked = self.link_sources(node_id);
+    let sources = self
+      .graph
+      .iter_nodes()
+      .filter(|&(source, node)| {
+        source != node_id
+          && !linked.contains(&source)
+          && node.note.is_markdown()
+          && parse_links(&node.note.content)
+            .iter()
+            .any(|link| self.resolve(source, link) == Some(node_id))
+      })
+      .map(|(source, _)| source)
+      .collect::<Vec<_>>();
+    for source in sources {
+      self.relink_note(source);
+    }
+  }
+
+  fn index_names(&mut self, node_id: NodeId) {
+    for name in self.graph.get_node(node_id).note.names() {
+      self
+        .by_name
+        .entry(name.to_lowercase())
+        .or_default()
+        .push(node_id);
+    }
+  }
+
+  fn unindex_names(&mut self, node_id: NodeId) {
+    for name in self.graph.get_node(node_id).note.names() {
+      let name = name.to_lowercase();
+      if let Some(node_ids) = self.by_name.get_mut(&name) {
+        node_ids.retain(|&id| id != node_id);
+        if node_ids.is_empty() {
+          self.by_name.remove(&name);
+        }
+      }
+    }
+  }
+
+  /// Remove a note whose file is gone, links to it point to a ghost node after that
+  fn remove_note(&mut self, node_id: NodeId) {
+    let path = self.graph.get_node(node_id).note.path.clone();
+    self.by_path.remove(&path);
+    self.unindex_names(node_id);
+    self.search.remove_note(node_id);
+    self.modified.remove(&path);
+    let sources = self.link_sources(node_id);
+    self.unlink_note(node_id);
+    self.remove_headings(node_id);
+    self.graph.remove_node(node_id);
+    for source in sources {
+      if source != node_id {
+        self.relink_note(source);
+      }
+    }
+    self.relink_folders();
+  }
+
+  /// Notes linking to the note or to it's heading nodes
+  fn link_sources(&self, node_id: NodeId) -> HashSet<NodeId> {
+    let headings = self
+      .heading_nodes
+      .as_ref()
+      .and_then(|nodes| nodes.get(&node_id));
+    let heading_ids = headings
+      .into_iter()
+      .flatten()
+      .map(|&(_, heading_id)| heading_id);
+    std::iter::once(node_id)
+      .chain(heading_ids)
+      .flat_map(|target| self.graph.iter_in_edges(target))
+      .map(|(edge_id, _)| self.graph.get_edge_incidents(edge_id).node_from)
+      .filter(|&source| source != node_id)
+      .collect()
+  }
+
+  /// Add nodes for the headings of the note, if headings are shown in the graph.
+  /// They are connected to the note by `link_note`.
+  fn insert_headings(&mut self, node_id: NodeId) {
+    let Some(heading_nodes) = &mut self.heading_nodes else {
+      return;
+    };
+    let note = &self.graph.get_node(node_id).note;
+    if !note.is_markdown() {
+      return;
+    }
+    let headings = note.headings();
+    let nodes = headings
+      .iter()
+      .map(|heading| NoteNode::heading(note, heading))
+      .collect::<Vec<_>>();
+    let headings = headings
+      .into_iter()
+      .zip(nodes)
+      .map(|(heading, node)| (heading, self.graph.insert_node(node)))
+      .collect();
+    heading_nodes.insert(node_id, headings);
+  }
+
+  fn remove_headings(&mut self, node_id: NodeId) {
+    let headings = self
+      .heading_nodes
+      .as_mut()
+      .and_then(|nodes| nodes.remove(&node_id));
+    for (_, heading_id) in headings.into_iter().flatten() {
+      self.graph.remove_node(heading_id);
+    }
+  }
+
+  /// Replace the heading nodes of the note if it's headings changed.
+  /// Returns whether they did.
+  fn update_headings(&mut self, node_id: NodeId) -> bool {
+    let Some(heading_nodes) = &mut self.heading_nodes else {
+      return false;
+    };
+    let headings = self.graph.get_node(node_id).note.headings();
+    let old_headings = heading_nodes.entry(node_id).or_default();
+    let same_texts = old_headings.len() == headings.len()
+      && old_headings
+        .iter()
+        .zip(&headings)
+        .all(|((old, _), new)| old.text == new.text);
+    if same_texts {
+      // Only the lines may have changed
+      for ((old, _), new) in old_headings.iter_mut().zip(headings) {
+        *old = new;
+      }
+      return false;
+    }
+    self.remove_headings(node_id);
+    self.insert_headings(node_id);
+    true
+  }
+
+  /// Node the link to the note with the anchor points to: the heading node if there is one
+  fn anchor_target(&self, note_id: NodeId, anchor: Option<&Anchor>) -> NodeId {
+    let Some(Anchor::Heading(anchor)) = anchor else {
+      return note_id;
+    };
+    let headings = self
+      .heading_nodes
+      .as_ref()
+      .and_then(|nodes| nodes.get(&note_id));
+    headings
+      .into_iter()
+      .flatten()
+      .find(|(heading, _)| self.names_match(&heading.text, anchor))
+      .map_or(note_id, |&(_, heading_id)| heading_id)
+  }
+
+  /// Whether headings are nodes of the graph
+  pub const fn has_heading_nodes(&self) -> bool {
+    self.heading_nodes.is_some()
+  }
+
+  /// Add heading nodes connected to their notes, or remove them.
+  /// Links to headings point to the heading nodes while they are shown.
+  pub fn set_heading_nodes(&mut self, enabled: bool) {
+    if self.imported || enabled == self.has_heading_nodes() {
+      return;
+    }
+    if enabled {
+      self.heading_nodes = Some(HashMap::new());
+      for node_id in self.note_ids() {
+        self.insert_headings(node_id);
+      }
+    } else {
+      let heading_ids = self
+        .heading_nodes
+        .take()
+        .into_iter()
+        .flat_map(HashMap::into_values);
+      for (_, heading_id) in heading_ids.flatten() {
+        self.graph.remove_node(heading_id);
+      }
+    }
+    self.relink_all();
+    self.revision += 1;
+  }
+
+  /// Nodes of the notes that exist
+  fn note_ids(&self) -> Vec<NodeId> {
+    self
+      .graph
+      .iter_nodes()
+      .filter(|(_, node)| node.kind == NodeKind::Note)
+      .map(|(node_id, _)| node_id)
+      .collect()
+  }
+
+  /// Resolve links of all notes again
+  fn relink_all(&mut self) {
+    let node_ids = self.note_ids();
+    for &node_id in &node_ids {
+      self.unlink_note(node_id);
+    }
+    for node_id in node_ids {
+      self.link_note(node_id);
+    }
+  }
+
+  /// Resolve links of the note again
+  fn relink_note(&mut self, node_id: NodeId) {
+    self.unlink_note(node_id);
+    self.link_note(node_id);
+  }
+
+  /// Replace the note with a new version of it, keeping the node.
+  /// If the names of the note changed, links to it are resolved again.
+  fn set_note(&mut self, node_id: NodeId, note: Note) {
+    self.unlink_note(node_id);
+    self.search.index_note(node_id, &note);
+    let old_note = &self.graph.get_node(node_id).note;
+    let renamed = old_note.names() != note.names();
+    if renamed {
+      self.unindex_names(node_id);
+    }
+    let sources = self.link_sources(node_id);
+    self.graph.get_node_mut(node_id).note = note;
+    let headings_changed = self.update_headings(node_id);
+    self.link_note(node_id);
+    if renamed {
+      self.index_names(node_id);
+    }
+    if renamed || headings_changed {
+      for source in sources {
+        self.relink_note(source);
+      }
+    }
+    if renamed {
+      self.relink_resolving_to(node_id);
+    }
+  }
+
+  /// Turn the ghost node into the note
+  fn materialize_ghost(&mut self, ghost_id: NodeId, note: Note) {
+    let ghost_key = self.ghost_key(&note.path);
+    self.ghosts.remove(&ghost_key);
+    self.by_path.insert(note.path.clone(), ghost_id);
+    self.search.index_note(ghost_id, &note);
+    let node = self.graph.get_node_mut(ghost_id);
+    node.note = note;
+    node.kind = NodeKind::Note;
+    self.index_names(ghost_id);
+    // Links to the note are resolved now
+    for source in self.link_sources(ghost_id) {
+      let node = self.graph.get_node_mut(source);
+      let mut unresolved_links = std::mem::take(&mut node.unresolved_links);
+      unresolved_links.retain(|link| self.resolve(source, link) != Some(ghost_id));
+      self.graph.get_node_mut(source).unresolved_links = unresolved_links;
+    }
+    self.insert_headings(ghost_id);
+    self.link_note(ghost_id);
+    // Links to headings point to the heading nodes now
+    if self.heading_nodes.is_some() {
+      for source in self.link_sources(ghost_id) {
+        self.relink_note(source);
+      }
+    }
+    self.relink_folders();
+  }
+
+  /// Add edges for the links of the note.
+  /// Unresolved links point to ghost nodes, which are created as needed.
+  fn link_note(&mut self, node_id: NodeId) {
+    let note = &self.graph.get_node(node_id).note;
+    if !note.is_markdown() {
+      return;
+    }
+    let links = parse_links(&note.content);
+    for link in links {
+      let target = self.resolve(node_id, &link).or_else(|| {
+        let path = self.ghost_path(node_id, &link)?;
+        let key = self.ghost_key(&path);
+        let ghost = *self
+          .ghosts
+          .entry(key)
+          .or_insert_with(|| self.graph.insert_node(NoteNode::ghost(path)));
+        Some(ghost)
+      });
+      if let Some(target) = target {
+        let edge = NoteEdge::new(EdgeKind::of_link(&link), link.line, link.anchor.clone());
+        let target = self.anchor_target(target, link.anchor.as_ref());
+        self.graph.insert_edge(node_id, target, edge);
+      }
+      if target.map_or(true, |target| self.graph.get_node(target).is_ghost()) {
+        self.graph.get_node_mut(node_id).unresolved_links.push(link);
+      }
+    }
+    self.link_tags(node_id);
+    self.link_headings(node_id);
+    self.link_folder(node_id);
+  }
+
+  /// Add an edge from the note to the folder note of it's folder
+  fn link_folder(&mut self, node_id: NodeId) {
+    if !self.folder_edges || self.graph.get_node(node_id).kind != NodeKind::Note {
+      return;
+    }
+    if let Some(folder_note) = self.folder_note(node_id) {
+      let edge = NoteEdge::new(EdgeKind::Folder, 0, None);
+      self.graph.insert_edge(node_id, folder_note, edge);
+    }
+  }
+
+  /// Folder note of the nearest folder containing the note:
+  /// `folder/folder.md`, `folder/index.md` or `folder.md` next to the folder.
+  /// Folder notes themselves belong to the folder above.
+  fn folder_note(&self, node_id: NodeId) -> Option<NodeId> {
+    let path = &self.graph.get_node(node_id).note.path;
+    path.ancestors().skip(1).find_map(|folder| {
+      let mut candidates = vec![folder.join("index.md")];
+      if let (Some(name), Some(parent)) = (folder.file_name(), folder.parent()) {
+        let file_name = format!("{}.md", name.to_string_lossy());
+        candidates.extend([folder.join(&file_name), parent.join(&file_name)]);
+      }
+      candidates
+        .iter()
+        .filter_map(|candidate| self.by_path.get(candidate).copied())
+        .find(|&folder_note| folder_note != node_id)
+    })
+  }
+
+  /// Link all notes to their folder notes again, after notes were added or removed
+  fn relink_folders(&mut self) {
+    if !self.folder_edges {
+      return;
+    }
+    self.unlink_folders();
+    for node_id in self.note_ids() {
+      self.link_folder(node_id);
+    }
+  }
+
+  fn unlink_folders(&mut self) {
+    let edges = self
+      .graph
+      .iter_edges()
+      .filter(|(_, edge)| edge.kind == EdgeKind::Folder)
+      .map(|(edge_id, _)| edge_id)
+      .collect::<Vec<_>>();
+    for edge_id in edges {
+      self.graph.remove_edge(edge_id);
+    }
+  }
+
+  /// Whether notes are connected to the folder notes of their folders
+  pub const fn has_folder_edges(&self) -> bool {
+    self.folder_edges
+  }
+
+  /// Connect notes to the folder notes of their folders, or disconnect them
+  pub fn set_folder_edges(&mut self, enabled: bool) {
+    if self.imported || enabled == self.has_folder_edges() {
+      return;
+    }
+    self.folder_edges = enabled;
+    if enabled {
+      self.relink_folders();
+    } else {
+      self.unlink_folders();
+    }
+    self.revision += 1;
+  }
+
+  /// Add edges from the note to it's heading nodes
+  fn link_headings(&mut self, node_id: NodeId) {
+    let headings = self
+      .heading_nodes
+      .as_ref()
+      .and_then(|nodes| nodes.get(&node_id));
+    let edges = headings
+      .into_iter()
+      .flatten()
+      .map(|(heading, heading_id)| {
+        let anchor = Anchor::Heading(heading.text.clone());
+        let edge = NoteEdge::new(EdgeKind::Heading, heading.line, Some(anchor));
+        (*heading_id, edge)
+      })
+      .collect::<Vec<_>>();
+    for (heading_id, edge) in edges {
+      self.graph.insert_edge(node_id, heading_id, edge);
+    }
+  }
+
+  /// Add edges from the note to the nodes of it's tags, which are created as needed
+  fn link_tags(&mut self, node_id: NodeId) {
+    let Some(tag_nodes) = &mut self.tag_nodes else {
+      return;
+    };
+    let note = &self.graph.get_node(node_id).note;
+    let tags = note
+      .tags
+      .iter()
+      .map(|tag| {
+        // Front matter tags are written without `#`, they are near the top anyway
+        let hashtag = format!("#{tag}");
+        let line = note
+          .content
+          .lines()
+          .position(|line| line.contains(&hashtag));
+        (tag.clone(), line.unwrap_or_default())
+      })
+      .collect::<Vec<_>>();
+    for (tag, line) in tags {
+      let tag_id = *tag_nodes
+        .entry(tag.to_lowercase())
+        .or_insert_with(|| self.graph.insert_node(NoteNode::tag(&tag)));
+      self
+        .graph
+        .insert_edge(node_id, tag_id, NoteEdge::new(EdgeKind::Tag, line, None));
+    }
+  }
+
+  /// Whether tags are nodes of the graph
+  pub const fn has_tag_nodes(&self) -> bool {
+    self.tag_nodes.is_some()
+  }
+
+  /// Add tag nodes connected to the notes that have the tags, or remove them
+  pub fn set_tag_nodes(&mut self, enabled: bool) {
+    if self.imported || enabled == self.has_tag_nodes() {
+      return;
+    }
+    if enabled {
+      self.tag_nodes = Some(HashMap::new());
+      for node_id in self.note_ids() {
+        self.link_tags(node_id);
+      }
+    } else {
+      for tag_id in self
+        .tag_nodes
+        .take()
+        .into_iter()
+        .flat_map(HashMap::into_values)
+      {
+        self.graph.remove_node(tag_id);
+      }
+    }
+    self.revision += 1;
+  }
+
+  /// Remove edges for the links and tags of the note, and ghost and tag nodes
+  /// that are no longer linked
+  fn unlink_note(&mut self, node_id: NodeId) {
+    let edges = self
+      .graph
+      .iter_out_edges(node_id)
+      .map(|(edge_id, _)| edge_id)
+      .collect::<Vec<_>>();
+    for edge_id in edges {
+      self.graph.remove_edge(edge_id);
+    }
+    self.graph.get_node_mut(node_id).unresolved_links.clear();
+    remove_unlinked(&mut self.graph, &mut self.ghosts);
+    if let Some(tag_nodes) = &mut self.tag_nodes {
+      remove_unlinked(&mut self.graph, tag_nodes);
+    }
+  }
+
+  /// Key of the ghost node for the path in `ghosts`
+  fn ghost_key(&self, path: &Path) -> String {
+    let key = slash_path(path);
+    if self.case_sensitive {
+      key
+    } else {
+      key.to_lowercase()
+    }
+  }
+
+  /// Path of the note an unresolved link would create.
+  /// `None` for links to files that are not Markdown notes.
+  fn ghost_path(&self, source: NodeId, link: &Link) -> Option<PathBuf> {
+    let path = match link.kind {
+      LinkKind::Wiki => normalize(Path::new(&link.target)),
+      LinkKind::Markdown => {
+        let source_dir = self.graph.get_node(source).note.path.parent()?;
+        normalize(&source_dir.join(&link.target))
+      }
+    };
+    match path.extension() {
+      None => Some(path.with_extension("md")),
+      Some(_) if is_markdown(&path) => Some(path),
+      Some(_) => None,
+    }
+  }
+
+  /// Load all files under `root`, hidden files and directories are skipped
+  pub fn load(root: &Path) -> io::Result<Self> {
+    let mut paths = vec![];
+    collect_files(root, &mut paths)?;
+    paths.sort();
+    let mut notes = vec![];
+    let mut modified = HashMap::new();
+    for path in paths {
+      let relative_path = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+      modified.insert(relative_path.clone(), std::fs::metadata(&path)?.modified()?);
+      let note = Note::new(relative_path, read_content(&path)?);
+      report_front_matter_error(&note);
+      notes.push(note);
+    }
+    let mut index = Self::from_notes(Some(root.to_path_buf()), notes);
+    index.modified = modified;
+    Ok(index)
+  }
+
+  /// Number of times the notes have changed since they were loaded
+  pub const fn revision(&self) -> u64 {
+    self.revision
+  }
+
+  /// Replace the content of the note, writing it to disk if the notes were loaded from a directory.
+  /// Links of the note are resolved again.
+  pub fn update_note(&mut self, node_id: NodeId, content: String) -> io::Result<()> {
+    self.check_editable()?;
+    let path = self.graph.get_node(node_id).note.path.clone();
+    if let Some(root) = &self.root {
+      let file_path = root.join(&path);
+      std::fs::write(&file_path, &content)?;
+      self
+        .modified
+        .insert(path.clone(), std::fs::metadata(file_path)?.modified()?);
+    }
+    self.set_note(node_id, Note::new(path, content));
+    self.revision += 1;
+    Ok(())
+  }
+
+  /// Turn the ghost node into a real note, created from the `template`.
+  /// `{{title}}` in the template is replaced with the title of the note.
+  /// The node keeps it's id, so it stays where it was in the layout.
+  pub fn create_note(&mut self, ghost_id: NodeId, template: &str) -> io::Result<()> {
+    self.check_editable()?;
+    let node = self.graph.get_node(ghost_id);
+    if !node.is_ghost() {
+      return Ok(());
+    }
+    let path = node.note.path.clone();
+    let content = template.replace("{{title}}", &node.note.title);
+    if let Some(root) = &self.root {
+      let file_path = root.join(&path);
+      if let Some(dir) = file_path.parent() {
+        std::fs::create_dir_all(dir)?;
+      }
+      std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&file_path)?
+        .write_all(content.as_bytes())?;
+      self
+        .modified
+        .insert(path.clone(), std::fs::metadata(file_path)?.modified()?);
+    }
+    self.materialize_ghost(ghost_id, Note::new(path, content));
+    self.revision += 1;
+    Ok(())
+  }
+
+  /// Move the note to `new_path` and replace the contents of notes with the `contents`, e.g. the
+  /// ones with links to the note rewritten. The node keeps it's id, so it stays where it was in the layout.
+  /// Nothing changes if the note can't be moved. Notes whose files can't be replaced after it was moved
+  /// keep their old contents, and the first such error is returned.
+  pub fn move_note(
+    &mut self,
+    node_id: NodeId,
+    new_path: PathBuf,
+    contents: Vec<(NodeId, String)>,
+  ) -> io::Result<()> {
+    self.check_editable()?;
+    let old_path = self.graph.get_node(node_id).note.path.clone();
+    let exists = self.by_path.contains_key(&new_path)
+      || (self.root.as_ref()).is_some_and(|root| root.join(&new_path).exists());
+    if exists {
+      return Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{} already exists", new_path.display()),
+      ));
+    }
+
+    // New contents are written next to the files first, so that nothing changes if that fails
+    let temp_files = match &self.root {
+      Some(root) => {
+        let temp_files = self.write_temp_files(root, &contents)?;
+        let file_path = root.join(&new_path);
+        let moved = file_path
+          .parent()
+          .map_or(Ok(()), std::fs::create_dir_all)
+          .and_then(|()| std::fs::rename(root.join(&old_path), &file_path));
+        if let Err(error) = moved {
+          remove_files(temp_files.iter().flatten());
+          return Err(error);
+        }
+        let modified = self.modified.remove(&old_path);
+        (self.modified).extend(modified.map(|modified| (new_path.clone(), modified)));
+        temp_files
+      }
+      None => vec![None; contents.len()],
+    };
+    self.by_path.remove(&old_path);
+    self.by_path.insert(new_path.clone(), node_id);
+    let content = self.graph.get_node(node_id).note.content.clone();
+    self.set_note(node_id, Note::new(new_path, content));
+
+    let mut result = Ok(());
+    for ((id, content), temp_file) in contents.into_iter().zip(temp_files) {
+      let path = self.graph.get_node(id).note.path.clone();
+      if let (Some(root), Some(temp_file)) = (&self.root, temp_file) {
+        let file_path = root.join(&path);
+        if let Err(error) = std::fs::rename(&temp_file, &file_path) {
+          remove_files([&temp_file]);
+          result = result.and(Err(error));
+          continue;
+        }
+        if let Ok(modified) = std::fs::metadata(&file_path).and_then(|meta| meta.modified()) {
+          self.modified.insert(path.clone(), modified);
+        }
+      }
+      self.set_note(id, Note::new(path, content));
+    }
+    self.relink_folders();
+    self.revision += 1;
+    result
+  }
+
+  /// Write the contents to hidden files next to the ones of the notes, which `refresh` skips.
+  /// Removes the ones already written if one can't be.
+  fn write_temp_files(
+    &self,
+    root: &Path,
+    contents: &[(NodeId, String)],
+  ) -> io::Result<Vec<Option<PathBuf>>> {
+    let mut temp_files = vec![];
+    for (id, content) in contents {
+      let file_path = root.join(&self.graph.get_node(*id).note.path);
+      let name = file_path.file_name().unwrap_or_default().to_string_lossy();
+      let temp_file = file_path.with_file_name(format!(".{name}.basalt"));
+      if let Err(error) = std::fs::write(&temp_file, content) {
+        remove_files(temp_files.iter().flatten().chain([&temp_file]));
+        return Err(error);
+      }
+      temp_files.push(Some(temp_file));
+    }
+    Ok(temp_files)
+  }
+
+  /// Pick up files that were added, changed or removed in the notes directory since the last time.
+  /// Returns whether anything changed.
+  pub fn refresh(&mut self) -> io::Result<bool> {
+    let Some(root) = self.root.clone() else {
+      return Ok(false);
+    };
+    let mut paths = vec![];
+    collect_files(&root, &mut paths)?;
+    paths.sort();
+    let mut changed = false;
+    let mut seen = HashSet::new();
+    for file_path in paths {
+      let path = file_path
+        .strip_prefix(&root)
+        .unwrap_or(&file_path)
+        .to_path_buf();
+      let modified = std::fs::metadata(&file_path)?.modified()?;
+      seen.insert(path.clone());
+      if self.modified.get(&path) == Some(&modified) {
+        continue;
+      }
+      let note = Note::new(path.clone(), read_content(&file_path)?);
+      report_front_matter_error(&note);
+      match self.node_by_path(&path) {
+        Some(node_id) => self.set_note(node_id, note),
+        None => {
+          self.add_note(note);
+        }
+      }
+      self.modified.insert(path, modified);
+      changed = true;
+    }
+    let removed = self
+      .by_path
+      .iter()
+      .filter(|(path, _)| !seen.contains(*path))
+      .map(|(_, &node_id)| node_id)
+      .collect::<Vec<_>>();
+    for node_id in removed {
+      self.remove_note(node_id);
+      changed = true;
+    }
+    if changed {
+      self.revision += 1;
+    }
+    Ok(changed)
+  }
+
+  /// Find notes matching the query, see `parse_query` for the syntax
+  pub fn search(&self, query: &str) -> Vec<SearchResult> {
+    self
+      .search
+      .search(query, |node_id| &self.graph.get_node(node_id).note.content)
+  }
+
+  /// Find the note by it's path relative to the notes directory
+  pub fn node_by_path(&self, path: &Path) -> Option<NodeId> {
+    self.by_path.get(path).copied()
+  }
+
+  /// Find the note the link points to, the best of `resolve_candidates`
+  pub fn resolve(&self, source: NodeId, link: &Link) -> Option<NodeId> {
+    self.resolve_candidates(source, link).first().copied()
+  }
+
+  /// Notes the link could point to, best first. Wikilinks match names (file names, titles and
+  /// aliases) or, if they contain a `/`, the end of the path. Markdown links match the path relative
+  /// to the source note. Of several matches the one from the notes directory root wins, then the one
+  /// closest to the source note, then the one with the shortest path, then the first alphabetically.
+  pub fn resolve_candidates(&self, source: NodeId, link: &Link) -> Vec<NodeId> {
+    let source_path = &self.graph.get_node(source).note.path;
+    let target = match link.kind {
+      LinkKind::Wiki => normalize(Path::new(&link.target)),
+      LinkKind::Markdown => {
+        let source_dir = source_path.parent().unwrap_or_else(|| Path::new(""));
+        normalize(&source_dir.join(&link.target))
+      }
+    };
+    let target = slash_path(&target);
+    // Markdown notes can be linked without the extension
+    let target = target.strip_suffix(".md").unwrap_or(&target);
+    let name = target.rsplit('/').next().unwrap_or(target);
+    let Some(node_ids) = self.by_name.get(&name.to_lowercase()) else {
+      return vec![];
+    };
+    let without_extension = |path: &Path| {
+      let path = slash_path(path);
+      path.strip_suffix(".md").map(str::to_owned).unwrap_or(path)
+    };
+    let mut candidates = node_ids
+      .iter()
+      .filter_map(|&node_id| {
+        let note = &self.graph.get_node(node_id).note;
+        let path = without_extension(&note.path);
+        let from_root = self.names_match(&path, target);
+        let matches = match link.kind {
+          LinkKind::Wiki if target.contains('/') => {
+            // The end of the path, after a `/`
+            from_root
+              || path.len().checked_sub(target.len()).is_some_and(|start| {
+                path.is_char_boundary(start)
+                  && path[..start].ends_with('/')
+                  && self.names_match(&path[start..], target)
+              })
+          }
+          LinkKind::Wiki => note.names().iter().any(|own| self.names_match(own, target)),
+          LinkKind::Markdown => from_root,
+        };
+        let shared = common_prefix(source_path.parent(), note.path.parent());
+        let depth = note.path.components().count();
+        matches.then_some((
+          (!from_root, std::cmp::Reverse(shared), depth, path),
+          node_id,
+        ))
+      })
+      .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.dedup_by_key(|(_, node_id)| *node_id);
+    candidates.into_iter().map(|(_, node_id)| node_id).collect()
+  }
+
+  /// Compare names or paths as configured with `set_case_sensitive`
+  pub(super) fn names_match(&self, a: &str, b: &str) -> bool {
+    if self.case_sensitive {
+      a == b
+    } else {
+      a == b || a.to_lowercase() == b.to_lowercase()
+    }
+  }
+
+  /// Make link resolution case-sensitive or not (the default), resolving all links again
+  pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+    if self.case_sensitive == case_sensitive {
+      return;
+    }
+    self.case_sensitive = case_sensitive;
+    self.relink_all();
+    self.revision += 1;
+  }
+}
+
+/// Path with `/` separators
+pub(super) fn slash_path(path: &Path) -> String {
+  path
+    .components()
+    .map(|component| component.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Number of leading components the directories have in common
+fn common_prefix(a: Option<&Path>, b: Option<&Path>) -> usize {
+  let (Some(a), Some(b)) = (a, b) else {
+    return 0;
+  };
+  a.components()
+    .zip(b.components())
+    .take_while(|(a, b)| a == b)
+    .count()
+}
+
+/// Resolve `.` and `..` components without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => (),
+      Component::ParentDir => {
+        normalized.pop();
+      }
+      component => normalized.push(component),
+    }
+  }
+  normalized
+}
+
+/// Remove the nodes nothing links to
+fn remove_unlinked(graph: &mut NoteGraph, nodes: &mut HashMap<String, NodeId>) {
+  nodes.retain(|_, &mut node_id| {
+    let linked = graph.iter_in_edges(node_id).next().is_some();
+    if !linked {
+      graph.remove_node(node_id);
+    }
+    linked
+  });
+}
+
+fn report_front_matter_error(note: &Note) {
+  if let Some(error) = &note.front_matter_error {
+    log::warn!("{}: {error}", note.path.display());
+  }
+}
+
+/// Content of a Markdown note, attachments are not read
+fn read_content(path: &Path) -> io::Result<String> {
+  if is_markdown(path) {
+    std::fs::read_to_string(path)
+  } else {
+    Ok(String::new())
+  }
+}
+
+/// Remove files that may or may not exist, ignoring errors
+fn remove_files<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) {
+  for path in paths {
+    let _ = std::fs::remove_file(path);
+  }
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+  for entry in std::fs::read_dir(dir)? {
+    let entry = entry?;
+    if entry.file_name().to_string_lossy().starts_with('.') {
+      continue;
+    }
+    if entry.file_type()?.is_dir() {
+      collect_files(&entry.path(), paths)?;
+    } else {
+      paths.push(entry.path());
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::notes::{link_diagnostics, LinkDiagnostic};
+  use crate::lib::export::ExportFormat;
+  use crate::lib::import::import_graph;
+
+  #[test]
+  fn links_are_resolved() {
+    let index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new(
+          "index.md",
+          "[[Rust]] and [cargo](topics/cargo.md) and [[Missing]]",
+        ),
+        Note::new("topics/Rust.md", "Back to [index](../index.md)"),
+        Note::new("topics/cargo.md", ""),
+      ],
+    );
+    let index_id = index.node_by_path(Path::new("index.md")).unwrap();
+    let rust_id = index.node_by_path(Path::new("topics/Rust.md")).unwrap();
+    // `[[Missing]]` points to a ghost node
+    assert_eq!(index.graph.iter_out_edges(index_id).count(), 3);
+    assert_eq!(index.graph.iter_in_edges(index_id).count(), 1);
+    assert_eq!(index.graph.iter_in_edges(rust_id).count(), 1);
+    let unresolved = &index.graph.get_node(index_id).unresolved_links;
+    assert_eq!(unresolved.len(), 1);
+    assert_eq!(unresolved[0].target, "Missing");
+  }
+
+  #[test]
+  fn ghost_becomes_note() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "[[Missing]]"),
+        Note::new("b.md", "[[missing]]"),
+      ],
+    );
+    let a = index.node_by_path(Path::new("a.md")).unwrap();
+    let (ghost_id, ghost) = index
+      .graph
+      .iter_nodes()
+      .find(|(_, node)| node.is_ghost())
+      .unwrap();
+    assert_eq!(ghost.note.path, Path::new("Missing.md"));
+    assert_eq!(index.graph.iter_in_edges(ghost_id).count(), 2);
+
+    index.create_note(ghost_id, "# {{title}}\n").unwrap();
+    let note = index.graph.get_node(ghost_id);
+    assert!(!note.is_ghost());
+    assert_eq!(note.note.content, "# Missing\n");
+    assert_eq!(index.node_by_path(Path::new("Missing.md")), Some(ghost_id));
+    assert!(index.graph.get_node(a).unresolved_links.is_empty());
+
+    index.update_note(a, String::new()).unwrap();
+    assert_eq!(index.graph.iter_in_edges(ghost_id).count(), 1);
+  }
+
+  #[test]
+  fn aliases_are_resolved() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "[[Graphs]] and [[Networks]]"),
+        Note::new(
+          "graph-theory.md",
+          "---\ntitle: Graph theory\naliases: [Graphs]\ntags: [math]\n---\n#cs",
+        ),
+        Note::new("broken.md", "---\ntags: [a\n---\n[[graph-theory]]"),
+      ],
+    );
+    let a = index.node_by_path(Path::new("a.md")).unwrap();
+    let graph_theory = index.node_by_path(Path::new("graph-theory.md")).unwrap();
+    let note = &index.graph.get_node(graph_theory).note;
+    assert_eq!(note.title, "Graph theory");
+    assert_eq!(note.tags, vec!["math", "cs"]);
+    // Linked by the alias and by the file name
+    assert_eq!(index.graph.iter_in_edges(graph_theory).count(), 2);
+    let broken = index.node_by_path(Path::new("broken.md")).unwrap();
+    assert!(index
+      .graph
+      .get_node(broken)
+      .note
+      .front_matter_error
+      .is_some());
+
+    // A new alias resolves a link that pointed to a ghost
+    let content = "---\naliases: [Graphs, Networks]\n---\n".to_owned();
+    index.update_note(graph_theory, content).unwrap();
+    assert!(index.graph.get_node(a).unresolved_links.is_empty());
+    assert_eq!(index.graph.iter_in_edges(graph_theory).count(), 3);
+    assert!(index.graph.iter_nodes().all(|(_, node)| !node.is_ghost()));
+  }
+
+  #[test]
+  fn tag_nodes() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "#rust #graphs"),
+        Note::new("b.md", "#Rust"),
+      ],
+    );
+    let tags = |index: &NotesIndex| {
+      index
+        .graph
+        .iter_nodes()
+        .filter(|(_, node)| node.kind == NodeKind::Tag)
+        .map(|(node_id, node)| {
+          (
+            node.note.title.clone(),
+            index.graph.iter_in_edges(node_id).count(),
+          )
+        })
+        .collect::<HashMap<_, _>>()
+    };
+    index.set_tag_nodes(true);
+    assert_eq!(
+      tags(&index),
+      HashMap::from([("#rust".to_owned(), 2), ("#graphs".to_owned(), 1)])
+    );
+
+    let a = index.node_by_path(Path::new("a.md")).unwrap();
+    index.update_note(a, "#rust".to_owned()).unwrap();
+    assert_eq!(tags(&index), HashMap::from([("#rust".to_owned(), 2)]));
+
+    index.set_tag_nodes(false);
+    assert!(tags(&index).is_empty());
+  }
+
+  #[test]
+  fn imported_graph() {
+    let dot = r#"digraph { a [label="Crate A", tags=rust]; a -> b [kind=depends]; }"#;
+    let mut index = NotesIndex::from_graph(import_graph(dot, ExportFormat::Dot).unwrap());
+    let a = index.node_by_path(Path::new("a")).unwrap();
+    assert_eq!(index.graph.get_node(a).note.title, "Crate A");
+    let (_, edge) = index.graph.iter_out_edges(a).next().unwrap();
+    assert_eq!(edge.kind, EdgeKind::Typed("depends".to_owned()));
+
+    index.set_tag_nodes(true);
+    index.set_heading_nodes(true);
+    assert_eq!(index.graph.iter_edges().count(), 1);
+    assert!(index.update_note(a, String::new()).is_err());
+  }
+
+  #[test]
+  fn heading_nodes() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "[[b#Usage]] [[b#Missing]]"),
+        Note::new("b.md", "# Intro\n## usage"),
+      ],
+    );
+    let a = index.node_by_path(Path::new("a.md")).unwrap();
+    let b = index.node_by_path(Path::new("b.md")).unwrap();
+    let targets = |index: &NotesIndex| {
+      index
+        .graph
+        .iter_out_edges(a)
+        .map(|(edge_id, _)| {
+          let target = index.graph.get_edge_incidents(edge_id).node_to;
+          index.graph.get_node(target).note.title.clone()
+        })
+        .collect::<HashSet<_>>()
+    };
+    assert_eq!(targets(&index), HashSet::from(["b".to_owned()]));
+
+    index.set_heading_nodes(true);
+    assert_eq!(index.graph.iter_out_edges(b).count(), 2);
+    assert_eq!(
+      targets(&index),
+      HashSet::from(["b".to_owned(), "b › usage".to_owned()])
+    );
+
+    index.update_note(b, "## Usage".to_owned()).unwrap();
+    assert_eq!(
+      targets(&index),
+      HashSet::from(["b".to_owned(), "b › Usage".to_owned()])
+    );
+
+    index.set_heading_nodes(false);
+    assert_eq!(targets(&index), HashSet::from(["b".to_owned()]));
+    assert_eq!(index.graph.iter_nodes().count(), 2);
+  }
+
+  #[test]
+  fn folder_edges() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("index.md", ""),
+        Note::new("projects.md", ""),
+        Note::new("projects/basalt.md", "parent:: [[index]]"),
+        Note::new("projects/basalt/notes.md", ""),
+      ],
+    );
+    index.set_folder_edges(true);
+    let folder_of = |index: &NotesIndex, path: &str| {
+      let node_id = index.node_by_path(Path::new(path)).unwrap();
+      index
+        .graph
+        .iter_out_edges(node_id)
+        .filter(|(_, edge)| edge.kind == EdgeKind::Folder)
+        .map(|(edge_id, _)| {
+          let target = index.graph.get_edge_incidents(edge_id).node_to;
+          index.graph.get_node(target).note.path.clone()
+        })
+        .next()
+    };
+    assert_eq!(folder_of(&index, "index.md"), None);
+    assert_eq!(folder_of(&index, "projects.md"), Some("index.md".into()));
+    assert_eq!(
+      folder_of(&index, "projects/basalt.md"),
+      Some("projects.md".into())
+    );
+    assert_eq!(
+      folder_of(&index, "projects/basalt/notes.md"),
+      Some("projects/basalt.md".into())
+    );
+    let basalt = index.node_by_path(Path::new("projects/basalt.md")).unwrap();
+    let kinds = index
+      .graph
+      .iter_out_edges(basalt)
+      .map(|(_, edge)| edge.kind.clone())
+      .collect::<HashSet<_>>();
+    assert_eq!(
+      kinds,
+      HashSet::from([EdgeKind::Folder, EdgeKind::Typed("parent".to_owned())])
+    );
+
+    index.set_folder_edges(false);
+    assert_eq!(folder_of(&index, "projects.md"), None);
+  }
+
+  #[test]
+  fn closest_path_breaks_ties() {
+    let index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("x/y/source.md", "[[Note]] [[Other]]"),
+        Note::new("x/y/z/Note.md", ""),
+        Note::new("x/Note.md", ""),
+        Note::new("w/Note.md", ""),
+        Note::new("c/d/Other.md", ""),
+        Note::new("b/Other.md", ""),
+        Note::new("a/Other.md", ""),
+        Note::new("root.md", "[[Other]] [[Note]]"),
+      ],
+    );
+    let id = |path: &str| index.node_by_path(Path::new(path)).unwrap();
+    let candidates = |source: &str| {
+      let source = id(source);
+      parse_links(&index.graph.get_node(source).note.content)
+        .iter()
+        .map(|link| index.resolve_candidates(source, link))
+        .collect::<Vec<_>>()
+    };
+    // Most folders in common with the source first, then the shortest path, then alphabetically
+    assert_eq!(
+      candidates("x/y/source.md"),
+      vec![
+        vec![id("x/y/z/Note.md"), id("x/Note.md"), id("w/Note.md")],
+        vec![id("a/Other.md"), id("b/Other.md"), id("c/d/Other.md")],
+      ]
+    );
+    assert_eq!(
+      candidates("root.md")[1],
+      vec![id("w/Note.md"), id("x/Note.md"), id("x/y/z/Note.md")]
+    );
+  }
+
+  #[test]
+  fn case_sensitive_links() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "[[rust]] [[Rust]] [[Topics/rust]]"),
+        Note::new("topics/Rust.md", ""),
+      ],
+    );
+    let a = index.node_by_path(Path::new("a.md")).unwrap();
+    let rust = index.node_by_path(Path::new("topics/Rust.md")).unwrap();
+    assert_eq!(index.graph.iter_in_edges(rust).count(), 3);
+
+    index.set_case_sensitive(true);
+    assert_eq!(index.graph.iter_in_edges(rust).count(), 1);
+    let unresolved = &index.graph.get_node(a).unresolved_links;
+    let targets = unresolved
+      .iter()
+      .map(|link| link.target.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(targets, vec!["rust", "Topics/rust"]);
+    assert_eq!(
+      index
+        .graph
+        .iter_nodes()
+        .filter(|(_, node)| node.is_ghost())
+        .count(),
+      2
+    );
+
+    index.set_case_sensitive(false);
+    assert_eq!(index.graph.iter_in_edges(rust).count(), 3);
+    assert!(index.graph.iter_nodes().all(|(_, node)| !node.is_ghost()));
+  }
+
+  #[test]
+  fn wikilink_paths_in_non_ascii_folders() {
+    let index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("é/c.md", ""),
+        Note::new("é/a/c.md", ""),
+        Note::new("source.md", "[[a/c]] [[é/c]] [[b/c]]"),
+      ],
+    );
+    let source = index.node_by_path(Path::new("source.md")).unwrap();
+    let resolved = parse_links(&index.graph.get_node(source).note.content)
+      .iter()
+      .map(|link| index.resolve(source, link))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      resolved,
+      vec![
+        index.node_by_path(Path::new("é/a/c.md")),
+        index.node_by_path(Path::new("é/c.md")),
+        None,
+      ]
+    );
+  }
+
+  #[test]
+  fn ambiguous_links_prefer_the_closest_note() {
+    let mut index = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a/Note.md", ""),
+        Note::new("b/Note.md", ""),
+        Note::new("a/source.md", "[[Note]] [[b/Note#Heading]] [[note|text]]"),
+        Note::new("root.md", "[[Note]]"),
+      ],
+    );
+    let id = |path: &str| index.node_by_path(Path::new(path)).unwrap();
+    let source = id("a/source.md");
+    let links = parse_links(&index.graph.get_node(source).note.content);
+    let resolved = links
+      .iter()
+      .map(|link| index.resolve(source, link))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      resolved,
+      vec![
+        Some(id("a/Note.md")),
+        Some(id("b/Note.md")),
+        Some(id("a/Note.md"))
+      ]
+    );
+    let root_link = &parse_links("[[Note]]")[0];
+    assert_eq!(
+      index.resolve(id("root.md"), root_link),
+      Some(id("a/Note.md"))
+    );
+    let diagnostics = link_diagnostics(&index);
+    assert_eq!(diagnostics.len(), 3);
+    assert!(diagnostics.iter().all(LinkDiagnostic::is_ambiguous));
+
+    index.set_case_sensitive(true);
+    let diagnostics = link_diagnostics(&index);
+    assert_eq!(diagnostics.iter().filter(|d| !d.is_ambiguous()).count(), 1);
+    assert!(index.graph.iter_nodes().any(|(_, node)| node.is_ghost()));
+  }
+
+  #[test]
+  fn load_from_directory() {
+    crate::lib::test::with_test_dir(|temp_dir| {
+      std::fs::create_dir_all(temp_dir.join("topics"))?;
+      std::fs::create_dir_all(temp_dir.join(".hidden"))?;
+      std::fs::write(temp_dir.join("index.md"), "[[Rust]]")?;
+      std::fs::write(temp_dir.join("topics/Rust.md"), "#programming")?;
+      std::fs::write(temp_dir.join(".hidden/secret.md"), "")?;
+      let index = NotesIndex::load(temp_dir)?;
+      assert_eq!(index.graph.iter_nodes().count(), 2);
+      assert_eq!(index.graph.iter_edges().count(), 1);
+      let rust_id = index.node_by_path(Path::new("topics/Rust.md")).unwrap();
+      assert_eq!(index.graph.get_node(rust_id).note.tags, vec!["programming"]);
+      Ok(())
+    })
+    .unwrap();
+  }
+
+  #[test]
+  fn move_note_on_disk() {
+    crate::lib::test::with_test_dir(|temp_dir| {
+      std::fs::write(temp_dir.join("index.md"), "[[Old]]")?;
+      std::fs::write(temp_dir.join("Old.md"), "old")?;
+      let mut index = NotesIndex::load(temp_dir)?;
+      let index_id = index.node_by_path(Path::new("index.md")).unwrap();
+      let old = index.node_by_path(Path::new("Old.md")).unwrap();
+      let contents = vec![(index_id, "[[New]]".to_owned())];
+
+      // The new content can't be written, nothing changes
+      std::fs::create_dir(temp_dir.join(".index.md.basalt"))?;
+      let revision = index.revision();
+      assert!(index
+        .move_note(old, "dir/New.md".into(), contents.clone())
+        .is_err());
+      assert!(temp_dir.join("Old.md").exists());
+      assert_eq!(index.node_by_path(Path::new("Old.md")), Some(old));
+      assert_eq!(index.revision(), revision);
+
+      std::fs::remove_dir(temp_dir.join(".index.md.basalt"))?;
+      index.move_note(old, "dir/New.md".into(), contents)?;
+      assert!(!temp_dir.join("Old.md").exists());
+      assert_eq!(std::fs::read_to_string(temp_dir.join("dir/New.md"))?, "old");
+      assert_eq!(
+        std::fs::read_to_string(temp_dir.join("index.md"))?,
+        "[[New]]"
+      );
+      assert_eq!(index.node_by_path(Path::new("dir/New.md")), Some(old));
+      assert_eq!(index.graph.iter_in_edges(old).count(), 1);
+      assert!(!index.refresh()?);
+      Ok(())
+    })
+    .unwrap();
+  }
+
+  #[test]
+  fn create_note_over_existing_file() {
+    crate::lib::test::with_test_dir(|temp_dir| {
+      std::fs::write(temp_dir.join("index.md"), "[[Missing]]")?;
+      let mut index = NotesIndex::load(temp_dir)?;
+      let (ghost_id, _) = index
+        .graph
+        .iter_nodes()
+        .find(|(_, node)| node.is_ghost())
+        .unwrap();
+      // Written by something else before the notes were refreshed
+      std::fs::write(temp_dir.join("Missing.md"), "mine")?;
+      let revision = index.revision();
+      let error = index.create_note(ghost_id, "# {{title}}\n").unwrap_err();
+      assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+      assert!(index.graph.get_node(ghost_id).is_ghost());
+      assert_eq!(index.revision(), revision);
+      assert_eq!(
+        std::fs::read_to_string(temp_dir.join("Missing.md"))?,
+        "mine"
+      );
+      Ok(())
+    })
+    .unwrap();
+  }
+
+  #[test]
+  fn refresh_picks_up_changes() {
+    crate::lib::test::with_test_dir(|temp_dir| {
+      std::fs::write(temp_dir.join("index.md"), "[[New]] and [[Old]]")?;
+      std::fs::write(temp_dir.join("Old.md"), "old")?;
+      let mut index = NotesIndex::load(temp_dir)?;
+      assert!(!index.refresh()?);
+      let index_id = index.node_by_path(Path::new("index.md")).unwrap();
+
+      std::fs::write(temp_dir.join("New.md"), "fresh words")?;
+      std::fs::remove_file(temp_dir.join("Old.md"))?;
+      assert!(index.refresh()?);
+      let new_id = index.node_by_path(Path::new("New.md")).unwrap();
+      assert!(!index.graph.get_node(new_id).is_ghost());
+      assert_eq!(index.search("fresh")[0].node_id, new_id);
+      assert!(index.node_by_path(Path::new("Old.md")).is_none());
+      assert!(index.search("title:old").is_empty());
+      // `[[Old]]` points to a ghost now
+      assert_eq!(index.graph.get_node(index_id).unresolved_links.len(), 1);
+      Ok(())
+    })
+    .unwrap();
+  }
+}
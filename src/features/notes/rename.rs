@@ -0,0 +1,342 @@
+use crate::features::note_graph::NodeKind;
+use crate::features::notes::notes_index::slash_path;
+use crate::features::notes::{
+  is_markdown, parse_links, title_from_path, Link, LinkKind, NotesIndex,
+};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use std::io;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+/// Rename of a note with the links to it rewritten, previewed before it's applied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenamePlan {
+  pub node_id: NodeId,
+  pub new_path: PathBuf,
+  /// Notes whose links change, by path
+  pub edits: Vec<NoteEdit>,
+  /// Notes revision the plan was made for
+  revision: u64,
+}
+
+/// Changed lines of a note
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteEdit {
+  pub node_id: NodeId,
+  /// Index of the line, the line before and after the change
+  pub lines: Vec<(usize, String, String)>,
+  content: String,
+}
+
+/// Plan moving the note to `new_path`, relative to the notes directory.
+/// Markdown notes keep the `.md` extension if it's left out. Wikilinks by file name or path and
+/// Markdown links to the note are rewritten, as are the note's own Markdown links if it changes folder.
+/// Links by title or alias stay as they are.
+pub fn plan_rename(notes: &NotesIndex, node_id: NodeId, new_path: &str) -> io::Result<RenamePlan> {
+  let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_owned());
+  let node = notes.graph.get_node(node_id);
+  if node.kind != NodeKind::Note {
+    return Err(invalid("only notes and attachments can be renamed"));
+  }
+  let old_path = &node.note.path;
+  let new_path = PathBuf::from(new_path.trim());
+  if new_path.as_os_str().is_empty() || new_path.file_name().is_none() {
+    return Err(invalid("the new path is empty"));
+  }
+  if !new_path
+    .components()
+    .all(|component| matches!(component, Component::Normal(_)))
+  {
+    return Err(invalid(
+      "the new path has to be inside the notes directory, without `..`",
+    ));
+  }
+  let new_path = if is_markdown(old_path) && new_path.extension().is_none() {
+    new_path.with_extension("md")
+  } else {
+    new_path
+  };
+  if is_markdown(old_path) != is_markdown(&new_path) {
+    return Err(invalid(
+      "the note can't change between Markdown and other files",
+    ));
+  }
+  if &new_path == old_path {
+    return Err(invalid("the note already has this path"));
+  }
+  if notes.node_by_path(&new_path).is_some() {
+    return Err(io::Error::new(
+      io::ErrorKind::AlreadyExists,
+      format!("{} already exists", new_path.display()),
+    ));
+  }
+
+  let new_title = title_from_path(&new_path);
+  // Wikilinks use the path instead of the file name if another note has the same name
+  let name_taken = notes.graph.iter_nodes().any(|(other, node)| {
+    other != node_id
+      && node.kind == NodeKind::Note
+      && node
+        .note
+        .names()
+        .iter()
+        .any(|name| notes.names_match(name, &new_title))
+  });
+  let renamed = Renamed {
+    notes,
+    old_path,
+    new_path: &new_path,
+    new_title: &new_title,
+    name_taken,
+  };
+
+  let changes_folder = new_path.parent() != old_path.parent();
+  let mut sources = notes
+    .graph
+    .iter_nodes()
+    .filter(|(_, node)| node.kind == NodeKind::Note && node.note.is_markdown())
+    .map(|(source, node)| (&node.note.path, source))
+    .collect::<Vec<_>>();
+  sources.sort();
+  let mut edits = vec![];
+  for (_, source) in sources {
+    let content = &notes.graph.get_node(source).note.content;
+    let source_dir = if source == node_id {
+      new_path.parent()
+    } else {
+      notes.graph.get_node(source).note.path.parent()
+    };
+    let source_dir = source_dir.unwrap_or_else(|| Path::new(""));
+    let mut replacements = vec![];
+    for link in parse_links(content) {
+      let Some(target) = notes.resolve(source, &link) else {
+        continue;
+      };
+      let Some(line) = content.split('\n').nth(link.line) else {
+        continue;
+      };
+      let new_target = if target == node_id {
+        renamed.link_target(&link, line, source_dir)
+      } else if source == node_id && changes_folder && link.kind == LinkKind::Markdown {
+        let target_path = &notes.graph.get_node(target).note.path;
+        Some(markdown_target(line, &link, source_dir, target_path))
+      } else {
+        None
+      };
+      let written = &line[link.target_range.clone()];
+      if let Some(new_target) = new_target.filter(|new_target| new_target != written) {
+        replacements.push((link.line, link.target_range, new_target));
+      }
+    }
+    if let Some(edit) = edit_note(source, content, replacements) {
+      edits.push(edit);
+    }
+  }
+  Ok(RenamePlan {
+    node_id,
+    new_path,
+    edits,
+    revision: notes.revision(),
+  })
+}
+
+/// Apply the rename, if the notes haven't changed since it was planned
+pub fn rename_note(notes: &mut NotesIndex, plan: RenamePlan) -> io::Result<()> {
+  if plan.revision != notes.revision() {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      "notes changed since the rename was planned",
+    ));
+  }
+  let contents = plan
+    .edits
+    .into_iter()
+    .map(|edit| (edit.node_id, edit.content))
+    .collect();
+  notes.move_note(plan.node_id, plan.new_path, contents)
+}
+
+/// The note being renamed
+struct Renamed<'a> {
+  notes: &'a NotesIndex,
+  old_path: &'a Path,
+  new_path: &'a Path,
+  new_title: &'a str,
+  /// Whether another note has the new title as it's name
+  name_taken: bool,
+}
+
+impl Renamed<'_> {
+  /// Target the link to the note is written with after the rename, `None` if it stays as it is
+  fn link_target(&self, link: &Link, line: &str, source_dir: &Path) -> Option<String> {
+    match link.kind {
+      LinkKind::Wiki => self.wikilink_target(&line[link.target_range.clone()]),
+      LinkKind::Markdown => Some(markdown_target(line, link, source_dir, self.new_path)),
+    }
+  }
+
+  fn wikilink_target(&self, written: &str) -> Option<String> {
+    let (name, extension) = match written.strip_suffix(".md") {
+      Some(name) if is_markdown(self.old_path) => (name, ".md"),
+      _ => (written, ""),
+    };
+    let by_path = name.contains('/');
+    if !by_path
+      && !self
+        .notes
+        .names_match(name, &title_from_path(self.old_path))
+    {
+      // Links by title or alias still match
+      return None;
+    }
+    let target = if by_path || self.name_taken {
+      slash_path(self.new_path)
+    } else {
+      self.new_title.to_owned()
+    };
+    if !is_markdown(self.new_path) {
+      return Some(target);
+    }
+    let name = target.strip_suffix(".md").unwrap_or(&target);
+    Some(format!("{name}{extension}"))
+  }
+}
+
+/// Markdown link target from the source folder to the path, written like the old one:
+/// with `%20` for spaces unless it's in `<>`, and without `.md` if that was left out
+fn markdown_target(line: &str, link: &Link, source_dir: &Path, path: &Path) -> String {
+  let written = &line[link.target_range.clone()];
+  let mut target = slash_path(&relative_path(source_dir, path));
+  if is_markdown(path) && !is_markdown(Path::new(&link.target)) {
+    target.truncate(target.len() - ".md".len());
+  }
+  let in_brackets = line[..link.target_range.start].ends_with('<');
+  if !in_brackets && (written.contains("%20") || target.contains(' ')) {
+    target = target.replace(' ', "%20");
+  }
+  target
+}
+
+/// Path of the file relative to the folder, with `..` to go up
+fn relative_path(dir: &Path, path: &Path) -> PathBuf {
+  let shared = dir
+    .components()
+    .zip(path.components())
+    .take_while(|(a, b)| a == b)
+    .count();
+  let ups = dir.components().count() - shared;
+  std::iter::repeat(Component::ParentDir.as_os_str())
+    .take(ups)
+    .chain(path.components().skip(shared).map(|c| c.as_os_str()))
+    .collect()
+}
+
+/// Replace the byte ranges of the lines, `None` if nothing changes
+fn edit_note(
+  node_id: NodeId,
+  content: &str,
+  mut replacements: Vec<(usize, Range<usize>, String)>,
+) -> Option<NoteEdit> {
+  if replacements.is_empty() {
+    return None;
+  }
+  // From the end, so that the ranges before stay valid
+  replacements.sort_by_key(|(line, range, _)| std::cmp::Reverse((*line, range.start)));
+  let mut lines = content.split('\n').map(str::to_owned).collect::<Vec<_>>();
+  let mut changed = vec![];
+  for (index, range, text) in replacements {
+    if !changed.contains(&index) {
+      changed.push(index);
+    }
+    lines[index].replace_range(range, &text);
+  }
+  let old_lines = content.split('\n').collect::<Vec<_>>();
+  changed.sort_unstable();
+  Some(NoteEdit {
+    node_id,
+    lines: changed
+      .into_iter()
+      .map(|index| (index, old_lines[index].to_owned(), lines[index].clone()))
+      .collect(),
+    content: lines.join("\n"),
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::notes::Note;
+
+  #[test]
+  fn links_are_rewritten() {
+    let mut notes = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new(
+          "a.md",
+          "[[Old]] [[old.md|text]] [[Old title]]\n[b](sub/b.md) [o](<dir/Old.md#top>)",
+        ),
+        Note::new(
+          "dir/Old.md",
+          "---\ntitle: Old title\n---\n[a](../a.md) [[a]]",
+        ),
+        Note::new("sub/b.md", "![[dir/Old]] [up](../dir/Old)"),
+      ],
+    );
+    let old = notes.node_by_path(Path::new("dir/Old.md")).unwrap();
+    let error = plan_rename(&notes, old, "../x").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(
+      plan_rename(&notes, old, "a").unwrap_err().kind(),
+      io::ErrorKind::AlreadyExists
+    );
+
+    let plan = plan_rename(&notes, old, "New name").unwrap();
+    assert_eq!(plan.new_path, PathBuf::from("New name.md"));
+    let changed_lines = plan
+      .edits
+      .iter()
+      .flat_map(|edit| edit.lines.iter().map(|(_, _, new)| new.as_str()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      changed_lines,
+      vec![
+        "[[New name]] [[New name.md|text]] [[Old title]]",
+        "[b](sub/b.md) [o](<New name.md#top>)",
+        "[a](a.md) [[a]]",
+        "![[New name]] [up](../New%20name)",
+      ]
+    );
+
+    rename_note(&mut notes, plan).unwrap();
+    let renamed = notes.node_by_path(Path::new("New name.md"));
+    assert_eq!(renamed, Some(old));
+    assert!(notes.node_by_path(Path::new("dir/Old.md")).is_none());
+    let a = notes.node_by_path(Path::new("a.md")).unwrap();
+    assert!(notes.graph.get_node(a).unresolved_links.is_empty());
+    assert_eq!(notes.graph.iter_in_edges(old).count(), 6);
+  }
+
+  #[test]
+  fn case_sensitive_renames() {
+    let mut notes = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "[[foo]] [[Foo]]"),
+        Note::new("foo.md", "---\ntitle: Foo\n---\n"),
+        Note::new("dir/Bar.md", ""),
+      ],
+    );
+    let foo = notes.node_by_path(Path::new("foo.md")).unwrap();
+    let changed_line = |notes: &NotesIndex| {
+      let plan = plan_rename(notes, foo, "sub/bar").unwrap();
+      plan.edits[0].lines[0].2.clone()
+    };
+    // `Bar` is taken ignoring case, and `Foo` is the file name
+    assert_eq!(changed_line(&notes), "[[sub/bar]] [[sub/bar]]");
+    // `Foo` is only the title
+    notes.set_case_sensitive(true);
+    assert_eq!(changed_line(&notes), "[[bar]] [[Foo]]");
+  }
+}
@@ -0,0 +1,130 @@
+use crate::features::note_graph::NodeKind;
+use crate::features::notes::{plan_rename, NotesIndex, RenamePlan};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use egui::*;
+
+const RENAME_DIALOG_WIDTH: f32 = 560.0;
+const PREVIEW_HEIGHT: f32 = 320.0;
+
+/// Rename dialog state, closed when `node_id` is `None`
+#[derive(Default)]
+pub struct RenameDialog {
+  node_id: Option<NodeId>,
+  new_path: String,
+  /// New path and notes revision the `plan` was made for
+  planned_for: Option<(String, u64)>,
+  plan: Option<Result<RenamePlan, String>>,
+}
+
+impl RenameDialog {
+  /// Start renaming the note, with it's current path to edit
+  pub fn open(&mut self, notes: &NotesIndex, node_id: NodeId) {
+    self.node_id = Some(node_id);
+    self.new_path = notes
+      .graph
+      .get_node(node_id)
+      .note
+      .path
+      .to_string_lossy()
+      .into_owned();
+    self.planned_for = None;
+    self.plan = None;
+  }
+
+  /// Show the new path and a preview of the changed links if the dialog is open.
+  /// Returns the rename once it's confirmed.
+  pub fn ui(&mut self, ctx: &Context, notes: &NotesIndex) -> Option<RenamePlan> {
+    let node_id = self.node_id?;
+    // The note may have been removed or turned into a ghost while the dialog was open
+    if !notes.graph.contains_node(node_id) || notes.graph.get_node(node_id).kind != NodeKind::Note {
+      self.node_id = None;
+      return None;
+    }
+    let old_path = &notes.graph.get_node(node_id).note.path;
+    let planned_for = Some((self.new_path.clone(), notes.revision()));
+    if self.planned_for != planned_for {
+      let unchanged = old_path.to_string_lossy() == self.new_path.trim();
+      self.plan = (!unchanged)
+        .then(|| plan_rename(notes, node_id, &self.new_path).map_err(|error| error.to_string()));
+      self.planned_for = planned_for;
+    }
+
+    let mut open = true;
+    let mut confirmed = false;
+    Window::new("Rename note")
+      .open(&mut open)
+      .collapsible(false)
+      .default_width(RENAME_DIALOG_WIDTH)
+      .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+      .show(ctx, |ui| {
+        ui.label(format!("Rename {}", old_path.display()));
+        let response = ui.add(
+          TextEdit::singleline(&mut self.new_path)
+            .hint_text("New path")
+            .desired_width(f32::INFINITY),
+        );
+        let entered = response.lost_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+        match &self.plan {
+          None => (),
+          Some(Err(error)) => {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+          }
+          Some(Ok(plan)) => {
+            preview_ui(ui, notes, plan);
+            confirmed = entered;
+          }
+        }
+        ui.horizontal(|ui| {
+          let can_rename = matches!(self.plan, Some(Ok(_)));
+          confirmed |= ui.add_enabled(can_rename, Button::new("Rename")).clicked();
+          if ui.button("Cancel").clicked() {
+            self.node_id = None;
+          }
+        });
+      });
+    if !open {
+      self.node_id = None;
+    }
+    if !confirmed {
+      return None;
+    }
+    self.node_id = None;
+    self.planned_for = None;
+    self.plan.take()?.ok()
+  }
+}
+
+/// Changed lines of the notes as a diff
+fn preview_ui(ui: &mut Ui, notes: &NotesIndex, plan: &RenamePlan) {
+  if plan.edits.is_empty() {
+    ui.weak("No links to rewrite");
+    return;
+  }
+  let line_count = plan
+    .edits
+    .iter()
+    .map(|edit| edit.lines.len())
+    .sum::<usize>();
+  ui.label(format!(
+    "{line_count} lines in {} notes change",
+    plan.edits.len()
+  ));
+  let (removed, added) = if ui.visuals().dark_mode {
+    (Color32::LIGHT_RED, Color32::LIGHT_GREEN)
+  } else {
+    (Color32::DARK_RED, Color32::DARK_GREEN)
+  };
+  ScrollArea::vertical()
+    .max_height(PREVIEW_HEIGHT)
+    .show(ui, |ui| {
+      for edit in &plan.edits {
+        let path = &notes.graph.get_node(edit.node_id).note.path;
+        ui.strong(path.display().to_string());
+        for (line, old, new) in &edit.lines {
+          ui.monospace(RichText::new(format!("{:>4} - {old}", line + 1)).color(removed));
+          ui.monospace(RichText::new(format!("{:>4} + {new}", line + 1)).color(added));
+        }
+      }
+    });
+}
@@ -0,0 +1,432 @@
+use crate::features::notes::Note;
+use crate::lib::glob::glob_match;
+use crate::lib::owned_graph::NodeId;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+
+/// How much more a term found in the title weighs than one found in the content
+const TITLE_BOOST: f32 = 2.0;
+/// Number of bytes of context shown around the first match in a snippet
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Split text into lowercased words, with their byte ranges in the text
+pub fn tokenize(text: &str) -> impl Iterator<Item = (Range<usize>, String)> + '_ {
+  let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+  let mut chars = text.char_indices().peekable();
+  std::iter::from_fn(move || {
+    let (start, _) = chars.find(|&(_, c)| is_word_char(c))?;
+    let mut end = text.len();
+    while let Some(&(index, c)) = chars.peek() {
+      if !is_word_char(c) {
+        end = index;
+        break;
+      }
+      chars.next();
+    }
+    Some((start..end, text[start..end].to_lowercase()))
+  })
+}
+
+/// One part of a search query, all of them have to match
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Clause {
+  /// Word in the title or content
+  Term(String),
+  /// Word starting with the text, written as `text*`
+  Prefix(String),
+  /// Words next to each other, written in quotes
+  Phrase(Vec<String>),
+  /// `tag:name`, also matches nested tags like `name/child`
+  Tag(String),
+  /// `path:text`, a glob if it has `*` or `?`, otherwise a part of the path
+  Path(String),
+  /// `title:text`, a part of the title
+  Title(String),
+}
+
+/// Parse a query like `rust "graph theory" tag:project path:journal/** title:idea pref*`
+pub fn parse_query(query: &str) -> Vec<Clause> {
+  let mut clauses = vec![];
+  let mut rest = query.trim_start();
+  while !rest.is_empty() {
+    // Operator name, if any
+    let (operator, value_start) = match rest.find(':') {
+      Some(colon) if matches!(&rest[..colon], "tag" | "path" | "title") => {
+        (Some(&rest[..colon]), colon + 1)
+      }
+      _ => (None, 0),
+    };
+    let after_operator = &rest[value_start..];
+    let (value, quoted, consumed) = after_operator.strip_prefix('"').map_or_else(
+      || {
+        let end = after_operator
+          .find(char::is_whitespace)
+          .unwrap_or(after_operator.len());
+        (&after_operator[..end], false, value_start + end)
+      },
+      |quoted| {
+        let end = quoted.find('"').unwrap_or(quoted.len());
+        let consumed = value_start + 1 + (end + 1).min(quoted.len());
+        (&quoted[..end], true, consumed)
+      },
+    );
+    rest = rest[consumed..].trim_start();
+    if value.is_empty() {
+      continue;
+    }
+    match operator {
+      Some("tag") => clauses.push(Clause::Tag(value.trim_start_matches('#').to_lowercase())),
+      Some("path") => clauses.push(Clause::Path(value.to_lowercase())),
+      Some(_) => clauses.push(Clause::Title(value.to_lowercase())),
+      None => {
+        let words = tokenize(value).map(|(_, word)| word).collect::<Vec<_>>();
+        match words.as_slice() {
+          [] => (),
+          [word] if !quoted && value.ends_with('*') => clauses.push(Clause::Prefix(word.clone())),
+          [word] => clauses.push(Clause::Term(word.clone())),
+          _ => clauses.push(Clause::Phrase(words)),
+        }
+      }
+    }
+  }
+  clauses
+}
+
+/// Note found by a search
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+  pub node_id: NodeId,
+  pub score: f32,
+  /// Part of the content around the first match, empty if only the title or operators matched
+  pub snippet: String,
+  /// Byte ranges of the matches in the `snippet`
+  pub highlights: Vec<Range<usize>>,
+}
+
+/// Words of an indexed note
+struct Document {
+  /// Words of the content, in order
+  words: Vec<String>,
+  /// Byte ranges of the `words` in the content
+  spans: Vec<Range<usize>>,
+  title_words: HashSet<String>,
+  title: String,
+  path: String,
+  tags: Vec<String>,
+}
+
+/// Inverted index over titles and contents of the notes
+#[derive(Default)]
+pub struct SearchIndex {
+  /// Word -> notes it's in -> positions of the word in the content
+  postings: BTreeMap<String, HashMap<NodeId, Vec<usize>>>,
+  documents: HashMap<NodeId, Document>,
+}
+
+impl SearchIndex {
+  /// Add the note to the index, replacing what was indexed for it before
+  pub fn index_note(&mut self, node_id: NodeId, note: &Note) {
+    self.remove_note(node_id);
+    let (spans, words): (Vec<_>, Vec<_>) = tokenize(&note.content).unzip();
+    // Aliases count as the title
+    let title_words = std::iter::once(&note.title)
+      .chain(&note.aliases)
+      .flat_map(|name| tokenize(name).map(|(_, word)| word))
+      .collect();
+    for (position, word) in words.iter().enumerate() {
+      self
+        .postings
+        .entry(word.clone())
+        .or_default()
+        .entry(node_id)
+        .or_default()
+        .push(position);
+    }
+    self.documents.insert(
+      node_id,
+      Document {
+        words,
+        spans,
+        title_words,
+        title: note.title.to_lowercase(),
+        path: note.path.to_string_lossy().to_lowercase(),
+        tags: note.tags.iter().map(|tag| tag.to_lowercase()).collect(),
+      },
+    );
+  }
+
+  /// Remove the note from the index
+  pub fn remove_note(&mut self, node_id: NodeId) {
+    let Some(document) = self.documents.remove(&node_id) else {
+      return;
+    };
+    for word in &document.words {
+      if let Some(postings) = self.postings.get_mut(word) {
+        postings.remove(&node_id);
+        if postings.is_empty() {
+          self.postings.remove(word);
+        }
+      }
+    }
+  }
+
+  /// Find notes matching all clauses of the query, best matches first.
+  /// `content` gives the content of a note, for snippets.
+  pub fn search<'a>(&self, query: &str, content: impl Fn(NodeId) -> &'a str) -> Vec<SearchResult> {
+    let clauses = parse_query(query);
+    if clauses.is_empty() {
+      return vec![];
+    }
+
+    // Score and matched word positions of every candidate note
+    let mut candidates: Option<HashMap<NodeId, (f32, Vec<usize>)>> = None;
+    for clause in &clauses {
+      let Some(matches) = self.text_matches(clause) else {
+        continue;
+      };
+      candidates = Some(match candidates {
+        None => matches,
+        Some(candidates) => candidates
+          .into_iter()
+          .filter_map(|(node_id, (score, mut positions))| {
+            let (clause_score, clause_positions) = matches.get(&node_id)?;
+            positions.extend(clause_positions);
+            Some((node_id, (score + clause_score, positions)))
+          })
+          .collect(),
+      });
+    }
+    let candidates = candidates.unwrap_or_else(|| {
+      self
+        .documents
+        .keys()
+        .map(|&node_id| (node_id, (1.0, vec![])))
+        .collect()
+    });
+
+    let mut results = candidates
+      .into_iter()
+      .filter(|(node_id, _)| {
+        let document = &self.documents[node_id];
+        clauses
+          .iter()
+          .all(|clause| document.matches_operator(clause))
+      })
+      .map(|(node_id, (score, positions))| {
+        let (snippet, highlights) = self.documents[&node_id].snippet(content(node_id), positions);
+        SearchResult {
+          node_id,
+          score,
+          snippet,
+          highlights,
+        }
+      })
+      .collect::<Vec<_>>();
+    results.sort_by(|a, b| {
+      b.score.total_cmp(&a.score).then_with(|| {
+        self.documents[&a.node_id]
+          .title
+          .cmp(&self.documents[&b.node_id].title)
+      })
+    });
+    results
+  }
+
+  /// Notes matching a text clause with their scores and positions of the matched words.
+  /// `None` for operator clauses.
+  fn text_matches(&self, clause: &Clause) -> Option<HashMap<NodeId, (f32, Vec<usize>)>> {
+    let mut matches: HashMap<NodeId, (f32, Vec<usize>)> = HashMap::new();
+    match clause {
+      Clause::Term(word) => self.add_word_matches(word, &mut matches),
+      Clause::Prefix(prefix) => {
+        for word in self
+          .postings
+          .range(prefix.clone()..)
+          .map(|(word, _)| word)
+          .take_while(|word| word.starts_with(prefix.as_str()))
+        {
+          self.add_word_matches(word, &mut matches);
+        }
+        self.add_title_matches(&mut matches, |title_word| {
+          title_word.starts_with(prefix.as_str())
+        });
+      }
+      Clause::Phrase(words) => {
+        let first = self.postings.get(&words[0]);
+        for (&node_id, positions) in first.into_iter().flatten() {
+          let document = &self.documents[&node_id];
+          let starts = positions
+            .iter()
+            .copied()
+            .filter(|&start| {
+              document
+                .words
+                .get(start..start + words.len())
+                .is_some_and(|window| window == words.as_slice())
+            })
+            .collect::<Vec<_>>();
+          if !starts.is_empty() {
+            let score = self.idf(&words[0]) * starts.len() as f32 / document.norm();
+            let positions = starts
+              .iter()
+              .flat_map(|&start| start..start + words.len())
+              .collect();
+            matches.insert(node_id, (score, positions));
+          }
+        }
+        let phrase = words.join(" ");
+        for (&node_id, document) in &self.documents {
+          if document.title.contains(&phrase) {
+            matches.entry(node_id).or_default().0 += TITLE_BOOST;
+          }
+        }
+      }
+      Clause::Tag(_) | Clause::Path(_) | Clause::Title(_) => return None,
+    }
+    Some(matches)
+  }
+
+  /// Add notes having the word in their content or title to `matches`
+  fn add_word_matches(&self, word: &str, matches: &mut HashMap<NodeId, (f32, Vec<usize>)>) {
+    let idf = self.idf(word);
+    for (&node_id, positions) in self.postings.get(word).into_iter().flatten() {
+      let (score, matched) = matches.entry(node_id).or_default();
+      *score += idf * positions.len() as f32 / self.documents[&node_id].norm();
+      matched.extend(positions);
+    }
+    self.add_title_matches(matches, |title_word| title_word == word);
+  }
+
+  /// Boost notes with a title word matching the predicate
+  fn add_title_matches(
+    &self,
+    matches: &mut HashMap<NodeId, (f32, Vec<usize>)>,
+    predicate: impl Fn(&str) -> bool,
+  ) {
+    for (&node_id, document) in &self.documents {
+      if document.title_words.iter().any(|word| predicate(word)) {
+        matches.entry(node_id).or_default().0 += TITLE_BOOST;
+      }
+    }
+  }
+
+  /// Inverse document frequency: rare words weigh more
+  fn idf(&self, word: &str) -> f32 {
+    let document_count = self.documents.len() as f32;
+    let with_word = self.postings.get(word).map_or(0, HashMap::len) as f32;
+    (document_count / (1.0 + with_word)).ln_1p()
+  }
+}
+
+impl Document {
+  /// Matches in long notes weigh less
+  fn norm(&self) -> f32 {
+    (self.words.len().max(1) as f32).sqrt()
+  }
+
+  /// Whether the note passes an operator clause, text clauses always pass
+  fn matches_operator(&self, clause: &Clause) -> bool {
+    match clause {
+      Clause::Tag(tag) => self.tags.iter().any(|own| {
+        own == tag
+          || own
+            .strip_prefix(tag.as_str())
+            .is_some_and(|rest| rest.starts_with('/'))
+      }),
+      Clause::Path(path) if path.contains(['*', '?']) => glob_match(path, &self.path),
+      Clause::Path(path) => self.path.contains(path.as_str()),
+      Clause::Title(title) => self.title.contains(title.as_str()),
+      Clause::Term(_) | Clause::Prefix(_) | Clause::Phrase(_) => true,
+    }
+  }
+
+  /// Part of the `content` around the first matched word, with ranges of the matches in it
+  fn snippet(&self, content: &str, mut positions: Vec<usize>) -> (String, Vec<Range<usize>>) {
+    positions.sort_unstable();
+    positions.dedup();
+    let Some(first) = positions
+      .first()
+      .and_then(|&position| self.spans.get(position))
+    else {
+      return (String::new(), vec![]);
+    };
+    let mut start = first.start.saturating_sub(SNIPPET_CONTEXT);
+    while !content.is_char_boundary(start) {
+      start -= 1;
+    }
+    let mut end = (first.end + SNIPPET_CONTEXT).min(content.len());
+    while !content.is_char_boundary(end) {
+      end += 1;
+    }
+    let highlights = positions
+      .iter()
+      .filter_map(|&position| self.spans.get(position))
+      .filter(|span| span.start >= start && span.end <= end)
+      .map(|span| span.start - start..span.end - start)
+      .collect();
+    (content[start..end].replace('\n', " "), highlights)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::notes::NotesIndex;
+  use crate::lib::graph::Graph;
+  use std::path::Path;
+
+  #[test]
+  fn query_parsing() {
+    assert_eq!(
+      parse_query(r#"rust "Graph theory" tag:#project path:journal/** title:"to do" fo*"#),
+      vec![
+        Clause::Term("rust".to_owned()),
+        Clause::Phrase(vec!["graph".to_owned(), "theory".to_owned()]),
+        Clause::Tag("project".to_owned()),
+        Clause::Path("journal/**".to_owned()),
+        Clause::Title("to do".to_owned()),
+        Clause::Prefix("fo".to_owned()),
+      ]
+    );
+  }
+
+  #[test]
+  fn search_notes() {
+    let notes = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("Rust.md", "Rust is a language. #programming"),
+        Note::new(
+          "journal/day.md",
+          "Wrote some rust code and read about graph theory.",
+        ),
+        Note::new("Graphs.md", "Theory of graphs. #math"),
+      ],
+    );
+    let paths = |query: &str| {
+      notes
+        .search(query)
+        .into_iter()
+        .map(|result| {
+          let note = &notes.graph.get_node(result.node_id).note;
+          note.path.to_string_lossy().into_owned()
+        })
+        .collect::<Vec<_>>()
+    };
+    // Title matches rank higher
+    assert_eq!(paths("rust"), vec!["Rust.md", "journal/day.md"]);
+    assert_eq!(paths("\"graph theory\""), vec!["journal/day.md"]);
+    assert_eq!(paths("graph*"), vec!["Graphs.md", "journal/day.md"]);
+    assert_eq!(paths("rust path:journal/**"), vec!["journal/day.md"]);
+    assert_eq!(paths("tag:math"), vec!["Graphs.md"]);
+    assert_eq!(paths("title:gra"), vec!["Graphs.md"]);
+
+    let day = notes.node_by_path(Path::new("journal/day.md")).unwrap();
+    let result = notes
+      .search("theory")
+      .into_iter()
+      .find(|result| result.node_id == day)
+      .unwrap();
+    assert_eq!(&result.snippet[result.highlights[0].clone()], "theory");
+  }
+}
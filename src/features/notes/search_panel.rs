@@ -0,0 +1,84 @@
+use crate::features::notes::{NotesIndex, SearchResult};
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use egui::text::LayoutJob;
+use egui::*;
+
+/// Maximum number of results shown
+const MAX_RESULTS: usize = 100;
+
+/// Search panel state
+#[derive(Default)]
+pub struct SearchPanel {
+  query: String,
+  /// Query and notes revision the current `results` were found for
+  searched: Option<(String, u64)>,
+  results: Vec<SearchResult>,
+}
+
+impl SearchPanel {
+  /// Search for the `query`, e.g. when a tag is clicked
+  pub fn set_query(&mut self, query: String) {
+    self.query = query;
+  }
+
+  /// Show the search field and the results.
+  /// Returns the note that was clicked, if any.
+  pub fn ui(&mut self, ui: &mut Ui, notes: &NotesIndex) -> Option<NodeId> {
+    ui.add(
+      TextEdit::singleline(&mut self.query)
+        .hint_text("\"exact phrase\" pref* tag: path: title:")
+        .desired_width(f32::INFINITY),
+    );
+
+    let searched = Some((self.query.clone(), notes.revision()));
+    if self.searched != searched {
+      self.results = notes.search(&self.query);
+      self.searched = searched;
+    }
+    if self.query.trim().is_empty() {
+      return None;
+    }
+
+    ui.weak(format!("{} results", self.results.len()));
+    let mut clicked = None;
+    ScrollArea::vertical().show(ui, |ui| {
+      for result in self.results.iter().take(MAX_RESULTS) {
+        let title = &notes.graph.get_node(result.node_id).note.title;
+        if ui.link(title).clicked() {
+          clicked = Some(result.node_id);
+        }
+        if !result.snippet.is_empty() {
+          ui.label(highlighted(ui, result));
+        }
+        ui.separator();
+      }
+    });
+    clicked
+  }
+}
+
+/// Snippet of the result with the matches emphasized
+fn highlighted(ui: &Ui, result: &SearchResult) -> LayoutJob {
+  let normal = TextFormat {
+    color: ui.visuals().weak_text_color(),
+    ..Default::default()
+  };
+  let highlight = TextFormat {
+    color: ui.visuals().strong_text_color(),
+    background: ui.visuals().selection.bg_fill,
+    ..Default::default()
+  };
+  let mut job = LayoutJob::default();
+  let mut position = 0;
+  for range in &result.highlights {
+    if range.start < position {
+      continue;
+    }
+    job.append(&result.snippet[position..range.start], 0.0, normal.clone());
+    job.append(&result.snippet[range.clone()], 0.0, highlight.clone());
+    position = range.end;
+  }
+  job.append(&result.snippet[position..], 0.0, normal);
+  job
+}
@@ -0,0 +1,119 @@
+use crate::features::note_graph::NodeKind;
+use crate::features::notes::NotesIndex;
+use crate::lib::graph::Graph;
+use egui::collapsing_header::CollapsingState;
+use egui::*;
+use std::collections::{BTreeMap, HashSet};
+
+const TAGS_PANEL_HEIGHT: f32 = 240.0;
+
+/// Tag with the tags nested in it, e.g. `area/sub` in `area`
+#[derive(Default, Debug, PartialEq, Eq)]
+struct TagTree {
+  /// Number of notes with the tag or a tag nested in it
+  count: usize,
+  children: BTreeMap<String, TagTree>,
+}
+
+/// Tags panel state
+#[derive(Default)]
+pub struct TagsPanel {
+  /// Notes revision the `tree` was built for
+  built_for: Option<u64>,
+  tree: TagTree,
+}
+
+impl TagsPanel {
+  /// Show the tags of the notes as a hierarchy, with the number of notes using them.
+  /// Returns the tag that was clicked, if any.
+  pub fn ui(&mut self, ui: &mut Ui, notes: &NotesIndex) -> Option<String> {
+    if self.built_for != Some(notes.revision()) {
+      self.tree = tag_tree(notes);
+      self.built_for = Some(notes.revision());
+    }
+    if self.tree.children.is_empty() {
+      ui.weak("No tags");
+      return None;
+    }
+    let mut clicked = None;
+    ScrollArea::vertical()
+      .id_source("tags_panel")
+      .max_height(TAGS_PANEL_HEIGHT)
+      .show(ui, |ui| tags_ui(ui, &self.tree, "", &mut clicked));
+    clicked
+  }
+}
+
+fn tag_tree(notes: &NotesIndex) -> TagTree {
+  let mut tree = TagTree::default();
+  for (_, node) in notes.graph.iter_nodes() {
+    if node.kind != NodeKind::Note {
+      continue;
+    }
+    // Every tag and the tags it's nested in count once per note
+    let mut tags = HashSet::new();
+    for tag in &node.note.tags {
+      let parts = tag
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+      tags.extend((1..=parts.len()).map(|length| parts[..length].to_vec()));
+    }
+    for parts in tags {
+      let subtree = parts.into_iter().fold(&mut tree, |subtree, part| {
+        subtree.children.entry(part.to_owned()).or_default()
+      });
+      subtree.count += 1;
+    }
+  }
+  tree
+}
+
+fn tags_ui(ui: &mut Ui, tree: &TagTree, parent: &str, clicked: &mut Option<String>) {
+  for (name, subtree) in &tree.children {
+    let tag = if parent.is_empty() {
+      name.clone()
+    } else {
+      format!("{parent}/{name}")
+    };
+    let label = format!("#{name} ({})", subtree.count);
+    if subtree.children.is_empty() {
+      if ui.link(label).clicked() {
+        *clicked = Some(tag);
+      }
+      continue;
+    }
+    CollapsingState::load_with_default_open(ui.ctx(), ui.make_persistent_id(&tag), false)
+      .show_header(ui, |ui| {
+        if ui.link(label).clicked() {
+          *clicked = Some(tag.clone());
+        }
+      })
+      .body(|ui| tags_ui(ui, subtree, &tag, clicked));
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::features::notes::Note;
+
+  #[test]
+  fn nested_tags_are_counted() {
+    let notes = NotesIndex::from_notes(
+      None,
+      vec![
+        Note::new("a.md", "#area/sub #area"),
+        Note::new("b.md", "---\ntags: [area/other]\n---\n"),
+        Note::new("c.md", "#misc\n```c\n#include <stdio.h>\n```"),
+      ],
+    );
+    let tree = tag_tree(&notes);
+    let area = &tree.children["area"];
+    assert_eq!(area.count, 2);
+    assert_eq!(area.children["sub"].count, 1);
+    assert_eq!(area.children["other"].count, 1);
+    assert_eq!(tree.children["misc"].count, 1);
+    assert!(!tree.children.contains_key("include"));
+  }
+}
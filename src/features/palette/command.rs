@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Action of the app that can be bound to keys or run from the command palette
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+  OpenQuickSwitcher,
+  OpenPalette,
+  ToggleLocalGraph,
+  ReloadConfig,
+  FitGraph,
+  StepLayout,
+  ResetLayout,
+  /// Rename the open note, rewriting links to it
+  RenameNote,
+  /// Open the nearest neighbor of the open note to the left
+  FocusLeft,
+  FocusDown,
+  FocusUp,
+  FocusRight,
+}
+
+impl Command {
+  /// Commands listed in the palette, in order
+  pub const PALETTE: &[Self] = &[
+    Self::ToggleLocalGraph,
+    Self::ReloadConfig,
+    Self::FitGraph,
+    Self::StepLayout,
+    Self::ResetLayout,
+    Self::RenameNote,
+  ];
+
+  /// Name shown in the palette
+  pub const fn title(self) -> &'static str {
+    match self {
+      Self::OpenQuickSwitcher => "Open quick switcher",
+      Self::OpenPalette => "Open command palette",
+      Self::ToggleLocalGraph => "Toggle local graph",
+      Self::ReloadConfig => "Reload config",
+      Self::FitGraph => "Fit graph",
+      Self::StepLayout => "Step layout",
+      Self::ResetLayout => "Reset layout",
+      Self::RenameNote => "Rename note",
+      Self::FocusLeft => "Focus note to the left",
+      Self::FocusDown => "Focus note below",
+      Self::FocusUp => "Focus note above",
+      Self::FocusRight => "Focus note to the right",
+    }
+  }
+}
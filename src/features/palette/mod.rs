@@ -0,0 +1,5 @@
+mod command;
+mod palette;
+
+pub use command::*;
+pub use palette::*;
@@ -0,0 +1,155 @@
+use crate::features::note_graph::NodeKind;
+use crate::features::notes::NotesIndex;
+use crate::features::palette::Command;
+use crate::lib::fuzzy::fuzzy_score;
+use crate::lib::graph::Graph;
+use crate::lib::owned_graph::NodeId;
+use egui::*;
+
+/// Maximum number of entries listed
+const MAX_ENTRIES: usize = 50;
+const PALETTE_WIDTH: f32 = 480.0;
+
+/// What the palette lists
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PaletteMode {
+  /// Quick switcher: notes by title and path
+  Notes,
+  Commands,
+}
+
+/// Entry chosen in the palette
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PaletteAction {
+  Open(NodeId),
+  Run(Command),
+}
+
+/// Keyboard-driven palette, closed when `mode` is `None`
+#[derive(Default)]
+pub struct Palette {
+  mode: Option<PaletteMode>,
+  query: String,
+  /// Index of the highlighted entry
+  selected: usize,
+}
+
+impl Palette {
+  /// Open the palette in the mode, or close it if it's already open in that mode
+  pub fn toggle(&mut self, mode: PaletteMode) {
+    if self.mode == Some(mode) {
+      self.mode = None;
+    } else {
+      self.mode = Some(mode);
+      self.query.clear();
+      self.selected = 0;
+    }
+  }
+
+  /// Show the palette if it's open. Returns the chosen entry, if any.
+  pub fn ui(&mut self, ctx: &Context, notes: &NotesIndex) -> Option<PaletteAction> {
+    let mode = self.mode?;
+
+    let entries = match mode {
+      PaletteMode::Notes => note_entries(notes, &self.query),
+      PaletteMode::Commands => command_entries(&self.query),
+    };
+    let (escape, up, down, enter) = ctx.input_mut(|input| {
+      (
+        input.consume_key(Modifiers::NONE, Key::Escape),
+        input.consume_key(Modifiers::NONE, Key::ArrowUp),
+        input.consume_key(Modifiers::NONE, Key::ArrowDown),
+        input.consume_key(Modifiers::NONE, Key::Enter),
+      )
+    });
+    if escape {
+      self.mode = None;
+      return None;
+    }
+    if up {
+      self.selected = self.selected.saturating_sub(1);
+    }
+    if down {
+      self.selected += 1;
+    }
+    self.selected = self.selected.min(entries.len().saturating_sub(1));
+
+    let mut chosen = enter
+      .then(|| entries.get(self.selected).map(|(action, _)| *action))
+      .flatten();
+    Window::new("Palette")
+      .title_bar(false)
+      .resizable(false)
+      .fixed_size([PALETTE_WIDTH, 0.0])
+      .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+      .show(ctx, |ui| {
+        let hint_text = match mode {
+          PaletteMode::Notes => "Open a note",
+          PaletteMode::Commands => "Run a command",
+        };
+        let response = ui.add(
+          TextEdit::singleline(&mut self.query)
+            .hint_text(hint_text)
+            .desired_width(f32::INFINITY),
+        );
+        response.request_focus();
+        if response.changed() {
+          self.selected = 0;
+        }
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+          for (index, (action, label)) in entries.iter().enumerate() {
+            let entry = ui.selectable_label(index == self.selected, label);
+            if index == self.selected && (up || down) {
+              entry.scroll_to_me(None);
+            }
+            if entry.clicked() {
+              chosen = Some(*action);
+            }
+          }
+        });
+      });
+    if chosen.is_some() {
+      self.mode = None;
+    }
+    chosen
+  }
+}
+
+/// Notes whose title, aliases or path match the query, best first
+fn note_entries(notes: &NotesIndex, query: &str) -> Vec<(PaletteAction, String)> {
+  let mut entries = notes
+    .graph
+    .iter_nodes()
+    .filter(|(_, node)| node.kind == NodeKind::Note)
+    .filter_map(|(node_id, node)| {
+      let path = node.note.path.to_string_lossy();
+      let score = node
+        .note
+        .names()
+        .iter()
+        .filter_map(|name| fuzzy_score(query, name))
+        .chain(fuzzy_score(query, &path))
+        .max()?;
+      Some((score, node_id, format!("{}  ({path})", node.note.title)))
+    })
+    .collect::<Vec<_>>();
+  entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.2.cmp(&b.2)));
+  entries
+    .into_iter()
+    .take(MAX_ENTRIES)
+    .map(|(_, node_id, label)| (PaletteAction::Open(node_id), label))
+    .collect()
+}
+
+/// Commands whose title matches the query, best first
+fn command_entries(query: &str) -> Vec<(PaletteAction, String)> {
+  let mut entries = Command::PALETTE
+    .iter()
+    .filter_map(|&command| Some((fuzzy_score(query, command.title())?, command)))
+    .collect::<Vec<_>>();
+  entries.sort_by(|a, b| b.0.cmp(&a.0));
+  entries
+    .into_iter()
+    .map(|(_, command)| (PaletteAction::Run(command), command.title().to_owned()))
+    .collect()
+}
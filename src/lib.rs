@@ -13,16 +13,28 @@
 #![feature(lint_reasons)]
 
 mod basalt;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 mod features;
 mod ui;
 mod lib {
+  pub mod export;
   pub mod fdp;
+  pub mod fuzzy;
+  pub mod glob;
   pub mod graph;
+  pub mod graph_algorithms;
+  pub mod import;
+  pub mod owned_graph;
+  pub mod png;
+  pub mod subgraph;
 
   #[cfg(test)]
   pub mod test;
 }
 pub use basalt::BasaltApp;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cli::run_command;
 
 // ----------------------------------------------------------------------------
 
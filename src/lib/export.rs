@@ -0,0 +1,466 @@
+use crate::lib::fdp::eades_custom::NodePositions;
+use crate::lib::graph::{EdgeIncidents, Graph};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// Node or edge data that can be exported
+pub trait ExportData {
+  /// Attributes of the node or edge by name.
+  /// The `label` attribute of a node is used as it's label where the format has one.
+  fn attributes(&self) -> Vec<(&'static str, String)>;
+}
+
+/// Format graphs are exported to and imported from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+  GraphMl,
+  Gexf,
+  Dot,
+  Json,
+}
+
+impl ExportFormat {
+  pub const ALL: &'static [Self] = &[Self::GraphMl, Self::Gexf, Self::Dot, Self::Json];
+
+  pub const fn extension(self) -> &'static str {
+    match self {
+      Self::GraphMl => "graphml",
+      Self::Gexf => "gexf",
+      Self::Dot => "dot",
+      Self::Json => "json",
+    }
+  }
+
+  /// Format of the file by it's extension
+  pub fn from_path(path: &Path) -> Option<Self> {
+    path.extension()?.to_str()?.parse().ok()
+  }
+}
+
+impl fmt::Display for ExportFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.extension())
+  }
+}
+
+impl FromStr for ExportFormat {
+  type Err = String;
+
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    match name.to_lowercase().as_str() {
+      "graphml" => Ok(Self::GraphMl),
+      "gexf" => Ok(Self::Gexf),
+      "dot" | "gv" => Ok(Self::Dot),
+      "json" => Ok(Self::Json),
+      _ => Err(format!(
+        "unknown format `{name}`, expected graphml, gexf, dot or json"
+      )),
+    }
+  }
+}
+
+/// Graph as written in the JSON format, every format is written from it:
+///
+/// ```json
+/// {
+///   "directed": true,
+///   "nodes": [
+///     { "id": "n0", "x": 12.5, "y": -3.0, "attributes": { "label": "Rust", "path": "Rust.md" } }
+///   ],
+///   "edges": [
+///     { "id": "e0", "source": "n0", "target": "n1", "attributes": { "kind": "link" } }
+///   ]
+/// }
+/// ```
+///
+/// Node positions are left out for nodes that have none, attribute values are strings.
+/// Edge ids are optional when the graph is imported.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct ExportGraph {
+  pub directed: bool,
+  pub nodes: Vec<ExportNode>,
+  pub edges: Vec<ExportEdge>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ExportNode {
+  pub id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub x: Option<f32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub y: Option<f32>,
+  #[serde(default)]
+  pub attributes: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ExportEdge {
+  #[serde(default)]
+  pub id: String,
+  pub source: String,
+  pub target: String,
+  #[serde(default)]
+  pub attributes: BTreeMap<String, String>,
+}
+
+impl ExportGraph {
+  /// Collect the nodes and edges of the graph, nodes are numbered in iteration order
+  pub fn new<G>(graph: &G, node_positions: &NodePositions<G::NodeId>) -> Self
+  where
+    G: Graph,
+    G::NodeId: Hash,
+    G::NodeData: ExportData,
+    G::EdgeData: ExportData,
+  {
+    let ids = graph
+      .iter_nodes()
+      .enumerate()
+      .map(|(index, (node_id, _))| (node_id, format!("n{index}")))
+      .collect::<HashMap<_, _>>();
+    let nodes = graph
+      .iter_nodes()
+      .map(|(node_id, node)| {
+        let pos = node_positions.get(&node_id).map(|node| node.pos);
+        ExportNode {
+          id: ids[&node_id].clone(),
+          x: pos.map(|pos| pos.x),
+          y: pos.map(|pos| pos.y),
+          attributes: attributes(node),
+        }
+      })
+      .collect();
+    let edges = graph
+      .iter_edges()
+      .enumerate()
+      .map(|(index, (edge_id, edge))| {
+        let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+        ExportEdge {
+          id: format!("e{index}"),
+          source: ids[&node_from].clone(),
+          target: ids[&node_to].clone(),
+          attributes: attributes(edge),
+        }
+      })
+      .collect();
+    Self {
+      directed: true,
+      nodes,
+      edges,
+    }
+  }
+
+  pub fn write(&self, format: ExportFormat, writer: &mut impl Write) -> io::Result<()> {
+    match format {
+      ExportFormat::GraphMl => self.write_graphml(writer),
+      ExportFormat::Gexf => self.write_gexf(writer),
+      ExportFormat::Dot => self.write_dot(writer),
+      ExportFormat::Json => {
+        serde_json::to_writer_pretty(&mut *writer, self)?;
+        writeln!(writer)
+      }
+    }
+  }
+
+  /// Names of the node and the edge attributes, in order
+  fn attribute_names(&self) -> (Vec<&str>, Vec<&str>) {
+    (
+      attribute_names(self.nodes.iter().map(|node| &node.attributes)),
+      attribute_names(self.edges.iter().map(|edge| &edge.attributes)),
+    )
+  }
+
+  fn write_graphml(&self, w: &mut impl Write) -> io::Result<()> {
+    let (node_names, edge_names) = self.attribute_names();
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+      w,
+      r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    for (target, names) in [("node", &node_names), ("edge", &edge_names)] {
+      for name in names {
+        let name = xml_escape(name);
+        writeln!(
+          w,
+          r#"  <key id="{target}_{name}" for="{target}" attr.name="{name}" attr.type="string"/>"#
+        )?;
+      }
+    }
+    for axis in ["x", "y"] {
+      writeln!(
+        w,
+        r#"  <key id="{axis}" for="node" attr.name="{axis}" attr.type="float"/>"#
+      )?;
+    }
+    let edge_default = if self.directed {
+      "directed"
+    } else {
+      "undirected"
+    };
+    writeln!(w, r#"  <graph edgedefault="{edge_default}">"#)?;
+    for node in &self.nodes {
+      writeln!(w, r#"    <node id="{}">"#, xml_escape(&node.id))?;
+      for (name, value) in &node.attributes {
+        writeln!(
+          w,
+          r#"      <data key="node_{}">{}</data>"#,
+          xml_escape(name),
+          xml_escape(value)
+        )?;
+      }
+      for (axis, value) in [("x", node.x), ("y", node.y)] {
+        if let Some(value) = value {
+          writeln!(w, r#"      <data key="{axis}">{value}</data>"#)?;
+        }
+      }
+      writeln!(w, "    </node>")?;
+    }
+    for edge in &self.edges {
+      writeln!(
+        w,
+        r#"    <edge id="{}" source="{}" target="{}">"#,
+        xml_escape(&edge.id),
+        xml_escape(&edge.source),
+        xml_escape(&edge.target)
+      )?;
+      for (name, value) in &edge.attributes {
+        writeln!(
+          w,
+          r#"      <data key="edge_{}">{}</data>"#,
+          xml_escape(name),
+          xml_escape(value)
+        )?;
+      }
+      writeln!(w, "    </edge>")?;
+    }
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+  }
+
+  fn write_gexf(&self, w: &mut impl Write) -> io::Result<()> {
+    let (node_names, edge_names) = self.attribute_names();
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+      w,
+      r#"<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">"#
+    )?;
+    let edge_type = if self.directed {
+      "directed"
+    } else {
+      "undirected"
+    };
+    writeln!(w, r#"  <graph defaultedgetype="{edge_type}">"#)?;
+    for (class, names) in [("node", &node_names), ("edge", &edge_names)] {
+      writeln!(w, r#"    <attributes class="{class}">"#)?;
+      for name in names {
+        let name = xml_escape(name);
+        writeln!(
+          w,
+          r#"      <attribute id="{name}" title="{name}" type="string"/>"#
+        )?;
+      }
+      writeln!(w, "    </attributes>")?;
+    }
+    writeln!(w, "    <nodes>")?;
+    for node in &self.nodes {
+      let label = node
+        .attributes
+        .get("label")
+        .map(|label| format!(r#" label="{}""#, xml_escape(label)))
+        .unwrap_or_default();
+      writeln!(w, r#"      <node id="{}"{label}>"#, xml_escape(&node.id))?;
+      write_gexf_attributes(w, &node.attributes)?;
+      if let (Some(x), Some(y)) = (node.x, node.y) {
+        writeln!(w, r#"        <viz:position x="{x}" y="{y}" z="0"/>"#)?;
+      }
+      writeln!(w, "      </node>")?;
+    }
+    writeln!(w, "    </nodes>")?;
+    writeln!(w, "    <edges>")?;
+    for edge in &self.edges {
+      writeln!(
+        w,
+        r#"      <edge id="{}" source="{}" target="{}">"#,
+        xml_escape(&edge.id),
+        xml_escape(&edge.source),
+        xml_escape(&edge.target)
+      )?;
+      write_gexf_attributes(w, &edge.attributes)?;
+      writeln!(w, "      </edge>")?;
+    }
+    writeln!(w, "    </edges>")?;
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</gexf>")
+  }
+
+  fn write_dot(&self, w: &mut impl Write) -> io::Result<()> {
+    let (kind, arrow) = if self.directed {
+      ("digraph", "->")
+    } else {
+      ("graph", "--")
+    };
+    writeln!(w, "{kind} {{")?;
+    for node in &self.nodes {
+      let mut attributes = node
+        .attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect::<Vec<_>>();
+      if let (Some(x), Some(y)) = (node.x, node.y) {
+        // Graphviz `y` points up, pinned with `!`
+        attributes.push(("pos", format!("{x},{}!", -y)));
+      }
+      writeln!(w, "  {}{};", dot_id(&node.id), dot_attributes(&attributes))?;
+    }
+    for edge in &self.edges {
+      let attributes = edge
+        .attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect::<Vec<_>>();
+      writeln!(
+        w,
+        "  {} {arrow} {}{};",
+        dot_id(&edge.source),
+        dot_id(&edge.target),
+        dot_attributes(&attributes)
+      )?;
+    }
+    writeln!(w, "}}")
+  }
+}
+
+/// Sorted names of the attributes, without duplicates
+fn attribute_names<'a>(
+  attributes: impl Iterator<Item = &'a BTreeMap<String, String>>,
+) -> Vec<&'a str> {
+  let mut names = attributes
+    .flat_map(BTreeMap::keys)
+    .map(String::as_str)
+    .collect::<Vec<_>>();
+  names.sort_unstable();
+  names.dedup();
+  names
+}
+
+fn attributes(data: &impl ExportData) -> BTreeMap<String, String> {
+  data
+    .attributes()
+    .into_iter()
+    .map(|(name, value)| (name.to_owned(), value))
+    .collect()
+}
+
+fn write_gexf_attributes(
+  w: &mut impl Write,
+  attributes: &BTreeMap<String, String>,
+) -> io::Result<()> {
+  if attributes.is_empty() {
+    return Ok(());
+  }
+  writeln!(w, "        <attvalues>")?;
+  for (name, value) in attributes {
+    writeln!(
+      w,
+      r#"          <attvalue for="{}" value="{}"/>"#,
+      xml_escape(name),
+      xml_escape(value)
+    )?;
+  }
+  writeln!(w, "        </attvalues>")
+}
+
+/// Text escaped for XML text and attribute values
+pub fn xml_escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+/// Quoted Graphviz id
+fn dot_id(text: &str) -> String {
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// ` [name="value", ...]`, or nothing without attributes
+fn dot_attributes(attributes: &[(&str, String)]) -> String {
+  if attributes.is_empty() {
+    return String::new();
+  }
+  let attributes = attributes
+    .iter()
+    .map(|(name, value)| format!("{}={}", dot_id(name), dot_id(value)))
+    .collect::<Vec<_>>();
+  format!(" [{}]", attributes.join(", "))
+}
+
+/// Write the graph with it's node positions in the format
+pub fn export_graph<G>(
+  graph: &G,
+  node_positions: &NodePositions<G::NodeId>,
+  format: ExportFormat,
+  writer: &mut impl Write,
+) -> io::Result<()>
+where
+  G: Graph,
+  G::NodeId: Hash,
+  G::NodeData: ExportData,
+  G::EdgeData: ExportData,
+{
+  ExportGraph::new(graph, node_positions).write(format, writer)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::lib::fdp::eades_custom::NodeFdpData;
+  use crate::lib::owned_graph::OwnedGraph;
+  use egui::{vec2, Vec2};
+
+  struct Label(&'static str);
+
+  impl ExportData for Label {
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+      vec![("label", self.0.to_owned())]
+    }
+  }
+
+  #[test]
+  fn export_formats() {
+    let mut graph = OwnedGraph::default();
+    let a = graph.insert_node(Label("Fish & \"Chips\""));
+    let b = graph.insert_node(Label("B"));
+    graph.insert_edge(a, b, Label("link"));
+    let node_positions = NodePositions(HashMap::from([(
+      a,
+      NodeFdpData {
+        pos: vec2(1.0, 2.0),
+        force: Vec2::ZERO,
+      },
+    )]));
+    let export = |format| {
+      let mut output = vec![];
+      export_graph(&graph, &node_positions, format, &mut output).unwrap();
+      String::from_utf8(output).unwrap()
+    };
+
+    let graphml = export(ExportFormat::GraphMl);
+    assert!(graphml.contains(r#"<data key="node_label">Fish &amp; &quot;Chips&quot;</data>"#));
+    assert!(graphml.contains(r#"<edge id="e0" source="n0" target="n1">"#));
+    let gexf = export(ExportFormat::Gexf);
+    assert!(gexf.contains(r#"<viz:position x="1" y="2" z="0"/>"#));
+    let dot = export(ExportFormat::Dot);
+    assert!(dot.contains(r#""n0" ["label"="Fish & \"Chips\"", "pos"="1,-2!"];"#));
+    assert!(dot.contains(r#""n0" -> "n1" ["label"="link"];"#));
+
+    let json = serde_json::from_str::<ExportGraph>(&export(ExportFormat::Json)).unwrap();
+    assert_eq!(json, ExportGraph::new(&graph, &node_positions));
+    assert_eq!((json.nodes[0].x, json.nodes[1].x), (Some(1.0), None));
+  }
+}
@@ -35,12 +35,7 @@ pub const GRAVITY_CONSTANT: f32 = 0.1;
 pub const FORCE_CONSTANT: f32 = 1000.0;
 pub const IDEAL_LENGTH: f32 = 25.0;
 
-pub fn apply_forces<NodeId, EdgeId>(
-  graph: &impl for<'a> Graph<'a, NodeId = NodeId, EdgeId = EdgeId>,
-  node_positions: &mut NodePositions<NodeId>,
-) where
-  NodeId: Hash + Eq,
-{
+pub fn apply_forces<G: Graph>(graph: &G, node_positions: &mut NodePositions<G::NodeId>) {
   // apply force towards center
   for (node_id, ..) in graph.iter_nodes() {
     node_positions.get_mut(&node_id).unwrap().force =
@@ -75,3 +70,16 @@ pub fn apply_forces<NodeId, EdgeId>(
     node_positions.get_mut(&node2_id).unwrap().force += dis * Vec2::splat(diff);
   }
 }
+
+/// Apply forces and move the nodes accordingly.
+/// Returns the longest distance a node moved.
+pub fn step<G: Graph>(graph: &G, node_positions: &mut NodePositions<G::NodeId>) -> f32 {
+  apply_forces(graph, node_positions);
+  let mut movement: f32 = 0.0;
+  for (node_id, ..) in graph.iter_nodes() {
+    let NodeFdpData { pos, force } = node_positions.get_mut(&node_id).unwrap();
+    *pos += *force;
+    movement = movement.max(force.length());
+  }
+  movement
+}
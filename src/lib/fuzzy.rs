@@ -0,0 +1,65 @@
+/// Bonus for a pattern character matched right after the previous one
+const CONSECUTIVE_BONUS: i32 = 5;
+/// Bonus for a pattern character matched at the start of a word
+const WORD_START_BONUS: i32 = 8;
+/// Penalty for every text character not matched, so tighter matches win
+const UNMATCHED_PENALTY: i32 = 1;
+/// Maximum unmatched penalty, so long texts are not punished too hard
+const MAX_UNMATCHED_PENALTY: i32 = 10;
+
+/// Score how well the `pattern` matches the `text`: all pattern characters have to appear
+/// in the text in order (case-insensitive). Consecutive matches and matches at word starts
+/// score higher. `None` if the pattern doesn't match.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+  let mut pattern_chars = pattern
+    .chars()
+    .filter(|c| !c.is_whitespace())
+    .flat_map(char::to_lowercase)
+    .peekable();
+  let mut score = 0;
+  let mut previous: Option<char> = None;
+  let mut previous_matched = false;
+  let mut matched_count = 0;
+  for c in text.chars() {
+    let Some(&wanted) = pattern_chars.peek() else {
+      break;
+    };
+    let matched = c.to_lowercase().eq(std::iter::once(wanted));
+    if matched {
+      pattern_chars.next();
+      score += 1;
+      if previous_matched {
+        score += CONSECUTIVE_BONUS;
+      }
+      let word_start = previous.map_or(true, |previous| {
+        !previous.is_alphanumeric() || (previous.is_lowercase() && c.is_uppercase())
+      });
+      if word_start {
+        score += WORD_START_BONUS;
+      }
+      matched_count += 1;
+    }
+    previous_matched = matched;
+    previous = Some(c);
+  }
+  if pattern_chars.peek().is_some() {
+    return None;
+  }
+  let unmatched = (text.chars().count() - matched_count) as i32 * UNMATCHED_PENALTY;
+  Some(score - unmatched.min(MAX_UNMATCHED_PENALTY))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn fuzzy_matching() {
+    assert!(fuzzy_score("gt", "Graph theory").is_some());
+    assert!(fuzzy_score("tg", "Graph theory").is_none());
+    assert_eq!(fuzzy_score("", "abc"), Some(-3));
+    // Word starts and consecutive characters win
+    assert!(fuzzy_score("gt", "Graph theory") > fuzzy_score("gt", "Egui tools"));
+    assert!(fuzzy_score("rust", "Rust") > fuzzy_score("rust", "Running studies"));
+  }
+}
@@ -0,0 +1,51 @@
+/// Match a `/`-separated path against a glob pattern.
+/// Supported syntax:
+/// - `?` matches any single character except `/`
+/// - `*` matches any sequence of characters except `/`
+/// - `**` matches any sequence of characters, including `/`
+/// - `**/` also matches zero directories
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+  let pattern = pattern.chars().collect::<Vec<_>>();
+  let path = path.chars().collect::<Vec<_>>();
+  match_here(&pattern, &path)
+}
+
+fn match_here(pattern: &[char], path: &[char]) -> bool {
+  match pattern {
+    [] => path.is_empty(),
+    ['*', '*', '/', rest @ ..] if match_here(rest, path) => true,
+    ['*', '*', rest @ ..] => (0..=path.len()).any(|i| match_here(rest, &path[i..])),
+    ['*', rest @ ..] => {
+      let segment_len = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+      (0..=segment_len).any(|i| match_here(rest, &path[i..]))
+    }
+    ['?', rest @ ..] => matches!(path, [c, ..] if *c != '/') && match_here(rest, &path[1..]),
+    [p, rest @ ..] => matches!(path, [c, ..] if c == p) && match_here(rest, &path[1..]),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn literal() {
+    assert!(glob_match("index.md", "index.md"));
+    assert!(!glob_match("index.md", "index.md.bak"));
+  }
+
+  #[test]
+  fn star_stays_in_segment() {
+    assert!(glob_match("journal/*.md", "journal/2023-08-21.md"));
+    assert!(!glob_match("journal/*.md", "journal/2023/08-21.md"));
+    assert!(glob_match("?ndex.md", "index.md"));
+  }
+
+  #[test]
+  fn double_star_crosses_segments() {
+    assert!(glob_match("journal/**", "journal/2023/08-21.md"));
+    assert!(glob_match("**/*.png", "attachments/diagram.png"));
+    assert!(glob_match("**/*.png", "diagram.png"));
+    assert!(!glob_match("journal/**", "topics/rust.md"));
+  }
+}
@@ -1,44 +1,83 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
 pub struct EdgeIncidents<T> {
   pub node_from: T,
   pub node_to: T,
 }
 
+/// Direction in which edges are followed when traversing a graph
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+  /// Follow edges from `node_from` to `node_to`
+  Outgoing,
+  /// Follow edges from `node_to` to `node_from`
+  Incoming,
+  /// Follow edges regardless of their direction
+  Both,
+}
+
 /// Represents a genreic graph structure
 /// It has to have an iterator over all nodes and all edges,
 /// as well as getters for nodes and edges.
 /// Iterator should be over pairs of nodes
-pub trait Graph<'a> {
-  type NodeId: 'a;
-  type NodeData: 'a;
+pub trait Graph {
+  type NodeId: Copy + Eq + Hash;
+  type NodeData;
 
-  type EdgeId: 'a;
-  type EdgeData: 'a;
+  type EdgeId: Copy + Eq + Hash;
+  type EdgeData;
 
-  type NodeIter: Iterator<Item = (Self::NodeId, &'a Self::NodeData)> + 'a;
-  type NodeIterMut: Iterator<Item = (Self::NodeId, &'a mut Self::NodeData)> + 'a;
+  type NodeIter<'a>: Iterator<Item = (Self::NodeId, &'a Self::NodeData)>
+  where
+    Self: 'a;
 
-  type EdgeIter: Iterator<Item = (Self::EdgeId, &'a Self::EdgeData)> + 'a;
-  type EdgeIterMut: Iterator<Item = (Self::EdgeId, &'a mut Self::EdgeData)> + 'a;
+  type EdgeIter<'a>: Iterator<Item = (Self::EdgeId, &'a Self::EdgeData)>
+  where
+    Self: 'a;
 
-  fn iter_nodes(&self) -> Self::NodeIter;
-  fn iter_nodes_mut(&mut self) -> Self::NodeIterMut;
+  fn iter_nodes(&self) -> Self::NodeIter<'_>;
 
-  fn iter_edges(&self) -> Self::EdgeIter;
-  fn iter_edges_mut(&mut self) -> Self::EdgeIterMut;
+  fn iter_edges(&self) -> Self::EdgeIter<'_>;
 
   /// Iterate edges incident to the node
-  fn iter_incidents(&self, node_id: Self::NodeId) -> Self::EdgeIter;
-  /// Iterate edges incident to the node (mutably)
-  fn iter_incidents_mut(&mut self, node_id: Self::NodeId) -> Self::EdgeIterMut;
+  fn iter_incidents(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_>;
+
+  /// Iterate edges coming into the node
+  fn iter_in_edges(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_>;
+
+  /// Iterate edges going out of the node
+  fn iter_out_edges(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_>;
 
-  fn get_node(&self, id: Self::NodeId) -> &'a Self::NodeData;
-  fn get_node_mut(&mut self, id: Self::NodeId) -> &'a mut Self::NodeData;
+  fn get_node(&self, id: Self::NodeId) -> &Self::NodeData;
 
-  fn get_edge(&self, id: Self::EdgeId) -> &'a Self::EdgeData;
-  fn get_edge_mut(&mut self, id: Self::EdgeId) -> &'a mut Self::EdgeData;
+  fn get_edge(&self, id: Self::EdgeId) -> &Self::EdgeData;
 
   /// Get incident nodes of an edge
   fn get_edge_incidents(&self, edge_id: Self::EdgeId) -> EdgeIncidents<Self::NodeId>;
+}
+
+/// Graph that can be modified in place.
+/// Views over other graphs (like `Subgraph`) don't implement this.
+pub trait GraphMut: Graph {
+  type NodeIterMut<'a>: Iterator<Item = (Self::NodeId, &'a mut Self::NodeData)>
+  where
+    Self: 'a;
+
+  type EdgeIterMut<'a>: Iterator<Item = (Self::EdgeId, &'a mut Self::EdgeData)>
+  where
+    Self: 'a;
+
+  fn iter_nodes_mut(&mut self) -> Self::NodeIterMut<'_>;
+
+  fn iter_edges_mut(&mut self) -> Self::EdgeIterMut<'_>;
+
+  /// Iterate edges incident to the node (mutably)
+  fn iter_incidents_mut(&mut self, node_id: Self::NodeId) -> Self::EdgeIterMut<'_>;
+
+  fn get_node_mut(&mut self, id: Self::NodeId) -> &mut Self::NodeData;
+
+  fn get_edge_mut(&mut self, id: Self::EdgeId) -> &mut Self::EdgeData;
 
   /// Adds a node to the graph
   fn add_node(&mut self, data: &Self::NodeData) -> Self::NodeId;
@@ -57,3 +96,58 @@ pub trait Graph<'a> {
   /// Removes an edge from the graph, returning it's `EdgeData` if the edge was previously in the graph
   fn remove_edge(&mut self, data: Self::EdgeId) -> Option<Self::EdgeData>;
 }
+
+/// Iterate edges incident to `node_id` that can be followed in the `direction`,
+/// together with the node on the other end
+pub fn adjacent_edges<G: Graph>(
+  graph: &G,
+  node_id: G::NodeId,
+  direction: Direction,
+) -> impl Iterator<Item = (G::EdgeId, G::NodeId)> + '_ {
+  graph
+    .iter_incidents(node_id)
+    .filter_map(move |(edge_id, ..)| {
+      let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+      let neighbor = match direction {
+        Direction::Outgoing if node_from == node_id => node_to,
+        Direction::Incoming if node_to == node_id => node_from,
+        Direction::Both if node_from == node_id => node_to,
+        Direction::Both => node_from,
+        _ => return None,
+      };
+      Some((edge_id, neighbor))
+    })
+}
+
+/// Iterate nodes adjacent to `node_id`, following edges in the `direction`.
+/// Neighbors connected by several edges are yielded several times.
+pub fn neighbors<G: Graph>(
+  graph: &G,
+  node_id: G::NodeId,
+  direction: Direction,
+) -> impl Iterator<Item = G::NodeId> + '_ {
+  adjacent_edges(graph, node_id, direction).map(|(_, neighbor)| neighbor)
+}
+
+/// Collect nodes reachable from `start` in at most `depth` hops,
+/// following edges in the `direction`. Includes `start` itself.
+pub fn neighborhood<G: Graph>(
+  graph: &G,
+  start: G::NodeId,
+  depth: usize,
+  direction: Direction,
+) -> HashSet<G::NodeId> {
+  let mut visited = HashSet::from([start]);
+  let mut queue = VecDeque::from([(start, 0)]);
+  while let Some((node_id, distance)) = queue.pop_front() {
+    if distance >= depth {
+      continue;
+    }
+    for neighbor in neighbors(graph, node_id, direction) {
+      if visited.insert(neighbor) {
+        queue.push_back((neighbor, distance + 1));
+      }
+    }
+  }
+  visited
+}
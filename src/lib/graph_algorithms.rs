@@ -0,0 +1,417 @@
+use crate::lib::graph::{adjacent_edges, neighbors, Direction, EdgeIncidents, Graph};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+pub const PAGE_RANK_DAMPING: f32 = 0.85;
+pub const PAGE_RANK_ITERATIONS: usize = 50;
+pub const PAGE_RANK_TOLERANCE: f32 = 1e-6;
+
+/// Number of edges coming into each node
+pub fn in_degrees<G: Graph>(graph: &G) -> HashMap<G::NodeId, usize> {
+  degrees(graph, false, true)
+}
+
+/// Number of edges going out of each node
+pub fn out_degrees<G: Graph>(graph: &G) -> HashMap<G::NodeId, usize> {
+  degrees(graph, true, false)
+}
+
+/// Number of edges incident to each node, regardless of direction
+pub fn total_degrees<G: Graph>(graph: &G) -> HashMap<G::NodeId, usize> {
+  degrees(graph, true, true)
+}
+
+fn degrees<G: Graph>(graph: &G, count_from: bool, count_to: bool) -> HashMap<G::NodeId, usize> {
+  let mut degrees: HashMap<_, _> = graph
+    .iter_nodes()
+    .map(|(node_id, _)| (node_id, 0))
+    .collect();
+  for (edge_id, ..) in graph.iter_edges() {
+    let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+    if count_from {
+      *degrees.entry(node_from).or_default() += 1;
+    }
+    if count_to {
+      *degrees.entry(node_to).or_default() += 1;
+    }
+  }
+  degrees
+}
+
+/// PageRank of each node. Ranks sum up to 1.
+/// Rank of nodes without outgoing edges is spread evenly over all nodes.
+pub fn page_rank<G: Graph>(graph: &G) -> HashMap<G::NodeId, f32> {
+  let node_count = graph.iter_nodes().count();
+  if node_count == 0 {
+    return HashMap::new();
+  }
+  let out_degrees = out_degrees(graph);
+  let initial = 1.0 / node_count as f32;
+  let mut ranks: HashMap<_, _> = graph
+    .iter_nodes()
+    .map(|(node_id, _)| (node_id, initial))
+    .collect();
+
+  for _ in 0..PAGE_RANK_ITERATIONS {
+    let dangling_rank: f32 = ranks
+      .iter()
+      .filter(|(node_id, _)| out_degrees[node_id] == 0)
+      .map(|(_, rank)| rank)
+      .sum();
+    let base = (1.0 - PAGE_RANK_DAMPING) / node_count as f32
+      + PAGE_RANK_DAMPING * dangling_rank / node_count as f32;
+    let mut next: HashMap<_, _> = ranks.keys().map(|&node_id| (node_id, base)).collect();
+    for (edge_id, ..) in graph.iter_edges() {
+      let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+      *next.get_mut(&node_to).unwrap() +=
+        PAGE_RANK_DAMPING * ranks[&node_from] / out_degrees[&node_from] as f32;
+    }
+    let change: f32 = next
+      .iter()
+      .map(|(node_id, rank)| (rank - ranks[node_id]).abs())
+      .sum();
+    ranks = next;
+    if change < PAGE_RANK_TOLERANCE {
+      break;
+    }
+  }
+  ranks
+}
+
+/// Nodes reachable from `start` in depth-first (pre-)order
+pub fn dfs<G: Graph>(graph: &G, start: G::NodeId, direction: Direction) -> Vec<G::NodeId> {
+  let mut visited = HashSet::new();
+  let mut order = vec![];
+  let mut stack = vec![start];
+  while let Some(node_id) = stack.pop() {
+    if !visited.insert(node_id) {
+      continue;
+    }
+    order.push(node_id);
+    // Reversed, so that neighbors are visited in iteration order
+    let mut next = neighbors(graph, node_id, direction)
+      .filter(|neighbor| !visited.contains(neighbor))
+      .collect::<Vec<_>>();
+    next.reverse();
+    stack.extend(next);
+  }
+  order
+}
+
+/// Path through the graph: `nodes` are visited in order,
+/// `edges[i]` connects `nodes[i]` and `nodes[i + 1]`
+#[derive(Debug, PartialEq, Eq)]
+pub struct GraphPath<NodeId, EdgeId> {
+  pub nodes: Vec<NodeId>,
+  pub edges: Vec<EdgeId>,
+}
+
+/// Shortest (by the number of edges) path from `from` to `to`
+pub fn shortest_path<G: Graph>(
+  graph: &G,
+  from: G::NodeId,
+  to: G::NodeId,
+  direction: Direction,
+) -> Option<GraphPath<G::NodeId, G::EdgeId>> {
+  // Node -> (previous node, edge from previous node)
+  let mut previous = HashMap::from([(from, None)]);
+  let mut queue = VecDeque::from([from]);
+  while let Some(node_id) = queue.pop_front() {
+    if node_id == to {
+      break;
+    }
+    for (edge_id, neighbor) in adjacent_edges(graph, node_id, direction) {
+      if let Entry::Vacant(entry) = previous.entry(neighbor) {
+        entry.insert(Some((node_id, edge_id)));
+        queue.push_back(neighbor);
+      }
+    }
+  }
+
+  let mut path = GraphPath {
+    nodes: vec![to],
+    edges: vec![],
+  };
+  let mut current = to;
+  while let Some((node_id, edge_id)) = *previous.get(&current)? {
+    path.nodes.push(node_id);
+    path.edges.push(edge_id);
+    current = node_id;
+  }
+  path.nodes.reverse();
+  path.edges.reverse();
+  Some(path)
+}
+
+/// Weakly connected components, largest first
+pub fn connected_components<G: Graph>(graph: &G) -> Vec<Vec<G::NodeId>> {
+  let mut visited = HashSet::new();
+  let mut components = vec![];
+  for (node_id, _) in graph.iter_nodes() {
+    if visited.contains(&node_id) {
+      continue;
+    }
+    let component = dfs(graph, node_id, Direction::Both);
+    visited.extend(component.iter().copied());
+    components.push(component);
+  }
+  components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+  components
+}
+
+/// Betweenness centrality of each node (Brandes' algorithm), not normalized.
+/// With `Direction::Both` the graph is treated as undirected.
+pub fn betweenness_centrality<G: Graph>(
+  graph: &G,
+  direction: Direction,
+) -> HashMap<G::NodeId, f32> {
+  let mut centrality: HashMap<_, _> = graph.iter_nodes().map(|(id, _)| (id, 0.0)).collect();
+  for (source, _) in graph.iter_nodes() {
+    let mut stack = vec![];
+    let mut predecessors: HashMap<_, Vec<_>> = HashMap::new();
+    let mut paths_count = HashMap::from([(source, 1.0_f32)]);
+    let mut distance = HashMap::from([(source, 0_usize)]);
+    let mut queue = VecDeque::from([source]);
+    while let Some(node_id) = queue.pop_front() {
+      stack.push(node_id);
+      let node_distance = distance[&node_id];
+      // Each neighbor only once, even if connected by several edges
+      let unique_neighbors = neighbors(graph, node_id, direction).collect::<HashSet<_>>();
+      for neighbor in unique_neighbors {
+        if neighbor == node_id {
+          continue;
+        }
+        if let Entry::Vacant(entry) = distance.entry(neighbor) {
+          entry.insert(node_distance + 1);
+          queue.push_back(neighbor);
+        }
+        if distance[&neighbor] == node_distance + 1 {
+          *paths_count.entry(neighbor).or_default() += paths_count[&node_id];
+          predecessors.entry(neighbor).or_default().push(node_id);
+        }
+      }
+    }
+
+    let mut dependency: HashMap<_, f32> = HashMap::new();
+    while let Some(node_id) = stack.pop() {
+      let node_dependency = dependency.get(&node_id).copied().unwrap_or_default();
+      for &predecessor in predecessors.get(&node_id).into_iter().flatten() {
+        *dependency.entry(predecessor).or_default() +=
+          paths_count[&predecessor] / paths_count[&node_id] * (1.0 + node_dependency);
+      }
+      if node_id != source {
+        *centrality.get_mut(&node_id).unwrap() += node_dependency;
+      }
+    }
+  }
+
+  // Every undirected path was counted from both ends
+  if direction == Direction::Both {
+    for value in centrality.values_mut() {
+      *value /= 2.0;
+    }
+  }
+  centrality
+}
+
+/// Community of each node found with the Louvain method.
+/// Edges are treated as undirected, parallel edges add up their weight.
+/// Communities are numbered from 0, largest first.
+pub fn louvain_communities<G: Graph>(graph: &G) -> HashMap<G::NodeId, usize> {
+  let node_ids = graph.iter_nodes().map(|(id, _)| id).collect::<Vec<_>>();
+  let index_of: HashMap<_, _> = node_ids
+    .iter()
+    .enumerate()
+    .map(|(index, &id)| (id, index))
+    .collect();
+
+  // Symmetric weighted adjacency, self-loops hold twice their weight
+  let mut adjacency = vec![HashMap::<usize, f64>::new(); node_ids.len()];
+  for (edge_id, ..) in graph.iter_edges() {
+    let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+    let (a, b) = (index_of[&node_from], index_of[&node_to]);
+    *adjacency[a].entry(b).or_default() += 1.0;
+    *adjacency[b].entry(a).or_default() += 1.0;
+  }
+
+  // Node of the original graph -> node of the current (aggregated) graph
+  let mut membership = (0..node_ids.len()).collect::<Vec<_>>();
+  loop {
+    let (communities, moved) = louvain_local_moving(&adjacency);
+    if !moved {
+      break;
+    }
+    for community in membership.iter_mut() {
+      *community = communities[*community];
+    }
+    adjacency = louvain_aggregate(&adjacency, &communities);
+  }
+
+  // Renumber: largest community first
+  let mut sizes: HashMap<usize, usize> = HashMap::new();
+  for &community in &membership {
+    *sizes.entry(community).or_default() += 1;
+  }
+  let mut order = sizes.into_iter().collect::<Vec<_>>();
+  order.sort_by_key(|&(community, size)| (std::cmp::Reverse(size), community));
+  let renumbered: HashMap<_, _> = order
+    .into_iter()
+    .enumerate()
+    .map(|(number, (community, _))| (community, number))
+    .collect();
+
+  node_ids
+    .into_iter()
+    .zip(membership)
+    .map(|(id, community)| (id, renumbered[&community]))
+    .collect()
+}
+
+/// First phase of the Louvain method: move nodes to neighboring communities
+/// while it increases modularity. Returns compactly numbered communities of
+/// the nodes and whether anything moved.
+fn louvain_local_moving(adjacency: &[HashMap<usize, f64>]) -> (Vec<usize>, bool) {
+  let degrees = adjacency
+    .iter()
+    .map(|neighbors| neighbors.values().sum::<f64>())
+    .collect::<Vec<_>>();
+  let total_weight: f64 = degrees.iter().sum();
+  let mut communities = (0..adjacency.len()).collect::<Vec<_>>();
+  if total_weight == 0.0 {
+    return (communities, false);
+  }
+  let mut community_degrees = degrees.clone();
+
+  let mut moved = false;
+  let mut improved = true;
+  while improved {
+    improved = false;
+    for node in 0..adjacency.len() {
+      let current = communities[node];
+      community_degrees[current] -= degrees[node];
+
+      // Weight of links from the node into each neighboring community
+      // Ordered, so that ties are broken the same way every time
+      let mut links: BTreeMap<usize, f64> = BTreeMap::from([(current, 0.0)]);
+      for (&neighbor, &weight) in &adjacency[node] {
+        if neighbor != node {
+          *links.entry(communities[neighbor]).or_default() += weight;
+        }
+      }
+
+      let gain = |community: usize| {
+        links[&community] - community_degrees[community] * degrees[node] / total_weight
+      };
+      let mut best = current;
+      let mut best_gain = gain(current);
+      for &community in links.keys() {
+        let community_gain = gain(community);
+        if community_gain > best_gain + f64::EPSILON {
+          best = community;
+          best_gain = community_gain;
+        }
+      }
+
+      community_degrees[best] += degrees[node];
+      if best != current {
+        communities[node] = best;
+        improved = true;
+        moved = true;
+      }
+    }
+  }
+
+  // Number communities compactly
+  let mut numbers = HashMap::new();
+  for community in communities.iter_mut() {
+    let next = numbers.len();
+    *community = *numbers.entry(*community).or_insert(next);
+  }
+  (communities, moved)
+}
+
+/// Second phase of the Louvain method: build a graph of communities
+fn louvain_aggregate(
+  adjacency: &[HashMap<usize, f64>],
+  communities: &[usize],
+) -> Vec<HashMap<usize, f64>> {
+  let community_count = communities.iter().max().map_or(0, |max| max + 1);
+  let mut aggregated = vec![HashMap::new(); community_count];
+  for (node, neighbors) in adjacency.iter().enumerate() {
+    for (&neighbor, &weight) in neighbors {
+      *aggregated[communities[node]]
+        .entry(communities[neighbor])
+        .or_default() += weight;
+    }
+  }
+  aggregated
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::lib::test::TestGraph;
+
+  /// Two triangles `0-1-2` and `3-4-5` joined by the `2->3` edge
+  fn two_triangles() -> TestGraph {
+    TestGraph::new(6, &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)])
+  }
+
+  #[test]
+  fn traversal_follows_direction() {
+    let graph = two_triangles();
+    assert_eq!(dfs(&graph, 3, Direction::Outgoing), vec![3, 4, 5]);
+    assert_eq!(dfs(&graph, 3, Direction::Incoming), vec![3, 5, 4, 2, 1, 0]);
+    assert_eq!(dfs(&graph, 3, Direction::Both).len(), 6);
+    assert_eq!(dfs(&graph, 0, Direction::Outgoing), vec![0, 1, 2, 3, 4, 5]);
+  }
+
+  #[test]
+  fn shortest_path_between_triangles() {
+    let graph = two_triangles();
+    let path = shortest_path(&graph, 1, 4, Direction::Outgoing).unwrap();
+    assert_eq!(path.nodes, vec![1, 2, 3, 4]);
+    assert_eq!(path.edges, vec![1, 6, 3]);
+    assert_eq!(shortest_path(&graph, 4, 1, Direction::Outgoing), None);
+    assert_eq!(
+      shortest_path(&graph, 4, 1, Direction::Both).unwrap().nodes,
+      vec![4, 3, 2, 1]
+    );
+  }
+
+  #[test]
+  fn components() {
+    let graph = TestGraph::new(5, &[(0, 1), (2, 3)]);
+    let components = connected_components(&graph);
+    assert_eq!(components.len(), 3);
+    assert_eq!(components[2], vec![4]);
+  }
+
+  #[test]
+  fn page_rank_sums_to_one() {
+    let graph = TestGraph::new(4, &[(1, 0), (2, 0), (3, 0)]);
+    let ranks = page_rank(&graph);
+    assert!((ranks.values().sum::<f32>() - 1.0).abs() < 1e-4);
+    assert!(ranks[&0] > ranks[&1]);
+  }
+
+  #[test]
+  fn bridge_has_highest_betweenness() {
+    let graph = two_triangles();
+    let centrality = betweenness_centrality(&graph, Direction::Both);
+    assert_eq!(centrality[&2], 6.0);
+    assert_eq!(centrality[&3], 6.0);
+    assert_eq!(centrality[&0], 0.0);
+  }
+
+  #[test]
+  fn louvain_finds_triangles() {
+    let graph = two_triangles();
+    let communities = louvain_communities(&graph);
+    assert_eq!(communities[&0], communities[&1]);
+    assert_eq!(communities[&1], communities[&2]);
+    assert_eq!(communities[&3], communities[&4]);
+    assert_eq!(communities[&4], communities[&5]);
+    assert_ne!(communities[&0], communities[&3]);
+  }
+}
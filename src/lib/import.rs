@@ -0,0 +1,985 @@
+use crate::lib::export::{ExportEdge, ExportFormat, ExportGraph, ExportNode};
+use crate::lib::owned_graph::OwnedGraph;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+
+/// Node or edge data that can be imported
+pub trait ImportData {
+  /// Data of the node or edge with the `id` it has in the imported file, and it's attributes by name
+  fn from_attributes(id: &str, attributes: &BTreeMap<String, String>) -> Self;
+}
+
+/// Read the graph from the file, in the format of it's extension
+pub fn read_graph<N: ImportData, E: ImportData>(path: &Path) -> io::Result<OwnedGraph<N, E>> {
+  let format = ExportFormat::from_path(path).ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!(
+        "can't tell the format of {} by it's extension",
+        path.display()
+      ),
+    )
+  })?;
+  import_graph(&std::fs::read_to_string(path)?, format)
+}
+
+/// Parse the graph in the `format`
+pub fn import_graph<N: ImportData, E: ImportData>(
+  text: &str,
+  format: ExportFormat,
+) -> io::Result<OwnedGraph<N, E>> {
+  Ok(ExportGraph::parse(text, format)?.to_graph())
+}
+
+impl ExportGraph {
+  /// Parse the graph in the `format`. JSON can also be a list of edges:
+  /// `[["a", "b"], { "source": "b", "target": "c", "weight": 2 }]`
+  pub fn parse(text: &str, format: ExportFormat) -> io::Result<Self> {
+    let mut graph = match format {
+      ExportFormat::GraphMl => parse_graphml(&parse_xml(text)?),
+      ExportFormat::Gexf => parse_gexf(&parse_xml(text)?),
+      ExportFormat::Dot => DotParser::new(text)?.parse()?,
+      ExportFormat::Json => parse_json(text)?,
+    };
+    for (index, edge) in graph.edges.iter_mut().enumerate() {
+      if edge.id.is_empty() {
+        edge.id = format!("e{index}");
+      }
+    }
+    Ok(graph)
+  }
+
+  /// Build the graph from the nodes and edges.
+  /// Nodes that edges refer to without being listed are created without attributes.
+  pub fn to_graph<N: ImportData, E: ImportData>(&self) -> OwnedGraph<N, E> {
+    let mut graph = OwnedGraph::default();
+    let mut node_ids = HashMap::new();
+    for node in &self.nodes {
+      node_ids
+        .entry(node.id.as_str())
+        .or_insert_with(|| graph.insert_node(N::from_attributes(&node.id, &node.attributes)));
+    }
+    let no_attributes = BTreeMap::new();
+    for edge in &self.edges {
+      let [source, target] = [&edge.source, &edge.target].map(|id| {
+        *node_ids
+          .entry(id.as_str())
+          .or_insert_with(|| graph.insert_node(N::from_attributes(id, &no_attributes)))
+      });
+      graph.insert_edge(
+        source,
+        target,
+        E::from_attributes(&edge.id, &edge.attributes),
+      );
+    }
+    graph
+  }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Nodes of the graph by id, created in order of their first mention
+#[derive(Default)]
+struct NodesBuilder {
+  graph: ExportGraph,
+  by_id: HashMap<String, usize>,
+}
+
+impl NodesBuilder {
+  /// Index of the node, created with the `attributes` if it's new
+  fn node(&mut self, id: &str, attributes: impl FnOnce() -> BTreeMap<String, String>) -> usize {
+    if let Some(&index) = self.by_id.get(id) {
+      return index;
+    }
+    self.graph.nodes.push(ExportNode {
+      id: id.to_owned(),
+      x: None,
+      y: None,
+      attributes: attributes(),
+    });
+    self.by_id.insert(id.to_owned(), self.graph.nodes.len() - 1);
+    self.graph.nodes.len() - 1
+  }
+
+  fn edge(&mut self, source: &str, target: &str, attributes: BTreeMap<String, String>) {
+    self.node(source, BTreeMap::new);
+    self.node(target, BTreeMap::new);
+    self.graph.edges.push(ExportEdge {
+      id: String::new(),
+      source: source.to_owned(),
+      target: target.to_owned(),
+      attributes,
+    });
+  }
+}
+
+// JSON
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonGraph {
+  Graph(ExportGraph),
+  Edges(Vec<JsonEdge>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEdge {
+  Pair(Value, Value),
+  Object(JsonEdgeObject),
+}
+
+#[derive(Deserialize)]
+struct JsonEdgeObject {
+  source: Value,
+  target: Value,
+  #[serde(flatten)]
+  attributes: BTreeMap<String, Value>,
+}
+
+/// JSON value as an id or attribute, strings without quotes
+fn json_string(value: Value) -> String {
+  match value {
+    Value::String(string) => string,
+    value => value.to_string(),
+  }
+}
+
+fn parse_json(text: &str) -> io::Result<ExportGraph> {
+  let edges = match serde_json::from_str(text)? {
+    JsonGraph::Graph(graph) => return Ok(graph),
+    JsonGraph::Edges(edges) => edges,
+  };
+  let mut builder = NodesBuilder::default();
+  builder.graph.directed = true;
+  for edge in edges {
+    let (source, target, attributes) = match edge {
+      JsonEdge::Pair(source, target) => (source, target, BTreeMap::new()),
+      JsonEdge::Object(JsonEdgeObject {
+        source,
+        target,
+        attributes,
+      }) => (source, target, attributes),
+    };
+    let attributes = attributes
+      .into_iter()
+      .map(|(name, value)| (name, json_string(value)))
+      .collect();
+    builder.edge(&json_string(source), &json_string(target), attributes);
+  }
+  Ok(builder.graph)
+}
+
+// XML
+
+#[derive(Debug, PartialEq)]
+enum Xml {
+  /// Element with it's name without the namespace prefix, and it's attributes
+  Start {
+    name: String,
+    attributes: HashMap<String, String>,
+  },
+  End(String),
+  Text(String),
+}
+
+/// Elements and text of the XML document, empty elements are a start and an end.
+/// Declarations, comments and doctypes are skipped.
+fn parse_xml(text: &str) -> io::Result<Vec<Xml>> {
+  let unclosed = |what: &str| invalid_data(format!("unclosed {what}"));
+  let mut events = vec![];
+  let mut rest = text;
+  while !rest.is_empty() {
+    if let Some(after) = rest.strip_prefix("<!--") {
+      let end = after.find("-->").ok_or_else(|| unclosed("comment"))?;
+      rest = &after[end + 3..];
+    } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+      let end = after.find("]]>").ok_or_else(|| unclosed("CDATA"))?;
+      events.push(Xml::Text(after[..end].to_owned()));
+      rest = &after[end + 3..];
+    } else if rest.starts_with("<?") || rest.starts_with("<!") {
+      let end = rest.find('>').ok_or_else(|| unclosed("declaration"))?;
+      rest = &rest[end + 1..];
+    } else if let Some(after) = rest.strip_prefix("</") {
+      let end = after.find('>').ok_or_else(|| unclosed("tag"))?;
+      events.push(Xml::End(local_name(after[..end].trim()).to_owned()));
+      rest = &after[end + 1..];
+    } else if let Some(after) = rest.strip_prefix('<') {
+      let mut quote = None;
+      let end = after
+        .find(|char| {
+          match quote {
+            Some(open) if char == open => quote = None,
+            None if char == '"' || char == '\'' => quote = Some(char),
+            _ => {}
+          }
+          quote.is_none() && char == '>'
+        })
+        .ok_or_else(|| unclosed("tag"))?;
+      let tag = &after[..end];
+      let (tag, empty) = tag
+        .strip_suffix('/')
+        .map_or((tag, false), |tag| (tag, true));
+      let (name, attributes) = parse_tag(tag)?;
+      events.push(Xml::Start {
+        name: name.clone(),
+        attributes,
+      });
+      if empty {
+        events.push(Xml::End(name));
+      }
+      rest = &after[end + 1..];
+    } else {
+      let end = rest.find('<').unwrap_or(rest.len());
+      events.push(Xml::Text(xml_unescape(&rest[..end])));
+      rest = &rest[end..];
+    }
+  }
+  Ok(events)
+}
+
+/// Name of the element and it's attributes, in a tag without the angle brackets
+fn parse_tag(tag: &str) -> io::Result<(String, HashMap<String, String>)> {
+  let tag = tag.trim();
+  let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+  let name = local_name(&tag[..name_end]).to_owned();
+  let mut attributes = HashMap::new();
+  let mut rest = tag[name_end..].trim_start();
+  while !rest.is_empty() {
+    let invalid = || invalid_data(format!("invalid attributes in <{name}>"));
+    let (attribute, after) = rest.split_once('=').ok_or_else(invalid)?;
+    let after = after.trim_start();
+    let quote = after
+      .chars()
+      .next()
+      .filter(|char| *char == '"' || *char == '\'');
+    let quote = quote.ok_or_else(invalid)?;
+    let end = after[1..].find(quote).ok_or_else(invalid)?;
+    attributes.insert(
+      attribute.trim().to_owned(),
+      xml_unescape(&after[1..end + 1]),
+    );
+    rest = after[end + 2..].trim_start();
+  }
+  Ok((name, attributes))
+}
+
+/// Name without the namespace prefix
+fn local_name(name: &str) -> &str {
+  name.rsplit(':').next().unwrap_or(name)
+}
+
+fn xml_unescape(text: &str) -> String {
+  let mut unescaped = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(start) = rest.find('&') {
+    unescaped.push_str(&rest[..start]);
+    rest = &rest[start..];
+    let entity = rest.find(';').and_then(|end| {
+      let char = match &rest[1..end] {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        code => {
+          let code = match code.strip_prefix("#x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => code.strip_prefix('#')?.parse().ok()?,
+          };
+          char::from_u32(code)?
+        }
+      };
+      Some((char, end))
+    });
+    match entity {
+      Some((char, end)) => {
+        unescaped.push(char);
+        rest = &rest[end + 1..];
+      }
+      None => {
+        unescaped.push('&');
+        rest = &rest[1..];
+      }
+    }
+  }
+  unescaped.push_str(rest);
+  unescaped
+}
+
+/// Node or edge the attributes that are read belong to
+#[derive(Clone, Copy)]
+enum Element {
+  Node(usize),
+  Edge(usize),
+}
+
+impl ExportGraph {
+  fn attributes_mut(&mut self, element: Element) -> &mut BTreeMap<String, String> {
+    match element {
+      Element::Node(index) => &mut self.nodes[index].attributes,
+      Element::Edge(index) => &mut self.edges[index].attributes,
+    }
+  }
+}
+
+fn attribute(attributes: &HashMap<String, String>, name: &str) -> String {
+  attributes.get(name).cloned().unwrap_or_default()
+}
+
+/// Move parsable `x` and `y` attributes of the nodes to their positions
+fn take_positions(graph: &mut ExportGraph) {
+  for node in &mut graph.nodes {
+    for (axis, pos) in [("x", &mut node.x), ("y", &mut node.y)] {
+      if let Some(value) = node
+        .attributes
+        .get(axis)
+        .and_then(|value| value.parse().ok())
+      {
+        node.attributes.remove(axis);
+        *pos = Some(value);
+      }
+    }
+  }
+}
+
+/// Key of GraphML data
+struct GraphMlKey {
+  name: String,
+  /// Whether the key is for nodes, edges or both
+  target: String,
+  default: Option<String>,
+}
+
+fn parse_graphml(events: &[Xml]) -> ExportGraph {
+  let mut graph = ExportGraph {
+    directed: true,
+    ..ExportGraph::default()
+  };
+  let mut keys = HashMap::new();
+  let mut key = None;
+  let mut element = None;
+  // Key of the data, or the key of the default, and the text read so far
+  let mut data: Option<(Option<String>, String)> = None;
+  for event in events {
+    match event {
+      Xml::Start { name, attributes } => match name.as_str() {
+        "key" => {
+          let id = attribute(attributes, "id");
+          let key_name = attributes.get("attr.name").unwrap_or(&id).clone();
+          keys.insert(
+            id.clone(),
+            GraphMlKey {
+              name: key_name,
+              target: attributes
+                .get("for")
+                .cloned()
+                .unwrap_or_else(|| "all".to_owned()),
+              default: None,
+            },
+          );
+          key = Some(id);
+        }
+        "default" => data = Some((None, String::new())),
+        "graph" => graph.directed = attribute(attributes, "edgedefault") != "undirected",
+        "node" => {
+          graph.nodes.push(ExportNode {
+            id: attribute(attributes, "id"),
+            x: None,
+            y: None,
+            attributes: BTreeMap::new(),
+          });
+          element = Some(Element::Node(graph.nodes.len() - 1));
+        }
+        "edge" => {
+          graph.edges.push(ExportEdge {
+            id: attribute(attributes, "id"),
+            source: attribute(attributes, "source"),
+            target: attribute(attributes, "target"),
+            attributes: BTreeMap::new(),
+          });
+          element = Some(Element::Edge(graph.edges.len() - 1));
+        }
+        "data" => data = Some((Some(attribute(attributes, "key")), String::new())),
+        _ => {}
+      },
+      Xml::Text(text) => {
+        if let Some((_, value)) = &mut data {
+          value.push_str(text);
+        }
+      }
+      Xml::End(name) => match name.as_str() {
+        "key" => key = None,
+        "node" | "edge" => element = None,
+        "default" | "data" => {
+          let Some((data_key, value)) = data.take() else {
+            continue;
+          };
+          let value = value.trim().to_owned();
+          match (data_key, element) {
+            (Some(data_key), Some(element)) => {
+              let name = keys.get(&data_key).map_or(data_key, |key| key.name.clone());
+              graph.attributes_mut(element).insert(name, value);
+            }
+            (None, _) => {
+              if let Some(key) = key.as_ref().and_then(|key| keys.get_mut(key)) {
+                key.default = Some(value);
+              }
+            }
+            _ => {}
+          }
+        }
+        _ => {}
+      },
+    }
+  }
+
+  for key in keys.values() {
+    let Some(default) = &key.default else {
+      continue;
+    };
+    let set_default = |attributes: &mut BTreeMap<String, String>| {
+      attributes
+        .entry(key.name.clone())
+        .or_insert_with(|| default.clone());
+    };
+    if key.target == "node" || key.target == "all" {
+      graph
+        .nodes
+        .iter_mut()
+        .for_each(|node| set_default(&mut node.attributes));
+    }
+    if key.target == "edge" || key.target == "all" {
+      graph
+        .edges
+        .iter_mut()
+        .for_each(|edge| set_default(&mut edge.attributes));
+    }
+  }
+  take_positions(&mut graph);
+  graph
+}
+
+fn parse_gexf(events: &[Xml]) -> ExportGraph {
+  let mut graph = ExportGraph {
+    directed: true,
+    ..ExportGraph::default()
+  };
+  // Titles of the attributes by class and id
+  let mut titles = HashMap::new();
+  let mut class = String::new();
+  let mut element = None;
+  for event in events {
+    match event {
+      Xml::Start { name, attributes } => match name.as_str() {
+        "graph" => graph.directed = attribute(attributes, "defaultedgetype") != "undirected",
+        "attributes" => class = attribute(attributes, "class"),
+        "attribute" => {
+          let id = attribute(attributes, "id");
+          let title = attributes.get("title").unwrap_or(&id).clone();
+          titles.insert((class.clone(), id), title);
+        }
+        "node" | "edge" => {
+          let mut element_attributes = BTreeMap::new();
+          if let Some(label) = attributes.get("label") {
+            element_attributes.insert("label".to_owned(), label.clone());
+          }
+          if name == "node" {
+            graph.nodes.push(ExportNode {
+              id: attribute(attributes, "id"),
+              x: None,
+              y: None,
+              attributes: element_attributes,
+            });
+            element = Some(Element::Node(graph.nodes.len() - 1));
+          } else {
+            graph.edges.push(ExportEdge {
+              id: attribute(attributes, "id"),
+              source: attribute(attributes, "source"),
+              target: attribute(attributes, "target"),
+              attributes: element_attributes,
+            });
+            element = Some(Element::Edge(graph.edges.len() - 1));
+          }
+        }
+        "attvalue" => {
+          let Some(element) = element else {
+            continue;
+          };
+          let class = match element {
+            Element::Node(_) => "node",
+            Element::Edge(_) => "edge",
+          };
+          let id = attribute(attributes, "for");
+          let title = titles
+            .get(&(class.to_owned(), id.clone()))
+            .cloned()
+            .unwrap_or(id);
+          graph
+            .attributes_mut(element)
+            .insert(title, attribute(attributes, "value"));
+        }
+        "position" => {
+          if let Some(Element::Node(index)) = element {
+            let node = &mut graph.nodes[index];
+            node.x = attributes.get("x").and_then(|x| x.parse().ok());
+            node.y = attributes.get("y").and_then(|y| y.parse().ok());
+          }
+        }
+        _ => {}
+      },
+      Xml::End(name) if name == "node" || name == "edge" => element = None,
+      _ => {}
+    }
+  }
+  graph
+}
+
+// DOT
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+  Id(String),
+  /// Unquoted keyword, lowercased
+  Keyword(String),
+  Punct(&'static str),
+}
+
+const DOT_KEYWORDS: &[&str] = &["strict", "graph", "digraph", "node", "edge", "subgraph"];
+const DOT_PUNCTS: &[&str] = &["->", "--", "{", "}", "[", "]", ";", ",", "=", ":"];
+
+/// Tokens of the DOT source with their line numbers
+fn tokenize_dot(text: &str) -> io::Result<Vec<(Token, usize)>> {
+  let mut tokens = vec![];
+  let mut chars = text.char_indices().peekable();
+  let mut line = 1;
+  while let Some((start, char)) = chars.next() {
+    let rest = &text[start..];
+    let token_line = line;
+    let token = match char {
+      '\n' => {
+        line += 1;
+        continue;
+      }
+      _ if char.is_whitespace() => continue,
+      '#' => {
+        while chars.next_if(|(_, char)| *char != '\n').is_some() {}
+        continue;
+      }
+      '/' if rest.starts_with("//") => {
+        while chars.next_if(|(_, char)| *char != '\n').is_some() {}
+        continue;
+      }
+      '/' if rest.starts_with("/*") => {
+        let end = rest
+          .find("*/")
+          .ok_or_else(|| invalid_data("unclosed comment"))?;
+        line += rest[..end].matches('\n').count();
+        while chars
+          .next_if(|(index, _)| *index < start + end + 2)
+          .is_some()
+        {}
+        continue;
+      }
+      '"' => {
+        let mut string = String::new();
+        loop {
+          match chars.next() {
+            Some((_, '"')) => break,
+            Some((_, '\\')) => match chars.next() {
+              Some((_, '\n')) => line += 1,
+              Some((_, escaped @ ('"' | '\\'))) => string.push(escaped),
+              Some((_, other)) => string.extend(['\\', other]),
+              None => {}
+            },
+            Some((_, char)) => {
+              line += usize::from(char == '\n');
+              string.push(char);
+            }
+            None => return Err(invalid_data(format!("line {token_line}: unclosed string"))),
+          }
+        }
+        Token::Id(string)
+      }
+      '<' => {
+        let mut depth = 1;
+        let mut string = String::new();
+        while depth > 0 {
+          let (_, char) = chars
+            .next()
+            .ok_or_else(|| invalid_data(format!("line {token_line}: unclosed HTML string")))?;
+          depth += usize::from(char == '<');
+          depth -= usize::from(char == '>');
+          line += usize::from(char == '\n');
+          if depth > 0 {
+            string.push(char);
+          }
+        }
+        Token::Id(string)
+      }
+      _ if char.is_alphanumeric()
+        || char == '_'
+        || char == '.'
+        || rest.starts_with("-.")
+        || (char == '-' && rest[1..].starts_with(|char: char| char.is_ascii_digit())) =>
+      {
+        let mut end = start + char.len_utf8();
+        while let Some((index, char)) =
+          chars.next_if(|(_, char)| char.is_alphanumeric() || *char == '_' || *char == '.')
+        {
+          end = index + char.len_utf8();
+        }
+        let id = &text[start..end];
+        let keyword = id.to_lowercase();
+        if DOT_KEYWORDS.contains(&keyword.as_str()) {
+          Token::Keyword(keyword)
+        } else {
+          Token::Id(id.to_owned())
+        }
+      }
+      _ => {
+        let punct = DOT_PUNCTS
+          .iter()
+          .find(|punct| rest.starts_with(**punct))
+          .ok_or_else(|| invalid_data(format!("line {line}: unexpected `{char}`")))?;
+        if punct.len() > 1 {
+          chars.next();
+        }
+        Token::Punct(punct)
+      }
+    };
+    tokens.push((token, token_line));
+  }
+  Ok(tokens)
+}
+
+/// Default attributes of the nodes and edges created in a graph or subgraph
+#[derive(Clone, Default)]
+struct DotScope {
+  node: BTreeMap<String, String>,
+  edge: BTreeMap<String, String>,
+}
+
+struct DotParser {
+  tokens: Vec<(Token, usize)>,
+  position: usize,
+  builder: NodesBuilder,
+}
+
+impl DotParser {
+  fn new(text: &str) -> io::Result<Self> {
+    Ok(Self {
+      tokens: tokenize_dot(text)?,
+      position: 0,
+      builder: NodesBuilder::default(),
+    })
+  }
+
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position).map(|(token, _)| token)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.peek().cloned();
+    self.position += 1;
+    token
+  }
+
+  /// Take the next token if it is the punctuation
+  fn eat(&mut self, punct: &str) -> bool {
+    let eaten = matches!(self.peek(), Some(Token::Punct(next)) if *next == punct);
+    self.position += usize::from(eaten);
+    eaten
+  }
+
+  fn error(&self, expected: &str) -> io::Error {
+    let position = self.position.min(self.tokens.len().saturating_sub(1));
+    match self.tokens.get(position) {
+      Some((token, line)) => {
+        invalid_data(format!("line {line}: expected {expected}, found {token:?}"))
+      }
+      None => invalid_data(format!("expected {expected}, found the end")),
+    }
+  }
+
+  fn expect(&mut self, punct: &str) -> io::Result<()> {
+    if self.eat(punct) {
+      Ok(())
+    } else {
+      Err(self.error(&format!("`{punct}`")))
+    }
+  }
+
+  fn id(&mut self) -> io::Result<String> {
+    match self.peek() {
+      Some(Token::Id(id)) => {
+        let id = id.clone();
+        self.position += 1;
+        Ok(id)
+      }
+      _ => Err(self.error("an id")),
+    }
+  }
+
+  fn parse(mut self) -> io::Result<ExportGraph> {
+    if self.peek() == Some(&Token::Keyword("strict".to_owned())) {
+      self.position += 1;
+    }
+    self.builder.graph.directed = match self.next() {
+      Some(Token::Keyword(keyword)) if keyword == "digraph" => true,
+      Some(Token::Keyword(keyword)) if keyword == "graph" => false,
+      _ => return Err(self.error("`graph` or `digraph`")),
+    };
+    if matches!(self.peek(), Some(Token::Id(_))) {
+      self.position += 1;
+    }
+    self.expect("{")?;
+    self.statements(&mut DotScope::default())?;
+    self.expect("}")?;
+    let mut graph = self.builder.graph;
+    // Graphviz positions are in inches with `y` pointing up, pinned with `!`
+    for node in &mut graph.nodes {
+      let pos = node.attributes.get("pos").and_then(|pos| {
+        let (x, y) = pos.trim_end_matches('!').split_once(',')?;
+        Some((x.trim().parse::<f32>().ok()?, y.trim().parse::<f32>().ok()?))
+      });
+      if let Some((x, y)) = pos {
+        node.attributes.remove("pos");
+        (node.x, node.y) = (Some(x), Some(-y));
+      }
+    }
+    Ok(graph)
+  }
+
+  /// Statements until the closing brace, returns the nodes they mention
+  fn statements(&mut self, scope: &mut DotScope) -> io::Result<Vec<usize>> {
+    let mut nodes = vec![];
+    while !matches!(self.peek(), Some(Token::Punct("}")) | None) {
+      nodes.extend(self.statement(scope)?);
+      self.eat(";");
+    }
+    Ok(nodes)
+  }
+
+  fn statement(&mut self, scope: &mut DotScope) -> io::Result<Vec<usize>> {
+    match self.peek().cloned() {
+      Some(Token::Keyword(keyword)) if keyword != "subgraph" => {
+        self.position += 1;
+        let attributes = self.attributes()?;
+        match keyword.as_str() {
+          "node" => scope.node.extend(attributes),
+          "edge" => scope.edge.extend(attributes),
+          _ => {}
+        }
+        Ok(vec![])
+      }
+      Some(Token::Id(_))
+        if self.tokens.get(self.position + 1).map(|(token, _)| token)
+          == Some(&Token::Punct("=")) =>
+      {
+        // Graph attribute
+        self.position += 2;
+        self.id()?;
+        Ok(vec![])
+      }
+      _ => {
+        let is_node = matches!(self.peek(), Some(Token::Id(_)));
+        let operand = self.operand(scope)?;
+        if matches!(self.peek(), Some(Token::Punct("->" | "--"))) {
+          self.edges(operand, scope)
+        } else {
+          if is_node {
+            let attributes = self.attributes()?;
+            for &index in &operand {
+              self.builder.graph.nodes[index]
+                .attributes
+                .extend(attributes.clone());
+            }
+          }
+          Ok(operand)
+        }
+      }
+    }
+  }
+
+  /// Node id or subgraph, as the nodes they stand for
+  fn operand(&mut self, scope: &DotScope) -> io::Result<Vec<usize>> {
+    if matches!(self.peek(), Some(Token::Id(_))) {
+      let id = self.id()?;
+      // Ports are ignored
+      while self.eat(":") {
+        self.id()?;
+      }
+      return Ok(vec![self.builder.node(&id, || scope.node.clone())]);
+    }
+    if self.peek() == Some(&Token::Keyword("subgraph".to_owned())) {
+      self.position += 1;
+      if matches!(self.peek(), Some(Token::Id(_))) {
+        self.position += 1;
+      }
+    }
+    self.expect("{")?;
+    let nodes = self.statements(&mut scope.clone())?;
+    self.expect("}")?;
+    Ok(nodes)
+  }
+
+  /// Chain of edges starting from the `source` nodes
+  fn edges(&mut self, source: Vec<usize>, scope: &DotScope) -> io::Result<Vec<usize>> {
+    let mut operands = vec![source];
+    while self.eat("->") || self.eat("--") {
+      operands.push(self.operand(scope)?);
+    }
+    let mut attributes = scope.edge.clone();
+    attributes.extend(self.attributes()?);
+    for pair in operands.windows(2) {
+      for &source in &pair[0] {
+        for &target in &pair[1] {
+          let [source, target] =
+            [source, target].map(|index| self.builder.graph.nodes[index].id.clone());
+          self.builder.edge(&source, &target, attributes.clone());
+        }
+      }
+    }
+    Ok(operands.concat())
+  }
+
+  /// Attribute lists, `[name=value, ...]`
+  fn attributes(&mut self) -> io::Result<BTreeMap<String, String>> {
+    let mut attributes = BTreeMap::new();
+    while self.eat("[") {
+      while !self.eat("]") {
+        let name = self.id()?;
+        let value = if self.eat("=") {
+          self.id()?
+        } else {
+          "true".to_owned()
+        };
+        attributes.insert(name, value);
+        if !self.eat(",") {
+          self.eat(";");
+        }
+      }
+    }
+    Ok(attributes)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::lib::fdp::eades_custom::{NodeFdpData, NodePositions};
+  use crate::lib::graph::{EdgeIncidents, Graph};
+  use egui::{vec2, Vec2};
+
+  struct Label(String);
+
+  impl ImportData for Label {
+    fn from_attributes(id: &str, attributes: &BTreeMap<String, String>) -> Self {
+      Self(
+        attributes
+          .get("label")
+          .map_or(id, String::as_str)
+          .to_owned(),
+      )
+    }
+  }
+
+  impl crate::lib::export::ExportData for Label {
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+      vec![("label", self.0.clone())]
+    }
+  }
+
+  /// Labels of the edges' source and target nodes
+  fn edge_labels(graph: &OwnedGraph<Label, Label>) -> Vec<(String, String)> {
+    graph
+      .iter_edges()
+      .map(|(edge_id, _)| {
+        let EdgeIncidents { node_from, node_to } = graph.get_edge_incidents(edge_id);
+        let label = |node_id| graph.get_node(node_id).0.clone();
+        (label(node_from), label(node_to))
+      })
+      .collect()
+  }
+
+  #[test]
+  fn round_trip() {
+    let mut graph = OwnedGraph::default();
+    let a = graph.insert_node(Label("<Fish> & \"Chips\" \\".to_owned()));
+    let b = graph.insert_node(Label("B".to_owned()));
+    graph.insert_edge(a, b, Label("link".to_owned()));
+    graph.insert_edge(b, a, Label("back".to_owned()));
+    let node_positions = NodePositions(HashMap::from([(
+      a,
+      NodeFdpData {
+        pos: vec2(1.5, -2.25),
+        force: Vec2::ZERO,
+      },
+    )]));
+    let exported = ExportGraph::new(&graph, &node_positions);
+    for &format in ExportFormat::ALL {
+      let mut output = vec![];
+      exported.write(format, &mut output).unwrap();
+      let imported = ExportGraph::parse(&String::from_utf8(output).unwrap(), format).unwrap();
+      assert_eq!(imported, exported, "{format}");
+    }
+  }
+
+  #[test]
+  fn external_graphs() {
+    let dot = r#"
+      /* Dependencies */
+      strict digraph deps {
+        rankdir = LR
+        node [shape=box]
+        app -> { core "ui kit" } -> log [weight=2];
+        subgraph cluster { edge [style=dashed]; core -> util:port }
+        app [label="The app"]; # entry point
+      }
+    "#;
+    let graph: OwnedGraph<Label, Label> = import_graph(dot, ExportFormat::Dot).unwrap();
+    assert_eq!(
+      edge_labels(&graph),
+      [
+        ("The app", "core"),
+        ("The app", "ui kit"),
+        ("core", "log"),
+        ("ui kit", "log"),
+        ("core", "util"),
+      ]
+      .map(|(a, b)| (a.to_owned(), b.to_owned()))
+    );
+    let parsed = ExportGraph::parse(dot, ExportFormat::Dot).unwrap();
+    assert_eq!(parsed.nodes[0].attributes["shape"], "box");
+    assert_eq!(parsed.edges[2].attributes["weight"], "2");
+    assert_eq!(parsed.edges[4].attributes["style"], "dashed");
+
+    let graphml = r#"<?xml version="1.0"?>
+      <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+        <key id="d0" for="node" attr.name="label"><default>unnamed</default></key>
+        <graph edgedefault="undirected">
+          <node id="a"><data key="d0">A &amp; co</data></node>
+          <node id="b"/>
+          <edge source="a" target="b"/>
+        </graph>
+      </graphml>"#;
+    let graph: OwnedGraph<Label, Label> = import_graph(graphml, ExportFormat::GraphMl).unwrap();
+    assert_eq!(
+      edge_labels(&graph),
+      [("A & co".to_owned(), "unnamed".to_owned())]
+    );
+
+    let json = r#"[["a", "b"], { "source": "b", "target": 3, "weight": 2 }]"#;
+    let parsed = ExportGraph::parse(json, ExportFormat::Json).unwrap();
+    assert_eq!(parsed.nodes.len(), 3);
+    assert_eq!(parsed.edges[1].target, "3");
+    assert_eq!(parsed.edges[1].attributes["weight"], "2");
+    assert_eq!(parsed.edges[1].id, "e1");
+  }
+}
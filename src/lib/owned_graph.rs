@@ -0,0 +1,332 @@
+use crate::lib::graph::{EdgeIncidents, Graph, GraphMut};
+
+#[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct NodeId(usize);
+
+#[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct EdgeId(usize);
+
+struct NodeEntry<N> {
+  data: N,
+  in_edges: Vec<EdgeId>,
+  out_edges: Vec<EdgeId>,
+}
+
+struct EdgeEntry<E> {
+  data: E,
+  node_from: NodeId,
+  node_to: NodeId,
+}
+
+/// Graph that owns it's node and edge data.
+/// Ids of removed nodes and edges are never reused.
+pub struct OwnedGraph<N, E> {
+  nodes: Vec<Option<NodeEntry<N>>>,
+  edges: Vec<Option<EdgeEntry<E>>>,
+}
+
+impl<N, E> Default for OwnedGraph<N, E> {
+  fn default() -> Self {
+    Self {
+      nodes: vec![],
+      edges: vec![],
+    }
+  }
+}
+
+impl<N, E> OwnedGraph<N, E> {
+  fn node_entry(&self, id: NodeId) -> &NodeEntry<N> {
+    self.nodes[id.0].as_ref().expect("node was removed")
+  }
+
+  /// Adds a node with owned data to the graph
+  pub fn insert_node(&mut self, data: N) -> NodeId {
+    self.nodes.push(Some(NodeEntry {
+      data,
+      in_edges: vec![],
+      out_edges: vec![],
+    }));
+    NodeId(self.nodes.len() - 1)
+  }
+
+  /// Adds an edge with owned data to the graph
+  pub fn insert_edge(&mut self, node_from: NodeId, node_to: NodeId, data: E) -> EdgeId {
+    let edge_id = EdgeId(self.edges.len());
+    self.edges.push(Some(EdgeEntry {
+      data,
+      node_from,
+      node_to,
+    }));
+    self.nodes[node_from.0]
+      .as_mut()
+      .expect("node was removed")
+      .out_edges
+      .push(edge_id);
+    self.nodes[node_to.0]
+      .as_mut()
+      .expect("node was removed")
+      .in_edges
+      .push(edge_id);
+    edge_id
+  }
+
+  /// Whether the node is in the graph
+  pub fn contains_node(&self, id: NodeId) -> bool {
+    self.nodes.get(id.0).is_some_and(Option::is_some)
+  }
+}
+
+pub struct NodeIter<'a, N> {
+  inner: std::iter::Enumerate<std::slice::Iter<'a, Option<NodeEntry<N>>>>,
+}
+
+impl<'a, N> Iterator for NodeIter<'a, N> {
+  type Item = (NodeId, &'a N);
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .inner
+      .find_map(|(index, entry)| Some((NodeId(index), &entry.as_ref()?.data)))
+  }
+}
+
+pub struct NodeIterMut<'a, N> {
+  inner: std::iter::Enumerate<std::slice::IterMut<'a, Option<NodeEntry<N>>>>,
+}
+
+impl<'a, N> Iterator for NodeIterMut<'a, N> {
+  type Item = (NodeId, &'a mut N);
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .inner
+      .find_map(|(index, entry)| Some((NodeId(index), &mut entry.as_mut()?.data)))
+  }
+}
+
+/// Which edges an `EdgeIter` yields
+enum EdgeIds<'a> {
+  All(std::ops::Range<usize>),
+  /// Edges from the first list, then edges from the second one that are not self-loops
+  /// (self-loops are in both `in_edges` and `out_edges` of the node)
+  Lists(std::slice::Iter<'a, EdgeId>, std::slice::Iter<'a, EdgeId>),
+}
+
+pub struct EdgeIter<'a, E> {
+  edges: &'a [Option<EdgeEntry<E>>],
+  ids: EdgeIds<'a>,
+}
+
+impl<'a, E> Iterator for EdgeIter<'a, E> {
+  type Item = (EdgeId, &'a E);
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let id = match &mut self.ids {
+        EdgeIds::All(range) => EdgeId(range.next()?),
+        EdgeIds::Lists(first, second) => match first.next() {
+          Some(&id) => id,
+          None => {
+            let id = *second.next()?;
+            match &self.edges[id.0] {
+              Some(entry) if entry.node_from == entry.node_to => continue,
+              _ => id,
+            }
+          }
+        },
+      };
+      if let Some(entry) = &self.edges[id.0] {
+        return Some((id, &entry.data));
+      }
+    }
+  }
+}
+
+pub struct EdgeIterMut<'a, E> {
+  inner: std::iter::Enumerate<std::slice::IterMut<'a, Option<EdgeEntry<E>>>>,
+  /// Only yield edges incident to this node, if set
+  incident_to: Option<NodeId>,
+}
+
+impl<'a, E> Iterator for EdgeIterMut<'a, E> {
+  type Item = (EdgeId, &'a mut E);
+  fn next(&mut self) -> Option<Self::Item> {
+    let incident_to = self.incident_to;
+    self.inner.find_map(|(index, entry)| {
+      let entry = entry.as_mut()?;
+      let incident = incident_to.map_or(true, |node_id| {
+        entry.node_from == node_id || entry.node_to == node_id
+      });
+      incident.then_some((EdgeId(index), &mut entry.data))
+    })
+  }
+}
+
+impl<N, E> Graph for OwnedGraph<N, E> {
+  type NodeId = NodeId;
+  type NodeData = N;
+
+  type EdgeId = EdgeId;
+  type EdgeData = E;
+
+  type NodeIter<'a> = NodeIter<'a, N> where Self: 'a;
+
+  type EdgeIter<'a> = EdgeIter<'a, E> where Self: 'a;
+
+  fn iter_nodes(&self) -> Self::NodeIter<'_> {
+    NodeIter {
+      inner: self.nodes.iter().enumerate(),
+    }
+  }
+
+  fn iter_edges(&self) -> Self::EdgeIter<'_> {
+    EdgeIter {
+      edges: &self.edges,
+      ids: EdgeIds::All(0..self.edges.len()),
+    }
+  }
+
+  /// Iterate edges incident to the node
+  fn iter_incidents(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_> {
+    let entry = self.node_entry(node_id);
+    EdgeIter {
+      edges: &self.edges,
+      ids: EdgeIds::Lists(entry.in_edges.iter(), entry.out_edges.iter()),
+    }
+  }
+
+  /// Iterate edges coming into the node
+  fn iter_in_edges(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_> {
+    let entry = self.node_entry(node_id);
+    EdgeIter {
+      edges: &self.edges,
+      ids: EdgeIds::Lists(entry.in_edges.iter(), Default::default()),
+    }
+  }
+
+  /// Iterate edges going out of the node
+  fn iter_out_edges(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_> {
+    let entry = self.node_entry(node_id);
+    EdgeIter {
+      edges: &self.edges,
+      ids: EdgeIds::Lists(entry.out_edges.iter(), Default::default()),
+    }
+  }
+
+  fn get_node(&self, id: Self::NodeId) -> &Self::NodeData {
+    &self.node_entry(id).data
+  }
+
+  fn get_edge(&self, id: Self::EdgeId) -> &Self::EdgeData {
+    &self.edges[id.0].as_ref().expect("edge was removed").data
+  }
+
+  /// Get incident nodes of the edge
+  fn get_edge_incidents(&self, edge_id: Self::EdgeId) -> EdgeIncidents<Self::NodeId> {
+    let entry = self.edges[edge_id.0].as_ref().expect("edge was removed");
+    EdgeIncidents {
+      node_from: entry.node_from,
+      node_to: entry.node_to,
+    }
+  }
+}
+
+impl<N: Clone, E: Clone> GraphMut for OwnedGraph<N, E> {
+  type NodeIterMut<'a> = NodeIterMut<'a, N> where Self: 'a;
+
+  type EdgeIterMut<'a> = EdgeIterMut<'a, E> where Self: 'a;
+
+  fn iter_nodes_mut(&mut self) -> Self::NodeIterMut<'_> {
+    NodeIterMut {
+      inner: self.nodes.iter_mut().enumerate(),
+    }
+  }
+
+  fn iter_edges_mut(&mut self) -> Self::EdgeIterMut<'_> {
+    EdgeIterMut {
+      inner: self.edges.iter_mut().enumerate(),
+      incident_to: None,
+    }
+  }
+
+  /// Iterate edges incident to the node (mutably)
+  fn iter_incidents_mut(&mut self, node_id: Self::NodeId) -> Self::EdgeIterMut<'_> {
+    EdgeIterMut {
+      inner: self.edges.iter_mut().enumerate(),
+      incident_to: Some(node_id),
+    }
+  }
+
+  fn get_node_mut(&mut self, id: Self::NodeId) -> &mut Self::NodeData {
+    &mut self.nodes[id.0].as_mut().expect("node was removed").data
+  }
+
+  fn get_edge_mut(&mut self, id: Self::EdgeId) -> &mut Self::EdgeData {
+    &mut self.edges[id.0].as_mut().expect("edge was removed").data
+  }
+
+  /// Adds a node to the graph
+  fn add_node(&mut self, data: &Self::NodeData) -> Self::NodeId {
+    self.insert_node(data.clone())
+  }
+
+  /// Removes a node from the graph, returning it's `NodeData` if the node was previously in the graph.
+  /// Edges incident to the node are removed as well.
+  fn remove_node(&mut self, id: Self::NodeId) -> Option<Self::NodeData> {
+    let entry = self.nodes.get_mut(id.0)?.take()?;
+    for edge_id in entry.in_edges.iter().chain(entry.out_edges.iter()) {
+      self.remove_edge(*edge_id);
+    }
+    Some(entry.data)
+  }
+
+  /// Adds an edge to the graph
+  fn add_edge(
+    &mut self,
+    node_from: Self::NodeId,
+    node_to: Self::NodeId,
+    data: &Self::EdgeData,
+  ) -> Self::EdgeId {
+    self.insert_edge(node_from, node_to, data.clone())
+  }
+
+  /// Removes an edge from the graph, returning it's `EdgeData` if the edge was previously in the graph
+  fn remove_edge(&mut self, id: Self::EdgeId) -> Option<Self::EdgeData> {
+    let entry = self.edges.get_mut(id.0)?.take()?;
+    if let Some(node) = self.nodes[entry.node_from.0].as_mut() {
+      node.out_edges.retain(|&edge_id| edge_id != id);
+    }
+    if let Some(node) = self.nodes[entry.node_to.0].as_mut() {
+      node.in_edges.retain(|&edge_id| edge_id != id);
+    }
+    Some(entry.data)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn incidents_by_direction() {
+    let mut graph = OwnedGraph::<(), ()>::default();
+    let a = graph.insert_node(());
+    let b = graph.insert_node(());
+    let ab = graph.insert_edge(a, b, ());
+    let aa = graph.insert_edge(a, a, ());
+    let ids = |iter: EdgeIter<()>| iter.map(|(id, _)| id).collect::<Vec<_>>();
+    assert_eq!(ids(graph.iter_out_edges(a)), vec![ab, aa]);
+    assert_eq!(ids(graph.iter_in_edges(a)), vec![aa]);
+    assert_eq!(ids(graph.iter_incidents(a)), vec![aa, ab]);
+    assert_eq!(ids(graph.iter_in_edges(b)), vec![ab]);
+  }
+
+  #[test]
+  fn remove_node_removes_edges() {
+    let mut graph = OwnedGraph::<(), ()>::default();
+    let a = graph.insert_node(());
+    let b = graph.insert_node(());
+    graph.insert_edge(a, b, ());
+    graph.remove_node(b);
+    assert_eq!(graph.iter_nodes().count(), 1);
+    assert_eq!(graph.iter_edges().count(), 0);
+    assert_eq!(graph.iter_out_edges(a).count(), 0);
+  }
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Longest match deflate can encode
+const MAX_MATCH: usize = 258;
+/// Shortest match worth encoding
+const MIN_MATCH: usize = 3;
+
+/// Write an 8-bit RGBA image, rows from the top, as a PNG
+pub fn write_png(writer: &mut impl Write, width: u32, height: u32, rgba: &[u8]) -> io::Result<()> {
+  let row_length = width as usize * 4;
+  if rgba.len() != row_length * height as usize {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      "image data doesn't match it's size",
+    ));
+  }
+  writer.write_all(&SIGNATURE)?;
+
+  let mut header = vec![];
+  header.extend(width.to_be_bytes());
+  header.extend(height.to_be_bytes());
+  // 8 bits per channel, RGBA, default compression, filtering and no interlacing
+  header.extend([8, 6, 0, 0, 0]);
+  write_chunk(writer, b"IHDR", &header)?;
+
+  // Every row starts with it's filter type, none
+  let mut scanlines = Vec::with_capacity(rgba.len() + height as usize);
+  for row in rgba.chunks(row_length.max(1)) {
+    scanlines.push(0);
+    scanlines.extend(row);
+  }
+  write_chunk(writer, b"IDAT", &zlib(&scanlines))?;
+  write_chunk(writer, b"IEND", &[])
+}
+
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+  writer.write_all(&(data.len() as u32).to_be_bytes())?;
+  writer.write_all(kind)?;
+  writer.write_all(data)?;
+  let crc = crc32(kind.iter().chain(data));
+  writer.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+  let mut crc = !0u32;
+  for &byte in bytes {
+    crc ^= u32::from(byte);
+    for _ in 0..8 {
+      crc = if crc & 1 == 1 {
+        (crc >> 1) ^ 0xedb8_8320
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+  const MODULO: u32 = 65521;
+  let (mut a, mut b) = (1, 0);
+  for &byte in bytes {
+    a = (a + u32::from(byte)) % MODULO;
+    b = (b + a) % MODULO;
+  }
+  (b << 16) | a
+}
+
+/// Zlib stream of the data
+fn zlib(data: &[u8]) -> Vec<u8> {
+  // Deflate with a 32K window, no dictionary
+  let mut stream = vec![0x78, 0x01];
+  stream.extend(deflate(data));
+  stream.extend(adler32(data).to_be_bytes());
+  stream
+}
+
+/// Bits written from the least significant one, as deflate packs them
+#[derive(Default)]
+struct BitWriter {
+  bytes: Vec<u8>,
+  bit: u8,
+}
+
+impl BitWriter {
+  fn bits(&mut self, value: u32, count: u8) {
+    for index in 0..count {
+      if self.bit == 0 {
+        self.bytes.push(0);
+      }
+      let last = self.bytes.len() - 1;
+      self.bytes[last] |= (((value >> index) & 1) as u8) << self.bit;
+      self.bit = (self.bit + 1) % 8;
+    }
+  }
+
+  /// Huffman codes are packed from their most significant bit
+  fn code(&mut self, code: u32, length: u8) {
+    self.bits(code.reverse_bits() >> (32 - length), length);
+  }
+}
+
+/// Literal or length symbol with the fixed Huffman code
+fn write_symbol(writer: &mut BitWriter, symbol: u32) {
+  match symbol {
+    0..=143 => writer.code(0x30 + symbol, 8),
+    144..=255 => writer.code(0x190 + symbol - 144, 9),
+    256..=279 => writer.code(symbol - 256, 7),
+    _ => writer.code(0xc0 + symbol - 280, 8),
+  }
+}
+
+/// Length symbol, it's extra bits and their count
+fn length_symbol(length: usize) -> (u32, u32, u8) {
+  const BASES: [usize; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+  ];
+  const EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+  ];
+  let index = BASES.iter().rposition(|base| *base <= length).unwrap_or(0);
+  (
+    257 + index as u32,
+    (length - BASES[index]) as u32,
+    EXTRA_BITS[index],
+  )
+}
+
+/// Deflate with the fixed Huffman codes. Only repeats of the previous 4 bytes are matched,
+/// which is what runs of pixels of the same color are.
+fn deflate(data: &[u8]) -> Vec<u8> {
+  const DISTANCE: usize = 4;
+  // Distance code of 4, without extra bits
+  const DISTANCE_CODE: u32 = 3;
+  let mut writer = BitWriter::default();
+  // Final block with fixed codes
+  writer.bits(1, 1);
+  writer.bits(1, 2);
+  let mut index = 0;
+  while index < data.len() {
+    let length = if index >= DISTANCE {
+      data[index..]
+        .iter()
+        .zip(&data[index - DISTANCE..])
+        .take(MAX_MATCH)
+        .take_while(|(a, b)| a == b)
+        .count()
+    } else {
+      0
+    };
+    if length >= MIN_MATCH {
+      let (symbol, extra, extra_bits) = length_symbol(length);
+      write_symbol(&mut writer, symbol);
+      writer.bits(extra, extra_bits);
+      writer.code(DISTANCE_CODE, 5);
+      index += length;
+    } else {
+      write_symbol(&mut writer, u32::from(data[index]));
+      index += 1;
+    }
+  }
+  write_symbol(&mut writer, 256);
+  writer.bytes
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn checksums_and_compression() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    assert_eq!(length_symbol(3), (257, 0, 0));
+    assert_eq!(length_symbol(12), (265, 1, 1));
+    assert_eq!(length_symbol(258), (285, 0, 0));
+
+    // A run of one color takes a few bytes
+    let pixels = [10, 20, 30, 255].repeat(1000);
+    assert!(deflate(&pixels).len() < 100);
+
+    let mut png = vec![];
+    write_png(&mut png, 2, 1, &[0, 0, 0, 255, 255, 255, 255, 255]).unwrap();
+    assert!(png.starts_with(&SIGNATURE));
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+  }
+}
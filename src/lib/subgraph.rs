@@ -0,0 +1,130 @@
+use crate::lib::graph::{EdgeIncidents, Graph};
+use std::collections::HashSet;
+
+/// Read-only view of a graph restricted to a set of nodes, and optionally a set of edges.
+/// Only edges with both incident nodes in the set are visible.
+/// Nothing is copied from the underlying graph.
+pub struct Subgraph<'g, G: Graph> {
+  graph: &'g G,
+  nodes: &'g HashSet<G::NodeId>,
+  edges: Option<&'g HashSet<G::EdgeId>>,
+}
+
+impl<'g, G: Graph> Subgraph<'g, G> {
+  pub const fn new(graph: &'g G, nodes: &'g HashSet<G::NodeId>) -> Self {
+    Self {
+      graph,
+      nodes,
+      edges: None,
+    }
+  }
+
+  /// Restrict the view to the `edges` too
+  pub const fn with_edges(self, edges: &'g HashSet<G::EdgeId>) -> Self {
+    Self {
+      edges: Some(edges),
+      ..self
+    }
+  }
+
+  /// Whether the node is visible in this view
+  pub fn contains(&self, node_id: G::NodeId) -> bool {
+    self.nodes.contains(&node_id)
+  }
+
+  fn contains_edge(&self, edge_id: G::EdgeId) -> bool {
+    let EdgeIncidents { node_from, node_to } = self.graph.get_edge_incidents(edge_id);
+    self.contains(node_from)
+      && self.contains(node_to)
+      && self.edges.map_or(true, |edges| edges.contains(&edge_id))
+  }
+}
+
+pub struct NodeIter<'a, G: Graph + 'a> {
+  inner: G::NodeIter<'a>,
+  nodes: &'a HashSet<G::NodeId>,
+}
+
+impl<'a, G: Graph + 'a> Iterator for NodeIter<'a, G> {
+  type Item = (G::NodeId, &'a G::NodeData);
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.find(|(node_id, _)| self.nodes.contains(node_id))
+  }
+}
+
+pub struct EdgeIter<'a, G: Graph + 'a> {
+  inner: G::EdgeIter<'a>,
+  subgraph: &'a Subgraph<'a, G>,
+}
+
+impl<'a, G: Graph + 'a> Iterator for EdgeIter<'a, G> {
+  type Item = (G::EdgeId, &'a G::EdgeData);
+  fn next(&mut self) -> Option<Self::Item> {
+    self
+      .inner
+      .find(|(edge_id, _)| self.subgraph.contains_edge(*edge_id))
+  }
+}
+
+impl<'g, G: Graph> Graph for Subgraph<'g, G> {
+  type NodeId = G::NodeId;
+  type NodeData = G::NodeData;
+
+  type EdgeId = G::EdgeId;
+  type EdgeData = G::EdgeData;
+
+  type NodeIter<'a> = NodeIter<'a, G> where Self: 'a;
+
+  type EdgeIter<'a> = EdgeIter<'a, G> where Self: 'a;
+
+  fn iter_nodes(&self) -> Self::NodeIter<'_> {
+    NodeIter {
+      inner: self.graph.iter_nodes(),
+      nodes: self.nodes,
+    }
+  }
+
+  fn iter_edges(&self) -> Self::EdgeIter<'_> {
+    EdgeIter {
+      inner: self.graph.iter_edges(),
+      subgraph: self,
+    }
+  }
+
+  /// Iterate edges incident to the node
+  fn iter_incidents(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_> {
+    EdgeIter {
+      inner: self.graph.iter_incidents(node_id),
+      subgraph: self,
+    }
+  }
+
+  /// Iterate edges coming into the node
+  fn iter_in_edges(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_> {
+    EdgeIter {
+      inner: self.graph.iter_in_edges(node_id),
+      subgraph: self,
+    }
+  }
+
+  /// Iterate edges going out of the node
+  fn iter_out_edges(&self, node_id: Self::NodeId) -> Self::EdgeIter<'_> {
+    EdgeIter {
+      inner: self.graph.iter_out_edges(node_id),
+      subgraph: self,
+    }
+  }
+
+  fn get_node(&self, id: Self::NodeId) -> &Self::NodeData {
+    self.graph.get_node(id)
+  }
+
+  fn get_edge(&self, id: Self::EdgeId) -> &Self::EdgeData {
+    self.graph.get_edge(id)
+  }
+
+  /// Get incident nodes of the edge
+  fn get_edge_incidents(&self, edge_id: Self::EdgeId) -> EdgeIncidents<Self::NodeId> {
+    self.graph.get_edge_incidents(edge_id)
+  }
+}
@@ -1,3 +1,4 @@
+use crate::lib::graph::{EdgeIncidents, Graph};
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
@@ -20,3 +21,82 @@ pub fn with_test_dir<T>(f: impl Fn(&Path) -> io::Result<T>) -> io::Result<T> {
   std::fs::remove_dir_all(tmp_dir).expect("test: can't remove");
   value
 }
+
+/// Minimal graph for testing graph algorithms: nodes are `0..node_count`,
+/// edges are numbered in the order they were given
+pub struct TestGraph {
+  nodes: Vec<()>,
+  edges: Vec<(usize, usize)>,
+  edge_data: Vec<()>,
+}
+
+impl TestGraph {
+  pub fn new(node_count: usize, edges: &[(usize, usize)]) -> Self {
+    Self {
+      nodes: vec![(); node_count],
+      edges: edges.to_vec(),
+      edge_data: vec![(); edges.len()],
+    }
+  }
+
+  fn edges_where<'a>(
+    &'a self,
+    predicate: impl Fn(usize, usize) -> bool + 'a,
+  ) -> Box<dyn Iterator<Item = (usize, &'a ())> + 'a> {
+    Box::new(
+      self
+        .edge_data
+        .iter()
+        .enumerate()
+        .filter(move |(edge_id, _)| {
+          let (from, to) = self.edges[*edge_id];
+          predicate(from, to)
+        }),
+    )
+  }
+}
+
+impl Graph for TestGraph {
+  type NodeId = usize;
+  type NodeData = ();
+
+  type EdgeId = usize;
+  type EdgeData = ();
+
+  type NodeIter<'a> = std::iter::Enumerate<std::slice::Iter<'a, ()>>;
+
+  type EdgeIter<'a> = Box<dyn Iterator<Item = (usize, &'a ())> + 'a>;
+
+  fn iter_nodes(&self) -> Self::NodeIter<'_> {
+    self.nodes.iter().enumerate()
+  }
+
+  fn iter_edges(&self) -> Self::EdgeIter<'_> {
+    Box::new(self.edge_data.iter().enumerate())
+  }
+
+  fn iter_incidents(&self, node_id: usize) -> Self::EdgeIter<'_> {
+    self.edges_where(move |from, to| from == node_id || to == node_id)
+  }
+
+  fn iter_in_edges(&self, node_id: usize) -> Self::EdgeIter<'_> {
+    self.edges_where(move |_, to| to == node_id)
+  }
+
+  fn iter_out_edges(&self, node_id: usize) -> Self::EdgeIter<'_> {
+    self.edges_where(move |from, _| from == node_id)
+  }
+
+  fn get_node(&self, id: usize) -> &() {
+    &self.nodes[id]
+  }
+
+  fn get_edge(&self, id: usize) -> &() {
+    &self.edge_data[id]
+  }
+
+  fn get_edge_incidents(&self, edge_id: usize) -> EdgeIncidents<usize> {
+    let (node_from, node_to) = self.edges[edge_id];
+    EdgeIncidents { node_from, node_to }
+  }
+}
@@ -6,6 +6,7 @@
 const APP_TITLE: &str = "Basalt";
 
 use basalt::BasaltApp;
+use std::path::PathBuf;
 
 fn main() -> Result<(), eframe::Error> {
   {
@@ -21,6 +22,16 @@ fn main() -> Result<(), eframe::Error> {
 
   env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+  // Commands run without opening a window
+  let args = std::env::args_os().skip(1).collect::<Vec<_>>();
+  if let Some(result) = basalt::run_command(&args) {
+    if let Err(error) = result {
+      eprintln!("basalt: {error}");
+      std::process::exit(1);
+    }
+    return Ok(());
+  }
+
   let options = eframe::NativeOptions {
     drag_and_drop_support: true,
     initial_window_size: Some([1280.0, 1024.0].into()),
@@ -39,6 +50,10 @@ fn main() -> Result<(), eframe::Error> {
     // NOTE: one of the things that you can do is to implement
     // and assign set custom `Storage`:
     // https://discord.com/channels/900275882684477440/904461220592119849/1128120151137583155
-    Box::new(|_creation_context| Box::<BasaltApp>::default()),
+    Box::new(|_creation_context| {
+      // Notes directory or a graph file to import can be given as the first argument
+      let notes_directory = std::env::args_os().nth(1).map(PathBuf::from);
+      Box::new(BasaltApp::new(notes_directory))
+    }),
   )
 }